@@ -0,0 +1,93 @@
+//! Compares rational::gcd against plain Euclid on the kinds of numbers compute_pi and compute_euler produce
+//! Run with `cargo bench --bench gcd`
+
+use std::time::{Duration, Instant};
+
+use num_bigint::BigUint;
+
+use Neoprene_Byzantine::rational::gcd;
+
+/// The gcd the crate used before, kept here as the baseline
+fn euclid_gcd(a_r: &BigUint, b_r: &BigUint) -> BigUint {
+    if *a_r==*b_r {
+        return a_r.clone();
+    }
+
+    let mut a = a_r.clone();
+    let mut b = b_r.clone();
+
+    if b > a {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    while b > BigUint::ZERO {
+        let mut temp = a;
+        temp %= &b;
+        a = b;
+        b = temp;
+    }
+
+    return a;
+}
+
+/// Partial sums of the Gregory-Leibniz style series in compute_pi, where every term has a denominator of 2n(2n+1)(2n+2)
+fn pi_pairs() -> Vec<(BigUint, BigUint)> {
+    let mut pairs = Vec::new();
+    let mut numer = BigUint::from(3_u8);
+    let mut denom = BigUint::from(1_u8);
+
+    for n in 1..300_u32 {
+        let term_denom = BigUint::from(2 * n) * (2 * n + 1) * (2 * n + 2);
+
+        numer = &numer * &term_denom + &denom * 4_u8;
+        denom *= &term_denom;
+        pairs.push((numer.clone(), denom.clone()));
+    }
+
+    return pairs;
+}
+
+/// Partial sums of sum(1/n!) as in compute_euler
+fn euler_pairs() -> Vec<(BigUint, BigUint)> {
+    let mut pairs = Vec::new();
+    let mut numer = BigUint::from(2_u8);
+    let mut denom = BigUint::from(1_u8);
+
+    for n in 2..300_u32 {
+        numer = &numer * n + 1_u8;
+        denom *= n;
+        pairs.push((numer.clone(), denom.clone()));
+    }
+
+    return pairs;
+}
+
+/// Small values like the coefficients in typical expressions and denominators limited to 6091
+fn small_pairs() -> Vec<(BigUint, BigUint)> {
+    return (1..20000_u64).map(|i| (BigUint::from(i * 6091), BigUint::from(i * i + 17))).collect();
+}
+
+fn time(f: fn(&BigUint, &BigUint) -> BigUint, pairs: &[(BigUint, BigUint)]) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..5 {
+        for pair in pairs {
+            std::hint::black_box(f(&pair.0, &pair.1));
+        }
+    }
+
+    return start.elapsed();
+}
+
+fn main() {
+    for (name, pairs) in [("compute_pi", pi_pairs()), ("compute_euler", euler_pairs()), ("small", small_pairs())] {
+        for pair in &pairs {
+            assert_eq!(gcd(&pair.0, &pair.1), euclid_gcd(&pair.0, &pair.1));
+        }
+
+        let euclid = time(euclid_gcd, &pairs);
+        let current = time(gcd, &pairs);
+
+        println!("{:>14}: euclid {:>12?}  gcd {:>12?}  ({:.2}x)", name, euclid, current, euclid.as_secs_f64() / current.as_secs_f64());
+    }
+}
@@ -0,0 +1,199 @@
+//! Generators for property testing code built on this crate
+//! Values are kept small on purpose (numerators below 2^20, denominators up to 1000, trees a few levels deep),
+//! As those find the same sign and ordering bugs as huge values while keeping every test case fast to evaluate
+
+use num_bigint::BigUint;
+
+use crate::byzantine::TransitiveConsts;
+use crate::rational::{Rational, Sign};
+use crate::rational_range::RationalRange;
+
+/// The leaves besides rationals
+const CONSTANTS: [TransitiveConsts; 7] = [
+    TransitiveConsts::Pi, TransitiveConsts::Euler, TransitiveConsts::Ln2, TransitiveConsts::GoldenRatio,
+    TransitiveConsts::EulerMascheroni, TransitiveConsts::Catalan, TransitiveConsts::Apery
+];
+
+/// Exponents that keep evaluation cheap, including roots and negative powers
+/// (So trees can contain things like a negative base under a square root, which evaluation reports as an error)
+const EXPONENTS: [(isize, isize); 7] = [(-2, 1), (-1, 1), (1, 2), (1, 3), (2, 1), (3, 1), (-1, 2)];
+
+fn rational_from_parts(negative: bool, numer: u32, denom: u32) -> Rational {
+    let sign = if negative && numer != 0 { Sign::Neg } else { Sign::Pos };
+
+    return Rational::new(sign, BigUint::from(numer), BigUint::from(denom));
+}
+
+fn range_from_pair(a: Rational, b: Rational) -> RationalRange {
+    if a > b {
+        return RationalRange::from((b, a));
+    }
+    return RationalRange::from((a, b));
+}
+
+#[cfg(feature = "proptest")]
+pub use self::proptest_impls::*;
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use proptest::prelude::*;
+
+    use crate::byzantine::ByzNode;
+    use crate::generate_byznode_utils as GBU;
+    use crate::rational::Rational;
+    use crate::rational_range::RationalRange;
+
+    use super::{range_from_pair, rational_from_parts, CONSTANTS, EXPONENTS};
+
+    pub fn rational_strategy() -> impl Strategy<Value = Rational> {
+        return (any::<bool>(), 0..(1_u32 << 20), 1..=1000_u32).prop_map(|x| rational_from_parts(x.0, x.1, x.2));
+    }
+
+    pub fn rational_range_strategy() -> impl Strategy<Value = RationalRange> {
+        return (rational_strategy(), rational_strategy()).prop_map(|x| range_from_pair(x.0, x.1));
+    }
+
+    fn exponent_strategy() -> impl Strategy<Value = Rational> {
+        return prop::sample::select(&EXPONENTS[..]).prop_map(Rational::from);
+    }
+
+    /// Trees of Add, Mul and Pow over rationals and transitive constants, at most depth levels deep
+    pub fn byznode_strategy(depth: u32) -> BoxedStrategy<ByzNode> {
+        let leaf = prop_oneof![
+            3 => rational_strategy().prop_map(GBU::rational),
+            1 => prop::sample::select(&CONSTANTS[..]).prop_map(GBU::transitive)
+        ];
+
+        return leaf.prop_recursive(depth, 16, 3, |inner| {
+            prop_oneof![
+                (rational_strategy(), prop::collection::vec((proptest::option::of(rational_strategy()), inner.clone()), 1..4))
+                    .prop_map(|x| GBU::add(x.0, x.1)),
+                (proptest::option::of(rational_strategy()), prop::collection::vec((proptest::option::of(exponent_strategy()), inner.clone()), 1..3))
+                    .prop_map(|x| GBU::mul(x.0, x.1)),
+                (inner, exponent_strategy())
+                    .prop_map(|x| GBU::pow(x.0, x.1))
+            ]
+        }).boxed();
+    }
+
+    impl Arbitrary for Rational {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Rational>;
+
+        fn arbitrary_with(_: ()) -> BoxedStrategy<Rational> {
+            return rational_strategy().boxed();
+        }
+    }
+
+    impl Arbitrary for RationalRange {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<RationalRange>;
+
+        fn arbitrary_with(_: ()) -> BoxedStrategy<RationalRange> {
+            return rational_range_strategy().boxed();
+        }
+    }
+
+    /// The parameter is the maximum depth of the tree
+    impl Arbitrary for ByzNode {
+        type Parameters = u32;
+        type Strategy = BoxedStrategy<ByzNode>;
+
+        fn arbitrary_with(depth: u32) -> BoxedStrategy<ByzNode> {
+            return byznode_strategy(if depth == 0 { 3 } else { depth });
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impls {
+    use quickcheck::{Arbitrary, Gen};
+
+    use crate::byzantine::{ByzNode, ByzRc};
+    use crate::generate_byznode_utils as GBU;
+    use crate::rational::Rational;
+    use crate::rational_range::RationalRange;
+
+    use super::{range_from_pair, rational_from_parts, CONSTANTS, EXPONENTS};
+
+    /// Trees are at most this many levels deep, the same as the default for the proptest strategy
+    const MAX_DEPTH: u32 = 3;
+
+    fn exponent(g: &mut Gen) -> Rational {
+        return Rational::from(*g.choose(&EXPONENTS).unwrap());
+    }
+
+    /// Some of the coefficients and powers are left out, like the None the proptest strategy sometimes picks
+    fn maybe<F>(g: &mut Gen, f: F) -> Option<Rational> where F: Fn(&mut Gen) -> Rational {
+        if bool::arbitrary(g) {
+            return Some(f(g));
+        }
+        return None;
+    }
+
+    /// Add, Mul and Pow over rationals and transitive constants, like byznode_strategy
+    fn byznode(g: &mut Gen, depth: u32) -> ByzNode {
+        if depth == 0 || u8::arbitrary(g) % 3 == 0 {
+            if u8::arbitrary(g) % 4 == 0 {
+                return GBU::transitive(*g.choose(&CONSTANTS).unwrap());
+            }
+            return GBU::rational(Rational::arbitrary(g));
+        }
+
+        match u8::arbitrary(g) % 3 {
+            0 => {
+                let terms = (0..(usize::arbitrary(g) % 3 + 1)).map(|_| (maybe(g, Rational::arbitrary), byznode(g, depth - 1))).collect();
+                return GBU::add(Rational::arbitrary(g), terms);
+            },
+            1 => {
+                let factors = (0..(usize::arbitrary(g) % 2 + 1)).map(|_| (maybe(g, exponent), byznode(g, depth - 1))).collect();
+                return GBU::mul(maybe(g, Rational::arbitrary), factors);
+            },
+            _ => {
+                return GBU::pow(byznode(g, depth - 1), exponent(g));
+            }
+        }
+    }
+
+    impl Arbitrary for Rational {
+        fn arbitrary(g: &mut Gen) -> Rational {
+            return rational_from_parts(bool::arbitrary(g), u32::arbitrary(g) % (1_u32 << 20), u32::arbitrary(g) % 1000 + 1);
+        }
+
+        /// Shrinks towards zero and then towards smaller denominators
+        fn shrink(&self) -> Box<dyn Iterator<Item = Rational>> {
+            let negative = self.is_negative();
+            let numer = u32::try_from(&*self.numer()).unwrap_or(u32::MAX);
+            let denom = u32::try_from(&*self.denom()).unwrap_or(u32::MAX);
+
+            return Box::new((numer, denom).shrink().filter(|x| x.1 != 0).map(move |x| rational_from_parts(negative, x.0, x.1)));
+        }
+    }
+
+    impl Arbitrary for RationalRange {
+        fn arbitrary(g: &mut Gen) -> RationalRange {
+            return range_from_pair(Rational::arbitrary(g), Rational::arbitrary(g));
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = RationalRange>> {
+            return Box::new((self.min.clone(), self.max.clone()).shrink().map(|x| range_from_pair(x.0, x.1)));
+        }
+    }
+
+    /// Small trees of Add, Mul and Pow over rationals and transitive constants, at most MAX_DEPTH levels deep
+    impl Arbitrary for ByzNode {
+        fn arbitrary(g: &mut Gen) -> ByzNode {
+            return byznode(g, MAX_DEPTH);
+        }
+
+        /// Shrinks to each of the children, and a rational to smaller rationals
+        fn shrink(&self) -> Box<dyn Iterator<Item = ByzNode>> {
+            if let ByzNode::Rational { rational } = self {
+                return Box::new(rational.shrink().map(GBU::rational));
+            }
+
+            let children: Vec<ByzNode> = self.children().into_iter().map(|x| ByzRc::as_ref(x).clone()).collect();
+            return Box::new(children.into_iter());
+        }
+    }
+}
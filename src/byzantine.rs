@@ -1,17 +1,38 @@
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::OnceLock;
 
 use crate::rational::Rational;
+use crate::rational_polynomial::RationalPolynomial;
 use crate::byznode_sorted_vec::ByzNodeCoefficientAddVec;
 use crate::byznode_sorted_vec::ByzNodePowerMulVec;
+use crate::byznode_sorted_vec::ByzNodeVec;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+/// The pointer ByzNode holds its children in, Arc with the sync feature so trees can be shared with other threads
+#[cfg(not(feature = "sync"))]
+pub type ByzRc<T> = std::rc::Rc<T>;
+#[cfg(feature = "sync")]
+pub type ByzRc<T> = std::sync::Arc<T>;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransitiveConsts {
     Pi,
-    Euler
+    Euler,
+    Ln2,
+    GoldenRatio,
+    EulerMascheroni,
+    Catalan,
+    /// zeta(3)
+    Apery
 }
 
+/// Clone is cheap, the copy shares its children with the original (see deep_clone for a copy that doesn't)
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::byzantine_serde::ByzNodeRepr", into = "crate::byzantine_serde::ByzNodeRepr"))]
 pub enum ByzNode {
     Rational {
         rational: Rational
@@ -26,98 +47,424 @@ pub enum ByzNode {
         products: ByzNodePowerMulVec
     },
     Pow{
-        base: Rc<ByzNode>, 
-        exp: Rational
+        base: ByzRc<ByzNode>, 
+        exp: Rational,
+        /// Filled in by structural_hash, new nodes start out with OnceLock::new() (every variant with children has one, except Add and Mul which keep it in their sorted vecs)
+        hash_cache: OnceLock<u64>
+    },
+    Exp{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// Natural logarithm, evaluation fails if arg can't be shown to be positive
+    Ln{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Sin{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Cos{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// Evaluation fails if arg may contain a pole
+    Tan{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Arctan{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// Evaluation fails if arg may be outside [-1, 1]
+    Arcsin{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// Evaluation fails if arg may be outside [-1, 1]
+    Arccos{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Sinh{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Cosh{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Tanh{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    Abs{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// args is sorted and has no duplicates, so Min and Max nodes with the same arguments are equal however they were built
+    Min{
+        args: Vec<ByzRc<ByzNode>>,
+        hash_cache: OnceLock<u64>
+    },
+    /// See Min
+    Max{
+        args: Vec<ByzRc<ByzNode>>,
+        hash_cache: OnceLock<u64>
+    },
+    /// Evaluation refines arg until its integer part is known, failing with NeopreneError::IndeterminateInteger if it never is
+    /// (Which always happens when arg is exactly an integer but isn't evaluated as a single point)
+    Floor{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// See Floor
+    Ceil{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// arg - floor(arg), see Floor
+    Frac{
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// Logarithm of arg to the given base, evaluation fails if either can't be shown to be positive or base may be 1
+    Log{
+        base: ByzRc<ByzNode>,
+        arg: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// A named unknown, replaced by a value with ByzNode::substitute
+    /// Evaluation fails with NeopreneError::UnboundVariable while any are left
+    Variable{
+        name: String
+    },
+    /// base^exp for an exponent that isn't rational, evaluated as exp(exp ln(base)) so base has to be shown to be positive
+    /// (When exp does evaluate to a single rational, it takes the same path as Pow and any base that Pow allows is fine)
+    RealPow{
+        base: ByzRc<ByzNode>,
+        exp: ByzRc<ByzNode>,
+        hash_cache: OnceLock<u64>
+    },
+    /// The index-th smallest real root (counting from 0) of polynomial, for algebraic numbers that aren't radicals, like the plastic number
+    /// polynomial is square free and monic (see generate_byznode_utils::algebraic_root) but isn't reduced any further, so one number can have several polynomials
+    /// Evaluation isolates the root with a Sturm sequence and then bisects, failing with NeopreneError::MissingRoot if polynomial doesn't have that many real roots
+    AlgebraicRoot{
+        polynomial: RationalPolynomial,
+        index: usize
     }
 }
 
 impl PartialEq for ByzNode {
+    /// Two nodes whose hashes are already cached and differ can't be equal, which settles the near misses in a HashMap without walking both trees
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (ByzNode::Rational { rational }, ByzNode::Rational { rational: rational_other }) => {
-                return *rational == *rational_other;
-            },
-            (ByzNode::TransitiveConst { transitive_const }, ByzNode::TransitiveConst { transitive_const: transitive_conts_other }) => {
-                return *transitive_const == *transitive_conts_other;
-            },
-            (ByzNode::Add { addends }, ByzNode::Add { addends: addends_other }) => {
-                return *addends == *addends_other;
-            },
-            (ByzNode::Mul { products }, ByzNode::Mul { products: products_other }) => {
-                return *products == *products_other;
-            },
-            (ByzNode::Pow { base, exp }, ByzNode::Pow { base: base_other, exp: exp_other }) => {
-                return *exp == *exp_other && *base.as_ref() == *base_other.as_ref();
-            }
-            _ => {
-                return false;
-            }
+        if let (Some(a), Some(b)) = (self.known_hash(), other.known_hash()) && a != b {
+            return false;
         }
+        return self.cmp(other) == Ordering::Equal;
     }
 }
 
 impl Eq for ByzNode {}
 
+/// Consistent with Eq, as it hashes exactly what eq compares (see structural_hash)
+impl Hash for ByzNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.structural_hash());
+    }
+}
+
 impl PartialOrd for ByzNode {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         return Some(self.cmp(other));
     }
 }
 
+/// A comparison cmp still has to make, in the order they're popped
+enum PendingCmp<'a> {
+    Nodes(&'a ByzNode, &'a ByzNode),
+    Rationals(&'a Rational, &'a Rational),
+    Done(Ordering)
+}
+
+/// Pushes the comparisons of two Add or Mul vecs, which is the order their own Ord uses (the items pairwise, coefficient first, then the lengths, then the rational parts)
+fn push_vec_cmp<'a, T>(stack: &mut Vec<PendingCmp<'a>>, vec: &'a T, other: &'a T) where T: ByzNodeVec {
+    stack.push(PendingCmp::Rationals(vec.get_rational_part(), other.get_rational_part()));
+    stack.push(PendingCmp::Done(vec.get_vec().len().cmp(&other.get_vec().len())));
+
+    for (x, y) in vec.get_vec().iter().zip(other.get_vec()).rev() {
+        stack.push(PendingCmp::Nodes(&x.1, &y.1));
+        stack.push(PendingCmp::Rationals(&x.0, &y.0));
+    }
+}
+
+/// Compares the notation, first by type and then by the contents of each type
+/// Nodes are compared off an explicit stack rather than by recursion, so arbitrarily deep trees can't overflow it
 impl Ord for ByzNode {
     fn cmp(&self, other: &ByzNode) -> Ordering {
-        match (self, other) {
-            (ByzNode::Rational { rational }, ByzNode::Rational { rational: rational_other }) => {
-                return rational.cmp(rational_other);
-            },
-            (ByzNode::TransitiveConst { transitive_const }, ByzNode::TransitiveConst { transitive_const: transitive_const_other }) => {
-                return transitive_const.cmp(transitive_const_other);
-            },
-            (ByzNode::Add { addends }, ByzNode::Add { addends: addends_other }) => {
-                return addends.cmp(addends_other);
-            },
-            (ByzNode::Mul { products }, ByzNode::Mul { products: products_other }) => {
-                return products.cmp(products_other);
-            },
-            (ByzNode::Pow { base, exp }, ByzNode::Pow { base: base_other, exp: exp_other }) => {
-                let base_cmp = base.cmp(base_other);
+        let mut stack = vec![PendingCmp::Nodes(self, other)];
 
-                match base_cmp {
-                    Ordering::Equal => {
-                        return exp.cmp(exp_other);
+        while let Some(pending) = stack.pop() {
+            let (a, b) = match pending {
+                PendingCmp::Nodes(a, b) => (a, b),
+                PendingCmp::Rationals(a, b) => {
+                    match a.cmp(b) {
+                        Ordering::Equal => {
+                            continue;
+                        },
+                        ordering => {
+                            return ordering;
+                        }
                     }
-                    _ => {
-                        return base_cmp;
+                },
+                PendingCmp::Done(Ordering::Equal) => {
+                    continue;
+                },
+                PendingCmp::Done(ordering) => {
+                    return ordering;
+                }
+            };
+
+            // Shared subtrees are equal without looking inside them
+            if std::ptr::eq(a, b) {
+                continue;
+            }
+
+            match (a, b) {
+                (ByzNode::Rational { rational }, ByzNode::Rational { rational: rational_other }) => {
+                    stack.push(PendingCmp::Rationals(rational, rational_other));
+                },
+                (ByzNode::TransitiveConst { transitive_const }, ByzNode::TransitiveConst { transitive_const: transitive_const_other }) => {
+                    stack.push(PendingCmp::Done(transitive_const.cmp(transitive_const_other)));
+                },
+                (ByzNode::Add { addends }, ByzNode::Add { addends: addends_other }) => {
+                    push_vec_cmp(&mut stack, addends, addends_other);
+                },
+                (ByzNode::Mul { products }, ByzNode::Mul { products: products_other }) => {
+                    push_vec_cmp(&mut stack, products, products_other);
+                },
+                (ByzNode::Pow { base, exp, .. }, ByzNode::Pow { base: base_other, exp: exp_other, .. }) => {
+                    stack.push(PendingCmp::Rationals(exp, exp_other));
+                    stack.push(PendingCmp::Nodes(base, base_other));
+                },
+                (ByzNode::Min { args, .. }, ByzNode::Min { args: args_other, .. }) | (ByzNode::Max { args, .. }, ByzNode::Max { args: args_other, .. }) => {
+                    stack.push(PendingCmp::Done(args.len().cmp(&args_other.len())));
+                    for (x, y) in args.iter().zip(args_other).rev() {
+                        stack.push(PendingCmp::Nodes(x, y));
+                    }
+                },
+                (ByzNode::Variable { name }, ByzNode::Variable { name: name_other }) => {
+                    stack.push(PendingCmp::Done(name.cmp(name_other)));
+                },
+                (ByzNode::AlgebraicRoot { polynomial, index }, ByzNode::AlgebraicRoot { polynomial: polynomial_other, index: index_other }) => {
+                    stack.push(PendingCmp::Done(polynomial.coefficients().cmp(polynomial_other.coefficients()).then_with(|| index.cmp(index_other))));
+                },
+                _ if a.basic_type_eq(b) => {
+                    // Everything else is made of nothing but its children, which are compared in order
+                    let children = a.children();
+                    let children_other = b.children();
+                    for (x, y) in children.into_iter().zip(children_other).rev() {
+                        stack.push(PendingCmp::Nodes(x, y));
                     }
+                },
+                _ => {
+                    // The types differ, use identifying type id
+                    return a.to_identifying_type_int().cmp(&b.to_identifying_type_int());
                 }
-            },
-            _ => {
-                // The types differ, use identifying type id
-                return self.to_identifying_type_int().cmp(&other.to_identifying_type_int());
             }
         }
+
+        return Ordering::Equal;
+    }
+}
+
+/// A piece of Debug output still to be written, in the order they're popped
+enum PendingDebug<'a> {
+    Node(&'a ByzNode),
+    Rational(&'a Rational),
+    Str(&'a str)
+}
+
+/// Pushes the items of an Add or Mul vec between separators, item writes one (coefficient, node) pair in reverse
+fn push_vec_debug<'a, T, F>(stack: &mut Vec<PendingDebug<'a>>, vec: &'a T, separator: &'static str, mut item: F) where T: ByzNodeVec, F: FnMut(&mut Vec<PendingDebug<'a>>, &'a Rational, &'a ByzNode) {
+    for (i, x) in vec.get_vec().iter().enumerate().rev() {
+        item(stack, &x.0, &x.1);
+        if i != 0 {
+            stack.push(PendingDebug::Str(separator));
+        }
     }
 }
 
+/// Written off an explicit stack rather than by recursion, so arbitrarily deep trees can't overflow it
 impl Debug for ByzNode {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> { 
-        match self {
-            ByzNode::Rational { rational } => {
-                return write!(f, "{:?}", rational);
-            },
-            ByzNode::TransitiveConst { transitive_const } => {
-                return write!(f, "{:?}", transitive_const);
-            },
-            ByzNode::Add { addends } => {
-                return write!(f, "({:?})", addends);
-            },
-            ByzNode::Mul { products } => {
-                return write!(f, "({:?})", products);
-            },
-            ByzNode::Pow { base, exp } => {
-                return write!(f, "{:?}^{:?}", base, exp);
+        let mut stack = vec![PendingDebug::Node(self)];
+
+        while let Some(pending) = stack.pop() {
+            let node = match pending {
+                PendingDebug::Node(node) => node,
+                PendingDebug::Rational(rational) => {
+                    write!(f, "{:?}", rational)?;
+                    continue;
+                },
+                PendingDebug::Str(s) => {
+                    f.write_str(s)?;
+                    continue;
+                }
+            };
+
+            // What comes before the children is written straight away, what comes after them is pushed first
+            match node {
+                ByzNode::Rational { rational } => {
+                    write!(f, "{:?}", rational)?;
+                },
+                ByzNode::TransitiveConst { transitive_const } => {
+                    write!(f, "{:?}", transitive_const)?;
+                },
+                ByzNode::Add { addends } => {
+                    f.write_str("(")?;
+                    if !addends.get_rational_part().is_zero() {
+                        write!(f, "{:?} + ", addends.get_rational_part())?;
+                    }
+
+                    stack.push(PendingDebug::Str(")"));
+                    push_vec_debug(&mut stack, addends, " + ", |stack, coefficient, x| {
+                        stack.push(PendingDebug::Node(x));
+                        if *coefficient != 1 {
+                            stack.push(PendingDebug::Str("*"));
+                            stack.push(PendingDebug::Rational(coefficient));
+                        }
+                    });
+                },
+                ByzNode::Mul { products } => {
+                    f.write_str("(")?;
+                    if !products.get_rational_part().is_one() {
+                        write!(f, "{:?} * ", products.get_rational_part())?;
+                    }
+
+                    stack.push(PendingDebug::Str(")"));
+                    push_vec_debug(&mut stack, products, " * ", |stack, power, x| {
+                        if *power != 1 {
+                            stack.push(PendingDebug::Rational(power));
+                            stack.push(PendingDebug::Str("^"));
+                        }
+                        stack.push(PendingDebug::Node(x));
+                    });
+                },
+                ByzNode::Pow { base, exp, .. } => {
+                    stack.push(PendingDebug::Rational(exp));
+                    stack.push(PendingDebug::Str("^"));
+                    stack.push(PendingDebug::Node(base));
+                },
+                ByzNode::Abs { arg, .. } => {
+                    f.write_str("|")?;
+                    stack.push(PendingDebug::Str("|"));
+                    stack.push(PendingDebug::Node(arg));
+                },
+                ByzNode::Log { base, arg, .. } => {
+                    f.write_str("log_")?;
+                    stack.push(PendingDebug::Str(")"));
+                    stack.push(PendingDebug::Node(arg));
+                    stack.push(PendingDebug::Str("("));
+                    stack.push(PendingDebug::Node(base));
+                },
+                ByzNode::Variable { name } => {
+                    f.write_str(name)?;
+                },
+                ByzNode::AlgebraicRoot { polynomial, index } => {
+                    write!(f, "root_{}({})", index, polynomial)?;
+                },
+                ByzNode::RealPow { base, exp, .. } => {
+                    f.write_str("(")?;
+                    stack.push(PendingDebug::Str(")"));
+                    stack.push(PendingDebug::Node(exp));
+                    stack.push(PendingDebug::Str(")^("));
+                    stack.push(PendingDebug::Node(base));
+                },
+                _ => {
+                    // Plain functions, name(arg) or name(arg, arg, ...)
+                    let name = match node {
+                        ByzNode::Exp { .. } => "exp",
+                        ByzNode::Ln { .. } => "ln",
+                        ByzNode::Sin { .. } => "sin",
+                        ByzNode::Cos { .. } => "cos",
+                        ByzNode::Tan { .. } => "tan",
+                        ByzNode::Arctan { .. } => "arctan",
+                        ByzNode::Arcsin { .. } => "arcsin",
+                        ByzNode::Arccos { .. } => "arccos",
+                        ByzNode::Sinh { .. } => "sinh",
+                        ByzNode::Cosh { .. } => "cosh",
+                        ByzNode::Tanh { .. } => "tanh",
+                        ByzNode::Min { .. } => "min",
+                        ByzNode::Max { .. } => "max",
+                        ByzNode::Floor { .. } => "floor",
+                        ByzNode::Ceil { .. } => "ceil",
+                        _ => "frac"
+                    };
+                    write!(f, "{}(", name)?;
+
+                    stack.push(PendingDebug::Str(")"));
+                    for (i, x) in node.children().into_iter().enumerate().rev() {
+                        stack.push(PendingDebug::Node(x));
+                        if i != 0 {
+                            stack.push(PendingDebug::Str(", "));
+                        }
+                    }
+                }
             }
         }
+
+        return Ok(());
+    }
+}
+
+/// Moves node's children that nothing else points to (and that have children of their own) out into stack, leaving a leaf in their place
+fn detach_unique_children(node: &mut ByzNode, stack: &mut Vec<ByzNode>) {
+    let mut detach = |x: &mut ByzRc<ByzNode>| {
+        if let Some(child) = ByzRc::get_mut(x) && child.has_children() {
+            stack.push(std::mem::replace(child, ByzNode::TransitiveConst { transitive_const: TransitiveConsts::Pi }));
+        }
+    };
+
+    match node {
+        ByzNode::Rational { .. } | ByzNode::TransitiveConst { .. } | ByzNode::Variable { .. } | ByzNode::AlgebraicRoot { .. } => {},
+        ByzNode::Add { addends } => {
+            addends.get_vec_mut().iter_mut().for_each(|x| detach(&mut x.1));
+        },
+        ByzNode::Mul { products } => {
+            products.get_vec_mut().iter_mut().for_each(|x| detach(&mut x.1));
+        },
+        ByzNode::Min { args, .. } | ByzNode::Max { args, .. } => {
+            args.iter_mut().for_each(detach);
+        },
+        ByzNode::Log { base, arg, .. } => {
+            detach(base);
+            detach(arg);
+        },
+        ByzNode::RealPow { base, exp, .. } => {
+            detach(base);
+            detach(exp);
+        },
+        ByzNode::Pow { base: arg, .. } | ByzNode::Exp { arg, .. } | ByzNode::Ln { arg, .. } | ByzNode::Sin { arg, .. } | ByzNode::Cos { arg, .. } | ByzNode::Tan { arg, .. } |
+        ByzNode::Arctan { arg, .. } | ByzNode::Arcsin { arg, .. } | ByzNode::Arccos { arg, .. } | ByzNode::Sinh { arg, .. } | ByzNode::Cosh { arg, .. } | ByzNode::Tanh { arg, .. } |
+        ByzNode::Abs { arg, .. } | ByzNode::Floor { arg, .. } | ByzNode::Ceil { arg, .. } | ByzNode::Frac { arg, .. } => {
+            detach(arg);
+        }
+    }
+}
+
+/// The default drop would recurse once per level, which overflows the stack on deep trees (like long generated chains of Pow)
+/// Instead every subtree this node owns alone is moved onto a heap stack and taken apart one node at a time, shared subtrees just lose a reference as usual
+impl Drop for ByzNode {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        detach_unique_children(self, &mut stack);
+
+        while let Some(mut node) = stack.pop() {
+            detach_unique_children(&mut node, &mut stack);
+        }
     }
 }
 
@@ -138,6 +485,69 @@ impl ByzNode {
             },
             ByzNode::Pow { .. } => {
                 return 4;
+            },
+            ByzNode::Exp { .. } => {
+                return 5;
+            },
+            ByzNode::Ln { .. } => {
+                return 6;
+            },
+            ByzNode::Sin { .. } => {
+                return 7;
+            },
+            ByzNode::Cos { .. } => {
+                return 8;
+            },
+            ByzNode::Tan { .. } => {
+                return 9;
+            },
+            ByzNode::Arctan { .. } => {
+                return 10;
+            },
+            ByzNode::Arcsin { .. } => {
+                return 11;
+            },
+            ByzNode::Arccos { .. } => {
+                return 12;
+            },
+            ByzNode::Sinh { .. } => {
+                return 13;
+            },
+            ByzNode::Cosh { .. } => {
+                return 14;
+            },
+            ByzNode::Tanh { .. } => {
+                return 15;
+            },
+            ByzNode::Abs { .. } => {
+                return 16;
+            },
+            ByzNode::Min { .. } => {
+                return 17;
+            },
+            ByzNode::Max { .. } => {
+                return 18;
+            },
+            ByzNode::Floor { .. } => {
+                return 19;
+            },
+            ByzNode::Ceil { .. } => {
+                return 20;
+            },
+            ByzNode::Frac { .. } => {
+                return 21;
+            },
+            ByzNode::Log { .. } => {
+                return 22;
+            },
+            ByzNode::Variable { .. } => {
+                return 23;
+            },
+            ByzNode::RealPow { .. } => {
+                return 24;
+            },
+            ByzNode::AlgebraicRoot { .. } => {
+                return 25;
             }
         }
     }
@@ -146,23 +556,339 @@ impl ByzNode {
         return self.to_identifying_type_int() == other.to_identifying_type_int();
     }
 
-    pub fn deep_clone(&self) -> ByzNode {
+    /// A hash of the node's notation, equal nodes always have the same structural hash
+    /// Every node keeps its hash once it's been worked out (Add and Mul in their sorted vecs, leaves just hash their own contents), so asking again is O(1)
+    /// The first time, the nodes below that aren't cached yet are hashed bottom up on a heap stack, so this works on trees of any depth
+    /// It's the same from run to run, but not between versions of this crate, so it shouldn't be stored
+    pub fn structural_hash(&self) -> u64 {
+        if !self.is_hash_cached() {
+            // Pre-order reversed puts every node after all of its children, each shared subtree is only pushed once
+            let mut pending = Vec::new();
+            let mut seen = HashSet::new();
+            let mut stack = vec![self];
+
+            while let Some(node) = stack.pop() {
+                pending.push(node);
+                stack.extend(node.children().into_iter().map(|x| x.as_ref()).filter(|x| !x.is_hash_cached() && seen.insert(*x as *const ByzNode)));
+            }
+
+            for node in pending.into_iter().rev() {
+                node.cache_hash();
+            }
+        }
+
+        return self.cache_hash();
+    }
+
+    /// Where the hash is kept, None for leaves and for Add and Mul (whose sorted vecs cache theirs)
+    fn hash_cache(&self) -> Option<&OnceLock<u64>> {
+        match self {
+            ByzNode::Rational { .. } | ByzNode::TransitiveConst { .. } | ByzNode::Variable { .. } | ByzNode::AlgebraicRoot { .. } | ByzNode::Add { .. } | ByzNode::Mul { .. } => {
+                return None;
+            },
+            ByzNode::Pow { hash_cache, .. } | ByzNode::Exp { hash_cache, .. } | ByzNode::Ln { hash_cache, .. } | ByzNode::Sin { hash_cache, .. } |
+            ByzNode::Cos { hash_cache, .. } | ByzNode::Tan { hash_cache, .. } | ByzNode::Arctan { hash_cache, .. } | ByzNode::Arcsin { hash_cache, .. } |
+            ByzNode::Arccos { hash_cache, .. } | ByzNode::Sinh { hash_cache, .. } | ByzNode::Cosh { hash_cache, .. } | ByzNode::Tanh { hash_cache, .. } |
+            ByzNode::Abs { hash_cache, .. } | ByzNode::Min { hash_cache, .. } | ByzNode::Max { hash_cache, .. } | ByzNode::Floor { hash_cache, .. } |
+            ByzNode::Ceil { hash_cache, .. } | ByzNode::Frac { hash_cache, .. } | ByzNode::Log { hash_cache, .. } | ByzNode::RealPow { hash_cache, .. } => {
+                return Some(hash_cache);
+            }
+        }
+    }
+
+    /// Whether structural_hash can answer without looking at any children
+    fn is_hash_cached(&self) -> bool {
+        match self {
+            ByzNode::Rational { .. } | ByzNode::TransitiveConst { .. } | ByzNode::Variable { .. } | ByzNode::AlgebraicRoot { .. } => {
+                return true;
+            },
+            ByzNode::Add { addends } => {
+                return addends.is_hash_cached();
+            },
+            ByzNode::Mul { products } => {
+                return products.is_hash_cached();
+            },
+            _ => {
+                return self.hash_cache().is_some_and(|x| x.get().is_some());
+            }
+        }
+    }
+
+    /// The cached hash, None if it hasn't been worked out yet or self is a leaf
+    fn known_hash(&self) -> Option<u64> {
+        match self {
+            ByzNode::Add { addends } if addends.is_hash_cached() => {
+                return Some(self.hash_from_children());
+            },
+            ByzNode::Mul { products } if products.is_hash_cached() => {
+                return Some(self.hash_from_children());
+            },
+            _ => {
+                return self.hash_cache().and_then(|x| x.get().copied());
+            }
+        }
+    }
+
+    /// The hash of this node, filling in its cache
+    /// Only looks up the children's hashes, so they should be cached already or this recurses through them
+    fn cache_hash(&self) -> u64 {
+        if let Some(hash_cache) = self.hash_cache() {
+            return *hash_cache.get_or_init(|| self.hash_from_children());
+        }
+        return self.hash_from_children();
+    }
+
+    fn hash_from_children(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.to_identifying_type_int().hash(&mut hasher);
+
         match self {
             ByzNode::Rational { rational } => {
-                return ByzNode::Rational { rational: rational.clone() };
+                rational.hash(&mut hasher);
             },
             ByzNode::TransitiveConst { transitive_const } => {
-                return ByzNode::TransitiveConst { transitive_const: transitive_const.clone() };
+                transitive_const.hash(&mut hasher);
             },
             ByzNode::Add { addends } => {
-                return ByzNode::Add { addends: addends.deep_clone() };
+                hasher.write_u64(addends.structural_hash());
             },
             ByzNode::Mul { products } => {
-                return ByzNode::Mul { products: products.deep_clone() };
+                hasher.write_u64(products.structural_hash());
+            },
+            ByzNode::Pow { base, exp, .. } => {
+                hasher.write_u64(base.structural_hash());
+                exp.hash(&mut hasher);
             },
-            ByzNode::Pow { base, exp } => {
-                return ByzNode::Pow { base: Rc::new(base.deep_clone()), exp: exp.clone() };
+            ByzNode::Variable { name } => {
+                name.hash(&mut hasher);
+            },
+            ByzNode::AlgebraicRoot { polynomial, index } => {
+                polynomial.hash(&mut hasher);
+                index.hash(&mut hasher);
+            },
+            _ => {
+                self.for_each_child(|x| hasher.write_u64(x.structural_hash()));
             }
         }
+
+        return hasher.finish();
     }
-}
\ No newline at end of file
+
+    /// Calls f on each direct child, in the same order as map_children
+    pub fn for_each_child<F>(&self, mut f: F) where F: FnMut(&ByzRc<ByzNode>) {
+        self.map_children(|x| {
+            f(x);
+            return None;
+        });
+    }
+
+    /// The direct children, in the same order as map_children
+    /// Unlike for_each_child the references live as long as self, so they can be kept on a stack to walk the tree without recursing
+    pub fn children(&self) -> Vec<&ByzRc<ByzNode>> {
+        match self {
+            ByzNode::Rational { .. } | ByzNode::TransitiveConst { .. } | ByzNode::Variable { .. } | ByzNode::AlgebraicRoot { .. } => {
+                return Vec::new();
+            },
+            ByzNode::Add { addends } => {
+                return addends.get_vec().iter().map(|x| &x.1).collect();
+            },
+            ByzNode::Mul { products } => {
+                return products.get_vec().iter().map(|x| &x.1).collect();
+            },
+            ByzNode::Min { args, .. } | ByzNode::Max { args, .. } => {
+                return args.iter().collect();
+            },
+            ByzNode::Log { base, arg, .. } => {
+                return vec![base, arg];
+            },
+            ByzNode::RealPow { base, exp, .. } => {
+                return vec![base, exp];
+            },
+            ByzNode::Pow { base: arg, .. } | ByzNode::Exp { arg, .. } | ByzNode::Ln { arg, .. } | ByzNode::Sin { arg, .. } | ByzNode::Cos { arg, .. } | ByzNode::Tan { arg, .. } |
+            ByzNode::Arctan { arg, .. } | ByzNode::Arcsin { arg, .. } | ByzNode::Arccos { arg, .. } | ByzNode::Sinh { arg, .. } | ByzNode::Cosh { arg, .. } | ByzNode::Tanh { arg, .. } |
+            ByzNode::Abs { arg, .. } | ByzNode::Floor { arg, .. } | ByzNode::Ceil { arg, .. } | ByzNode::Frac { arg, .. } => {
+                return vec![arg];
+            }
+        }
+    }
+
+    /// Whether the node has any children at all, so false for Rational, TransitiveConst, Variable and AlgebraicRoot
+    pub fn has_children(&self) -> bool {
+        return !matches!(self, ByzNode::Rational { .. } | ByzNode::TransitiveConst { .. } | ByzNode::Variable { .. } | ByzNode::AlgebraicRoot { .. });
+    }
+
+    /// A copy of the whole tree that shares nothing with the original
+    /// Subtrees that are shared within the original are shared the same way within the copy, and since this is a fold it doesn't recurse
+    pub fn deep_clone(&self) -> ByzNode {
+        return self.fold(&mut |x| x);
+    }
+
+    /// Rebuilds this node with f applied to each of its direct children, where f returns None for children it leaves alone
+    /// Returns None if f left every child alone, so the caller can keep using (and sharing) this node
+    /// Add and Mul are rebuilt through insert, so children that f makes equal are merged
+    pub fn map_children<F>(&self, mut f: F) -> Option<ByzNode> where F: FnMut(&ByzRc<ByzNode>) -> Option<ByzRc<ByzNode>> {
+        match self {
+            ByzNode::Rational { .. } | ByzNode::TransitiveConst { .. } | ByzNode::Variable { .. } | ByzNode::AlgebraicRoot { .. } => {
+                return None;
+            },
+            ByzNode::Add { addends } => {
+                return map_vec_children(addends, ByzNodeCoefficientAddVec::new(), f).map(|addends| ByzNode::Add { addends });
+            },
+            ByzNode::Mul { products } => {
+                return map_vec_children(products, ByzNodePowerMulVec::new(), f).map(|products| ByzNode::Mul { products });
+            },
+            ByzNode::Pow { base, exp, .. } => {
+                return f(base).map(|base| ByzNode::Pow { base, exp: exp.clone(), hash_cache: OnceLock::new() });
+            },
+            ByzNode::Exp { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Exp { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Ln { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Ln { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Sin { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Sin { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Cos { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Cos { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Tan { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Tan { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Arctan { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Arctan { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Arcsin { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Arcsin { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Arccos { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Arccos { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Sinh { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Sinh { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Cosh { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Cosh { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Tanh { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Tanh { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Abs { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Abs { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Floor { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Floor { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Ceil { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Ceil { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Frac { arg, .. } => {
+                return f(arg).map(|arg| ByzNode::Frac { arg, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Min { args, .. } => {
+                return map_args_children(args, f).map(|args| ByzNode::Min { args, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Max { args, .. } => {
+                return map_args_children(args, f).map(|args| ByzNode::Max { args, hash_cache: OnceLock::new() });
+            },
+            ByzNode::Log { base, arg, .. } => {
+                let new_base = f(base);
+                let new_arg = f(arg);
+
+                if new_base.is_none() && new_arg.is_none() {
+                    return None;
+                }
+
+                return Some(ByzNode::Log {
+                    base: new_base.unwrap_or_else(|| ByzRc::clone(base)),
+                    arg: new_arg.unwrap_or_else(|| ByzRc::clone(arg)),
+                    hash_cache: OnceLock::new()
+                });
+            },
+            ByzNode::RealPow { base, exp, .. } => {
+                let new_base = f(base);
+                let new_exp = f(exp);
+
+                if new_base.is_none() && new_exp.is_none() {
+                    return None;
+                }
+
+                return Some(ByzNode::RealPow {
+                    base: new_base.unwrap_or_else(|| ByzRc::clone(base)),
+                    exp: new_exp.unwrap_or_else(|| ByzRc::clone(exp)),
+                    hash_cache: OnceLock::new()
+                });
+            }
+        }
+    }
+
+    /// Replaces every Variable named in map with its value, variables that aren't in map are left as they are
+    /// Subtrees without any of the variables (and the values themselves) are shared rather than copied
+    /// Like fold it works from the bottom up on an explicit stack, so it doesn't recurse
+    pub fn substitute(&self, map: &HashMap<String, ByzRc<ByzNode>>) -> ByzNode {
+        // What each subtree became by address, None when nothing in it was substituted
+        let mut substituted: HashMap<*const ByzNode, Option<ByzRc<ByzNode>>> = HashMap::new();
+        let mut stack = vec![(self, false)];
+
+        while let Some((node, children_substituted)) = stack.pop() {
+            if !children_substituted {
+                stack.push((node, true));
+                stack.extend(node.children().into_iter().rev().map(|x| x.as_ref()).filter(|x| !substituted.contains_key(&(*x as *const ByzNode))).map(|x| (x, false)));
+                continue;
+            }
+
+            // A shared subtree can be pushed by several parents before the first of them gets to it
+            if substituted.contains_key(&(node as *const ByzNode)) {
+                continue;
+            }
+
+            if let ByzNode::Variable { name } = node && let Some(value) = map.get(name) {
+                if std::ptr::eq(node, self) {
+                    return value.as_ref().clone();
+                }
+                substituted.insert(node, Some(ByzRc::clone(value)));
+                continue;
+            }
+
+            let changed = node.map_children(|x| substituted[&ByzRc::as_ptr(x)].clone());
+
+            if std::ptr::eq(node, self) {
+                return changed.unwrap_or_else(|| self.clone());
+            }
+            substituted.insert(node, changed.map(ByzRc::new));
+        }
+
+        unreachable!("the root is always substituted last");
+    }
+}
+
+/// map_children for the vec of an Add or Mul, new is the empty vec to rebuild into
+fn map_vec_children<T, F>(vec: &T, mut new: T, mut f: F) -> Option<T> where T: ByzNodeVec, F: FnMut(&ByzRc<ByzNode>) -> Option<ByzRc<ByzNode>> {
+    let mapped: Vec<Option<ByzRc<ByzNode>>> = vec.get_vec().iter().map(|x| f(&x.1)).collect();
+
+    if mapped.iter().all(Option::is_none) {
+        return None;
+    }
+
+    new.insert_rational(vec.get_rational_part().clone());
+    for (x, mapped) in vec.get_vec().iter().zip(mapped) {
+        new.insert_rc((x.0.clone(), mapped.unwrap_or_else(|| ByzRc::clone(&x.1))));
+    }
+
+    return Some(new);
+}
+
+/// map_children for the arguments of Min and Max, which are sorted and deduplicated again afterwards
+fn map_args_children<F>(args: &[ByzRc<ByzNode>], mut f: F) -> Option<Vec<ByzRc<ByzNode>>> where F: FnMut(&ByzRc<ByzNode>) -> Option<ByzRc<ByzNode>> {
+    let mapped: Vec<Option<ByzRc<ByzNode>>> = args.iter().map(&mut f).collect();
+
+    if mapped.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let mut new_args: Vec<ByzRc<ByzNode>> = args.iter().zip(mapped).map(|(x, mapped)| mapped.unwrap_or_else(|| ByzRc::clone(x))).collect();
+    new_args.sort();
+    new_args.dedup();
+
+    return Some(new_args);
+}
@@ -0,0 +1,426 @@
+//! Compact binary encoding for ByzNode, as postfix byte code for a stack machine
+//! Each instruction is an opcode byte (the node's identifying type int) and its payload, and builds a node out of the ones on top of the stack
+//! Children come before their parent (and in the order map_children visits them), so the last instruction builds the root
+//! Rationals use Rational::write_bytes, and counts, indices and string lengths are LEB128 like the lengths in there
+//! Every node built gets the next index, and a subtree equal to one that was already written is written as opcode 255 and that index instead
+//! So repeated structure takes a couple of bytes and decodes as a shared node
+//!
+//! Payloads (children taken off the stack in brackets):
+//! Rational: the value. TransitiveConst: one byte (Pi 0, Euler 1, Ln2 2, GoldenRatio 3, EulerMascheroni 4, Catalan 5, Apery 6)
+//! Add and Mul: the rational part, the count n and then the n coefficients or powers [n terms]. Pow: the exponent [base]
+//! Min and Max: the count n [n args]. Log [base, arg]. RealPow [base, exp]. Variable: the length and then the UTF-8 name
+//! AlgebraicRoot: the count n, the n coefficients from the constant term up and then the index
+//! Everything else has no payload [arg]
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::byznode_visit::ByzVisitor;
+use crate::generate_byznode_utils::is_valid_algebraic_root;
+use crate::rational::Rational;
+use crate::rational_polynomial::RationalPolynomial;
+use crate::rational_bytes::{read_length, write_length, DecodeRationalError};
+
+const REFERENCE_OPCODE: u8 = 255;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeByzNodeError {
+    /// A Rational, count or index inside the byte code couldn't be decoded
+    Rational(DecodeRationalError),
+    InvalidOpcode(u8),
+    InvalidTransitiveConst(u8),
+    /// A reference to an index that no node had yet
+    InvalidReference(usize),
+    InvalidUtf8,
+    /// An instruction needed more nodes than were on the stack
+    StackUnderflow,
+    /// A Min or Max with no arguments
+    EmptyArgs,
+    /// The byte code didn't leave exactly one node on the stack
+    UnbalancedStack,
+    /// An AlgebraicRoot whose polynomial isn't square free and monic or doesn't have enough real roots
+    InvalidAlgebraicRoot
+}
+
+impl Display for DecodeByzNodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            DecodeByzNodeError::Rational(e) => {
+                return write!(f, "{}", e);
+            },
+            DecodeByzNodeError::InvalidOpcode(opcode) => {
+                return write!(f, "invalid opcode {} in encoded byznode", opcode);
+            },
+            DecodeByzNodeError::InvalidTransitiveConst(byte) => {
+                return write!(f, "invalid transitive constant {} in encoded byznode", byte);
+            },
+            DecodeByzNodeError::InvalidReference(index) => {
+                return write!(f, "reference to node {} before it was defined", index);
+            },
+            DecodeByzNodeError::InvalidUtf8 => {
+                return write!(f, "variable name in encoded byznode isn't valid UTF-8");
+            },
+            DecodeByzNodeError::StackUnderflow => {
+                return write!(f, "instruction in encoded byznode has too few children on the stack");
+            },
+            DecodeByzNodeError::EmptyArgs => {
+                return write!(f, "min or max in encoded byznode has no arguments");
+            },
+            DecodeByzNodeError::UnbalancedStack => {
+                return write!(f, "encoded byznode doesn't end with exactly one node");
+            },
+            DecodeByzNodeError::InvalidAlgebraicRoot => {
+                return write!(f, "algebraic root in encoded byznode doesn't have a valid polynomial and index");
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeByzNodeError {}
+
+impl From<DecodeRationalError> for DecodeByzNodeError {
+    fn from(e: DecodeRationalError) -> DecodeByzNodeError {
+        return DecodeByzNodeError::Rational(e);
+    }
+}
+
+fn transitive_const_byte(transitive_const: TransitiveConsts) -> u8 {
+    match transitive_const {
+        TransitiveConsts::Pi => {
+            return 0;
+        },
+        TransitiveConsts::Euler => {
+            return 1;
+        },
+        TransitiveConsts::Ln2 => {
+            return 2;
+        },
+        TransitiveConsts::GoldenRatio => {
+            return 3;
+        },
+        TransitiveConsts::EulerMascheroni => {
+            return 4;
+        },
+        TransitiveConsts::Catalan => {
+            return 5;
+        },
+        TransitiveConsts::Apery => {
+            return 6;
+        }
+    }
+}
+
+fn transitive_const_from_byte(byte: u8) -> Result<TransitiveConsts, DecodeByzNodeError> {
+    match byte {
+        0 => {
+            return Ok(TransitiveConsts::Pi);
+        },
+        1 => {
+            return Ok(TransitiveConsts::Euler);
+        },
+        2 => {
+            return Ok(TransitiveConsts::Ln2);
+        },
+        3 => {
+            return Ok(TransitiveConsts::GoldenRatio);
+        },
+        4 => {
+            return Ok(TransitiveConsts::EulerMascheroni);
+        },
+        5 => {
+            return Ok(TransitiveConsts::Catalan);
+        },
+        6 => {
+            return Ok(TransitiveConsts::Apery);
+        },
+        _ => {
+            return Err(DecodeByzNodeError::InvalidTransitiveConst(byte));
+        }
+    }
+}
+
+/// The indices given to the nodes written so far, keyed by structure so equal subtrees are found even when they aren't shared
+struct Encoder {
+    out: Vec<u8>,
+    indices: HashMap<ByzNode, usize>,
+    /// Set by enter when it wrote a reference, so the leave that follows doesn't write the node again
+    referenced: bool
+}
+
+impl Encoder {
+    fn write_vec<T>(&mut self, vec: &T) where T: ByzNodeVec {
+        self.out.extend(vec.get_rational_part().to_bytes());
+        write_length(&mut self.out, vec.get_vec().len());
+
+        for x in vec.get_vec() {
+            x.0.write_bytes(&mut self.out);
+        }
+    }
+
+}
+
+/// Postfix order is exactly the order walk leaves nodes in, so the encoder never recurses
+impl ByzVisitor for Encoder {
+    /// A subtree that was already written becomes a reference to it, and its children are skipped
+    fn enter(&mut self, node: &ByzNode) -> bool {
+        if let Some(&index) = self.indices.get(node) {
+            self.out.push(REFERENCE_OPCODE);
+            write_length(&mut self.out, index);
+            self.referenced = true;
+            return false;
+        }
+
+        return true;
+    }
+
+    /// Every child has been written by now, so only the node's own opcode and payload are left
+    fn leave(&mut self, node: &ByzNode) {
+        if std::mem::take(&mut self.referenced) {
+            return;
+        }
+
+        self.out.push(node.to_identifying_type_int() as u8);
+        match node {
+            ByzNode::Rational { rational } => {
+                rational.write_bytes(&mut self.out);
+            },
+            ByzNode::TransitiveConst { transitive_const } => {
+                self.out.push(transitive_const_byte(*transitive_const));
+            },
+            ByzNode::Add { addends } => {
+                self.write_vec(addends);
+            },
+            ByzNode::Mul { products } => {
+                self.write_vec(products);
+            },
+            ByzNode::Pow { exp, .. } => {
+                exp.write_bytes(&mut self.out);
+            },
+            ByzNode::Min { args, .. } | ByzNode::Max { args, .. } => {
+                write_length(&mut self.out, args.len());
+            },
+            ByzNode::Variable { name } => {
+                write_length(&mut self.out, name.len());
+                self.out.extend_from_slice(name.as_bytes());
+            },
+            ByzNode::AlgebraicRoot { polynomial, index } => {
+                write_length(&mut self.out, polynomial.coefficients().len());
+                for c in polynomial.coefficients() {
+                    c.write_bytes(&mut self.out);
+                }
+                write_length(&mut self.out, *index);
+            },
+            _ => ()
+        }
+
+        let index = self.indices.len();
+        self.indices.insert(node.clone(), index);
+    }
+}
+
+/// Reads through the byte code, keeping track of where it's up to
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl<'a> Decoder<'a> {
+    fn read_byte(&mut self) -> Result<u8, DecodeByzNodeError> {
+        let byte = *self.bytes.get(self.position).ok_or(DecodeRationalError::UnexpectedEnd)?;
+        self.position += 1;
+        return Ok(byte);
+    }
+
+    fn read_length(&mut self) -> Result<usize, DecodeByzNodeError> {
+        let (len, used) = read_length(&self.bytes[self.position..])?;
+        self.position += used;
+        return Ok(len);
+    }
+
+    fn read_rational(&mut self) -> Result<Rational, DecodeByzNodeError> {
+        let (rational, used) = Rational::read_bytes(&self.bytes[self.position..])?;
+        self.position += used;
+        return Ok(rational);
+    }
+
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8], DecodeByzNodeError> {
+        let end = self.position.checked_add(len).ok_or(DecodeRationalError::InvalidLength)?;
+        let slice = self.bytes.get(self.position..end).ok_or(DecodeRationalError::UnexpectedEnd)?;
+        self.position = end;
+        return Ok(slice);
+    }
+}
+
+fn pop(stack: &mut Vec<ByzRc<ByzNode>>) -> Result<ByzRc<ByzNode>, DecodeByzNodeError> {
+    return stack.pop().ok_or(DecodeByzNodeError::StackUnderflow);
+}
+
+/// The top n nodes of the stack, in the order they were pushed
+fn pop_n(stack: &mut Vec<ByzRc<ByzNode>>, n: usize) -> Result<Vec<ByzRc<ByzNode>>, DecodeByzNodeError> {
+    if n > stack.len() {
+        return Err(DecodeByzNodeError::StackUnderflow);
+    }
+
+    return Ok(stack.split_off(stack.len() - n));
+}
+
+/// Builds an Add or Mul vec out of the payload and the terms on the stack, through insert so it comes out sorted and merged
+fn read_vec<T>(mut vec: T, decoder: &mut Decoder, stack: &mut Vec<ByzRc<ByzNode>>) -> Result<T, DecodeByzNodeError> where T: ByzNodeVec {
+    vec.insert_rational(decoder.read_rational()?);
+
+    let n = decoder.read_length()?;
+    for node in pop_n(stack, n)? {
+        vec.insert_rc((decoder.read_rational()?, node));
+    }
+
+    return Ok(vec);
+}
+
+fn read_args(decoder: &mut Decoder, stack: &mut Vec<ByzRc<ByzNode>>) -> Result<Vec<ByzRc<ByzNode>>, DecodeByzNodeError> {
+    let mut args = pop_n(stack, decoder.read_length()?)?;
+    if args.is_empty() {
+        return Err(DecodeByzNodeError::EmptyArgs);
+    }
+
+    args.sort();
+    args.dedup();
+
+    return Ok(args);
+}
+
+impl ByzNode {
+    /// See the module docs for the format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder { out: Vec::new(), indices: HashMap::new(), referenced: false };
+        self.walk(&mut encoder);
+        return encoder.out;
+    }
+
+    /// Decodes a whole buffer written by to_bytes, subtrees that were written as references come out shared
+    pub fn from_bytes(bytes: &[u8]) -> Result<ByzNode, DecodeByzNodeError> {
+        let mut decoder = Decoder { bytes, position: 0 };
+        let mut stack: Vec<ByzRc<ByzNode>> = Vec::new();
+        let mut nodes: Vec<ByzRc<ByzNode>> = Vec::new();
+
+        while decoder.position < bytes.len() {
+            let opcode = decoder.read_byte()?;
+
+            if opcode == REFERENCE_OPCODE {
+                let index = decoder.read_length()?;
+                let node = nodes.get(index).ok_or(DecodeByzNodeError::InvalidReference(index))?;
+                stack.push(ByzRc::clone(node));
+                continue;
+            }
+
+            let node = match opcode {
+                0 => {
+                    ByzNode::Rational { rational: decoder.read_rational()? }
+                },
+                1 => {
+                    ByzNode::TransitiveConst { transitive_const: transitive_const_from_byte(decoder.read_byte()?)? }
+                },
+                2 => {
+                    ByzNode::Add { addends: read_vec(ByzNodeCoefficientAddVec::new(), &mut decoder, &mut stack)? }
+                },
+                3 => {
+                    ByzNode::Mul { products: read_vec(ByzNodePowerMulVec::new(), &mut decoder, &mut stack)? }
+                },
+                4 => {
+                    ByzNode::Pow { base: pop(&mut stack)?, exp: decoder.read_rational()?, hash_cache: OnceLock::new() }
+                },
+                5 => {
+                    ByzNode::Exp { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                6 => {
+                    ByzNode::Ln { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                7 => {
+                    ByzNode::Sin { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                8 => {
+                    ByzNode::Cos { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                9 => {
+                    ByzNode::Tan { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                10 => {
+                    ByzNode::Arctan { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                11 => {
+                    ByzNode::Arcsin { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                12 => {
+                    ByzNode::Arccos { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                13 => {
+                    ByzNode::Sinh { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                14 => {
+                    ByzNode::Cosh { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                15 => {
+                    ByzNode::Tanh { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                16 => {
+                    ByzNode::Abs { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                19 => {
+                    ByzNode::Floor { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                20 => {
+                    ByzNode::Ceil { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                21 => {
+                    ByzNode::Frac { arg: pop(&mut stack)?, hash_cache: OnceLock::new() }
+                },
+                17 => {
+                    ByzNode::Min { args: read_args(&mut decoder, &mut stack)?, hash_cache: OnceLock::new() }
+                },
+                18 => {
+                    ByzNode::Max { args: read_args(&mut decoder, &mut stack)?, hash_cache: OnceLock::new() }
+                },
+                22 => {
+                    let arg = pop(&mut stack)?;
+                    ByzNode::Log { base: pop(&mut stack)?, arg, hash_cache: OnceLock::new() }
+                },
+                23 => {
+                    let len = decoder.read_length()?;
+                    let name = std::str::from_utf8(decoder.read_slice(len)?).map_err(|_| DecodeByzNodeError::InvalidUtf8)?;
+                    ByzNode::Variable { name: name.to_string() }
+                },
+                24 => {
+                    let exp = pop(&mut stack)?;
+                    ByzNode::RealPow { base: pop(&mut stack)?, exp, hash_cache: OnceLock::new() }
+                },
+                25 => {
+                    let n = decoder.read_length()?;
+                    let coefficients = (0..n).map(|_| decoder.read_rational()).collect::<Result<Vec<Rational>, DecodeByzNodeError>>()?;
+                    let polynomial = RationalPolynomial::new(coefficients);
+                    let index = decoder.read_length()?;
+
+                    if !is_valid_algebraic_root(&polynomial, index) {
+                        return Err(DecodeByzNodeError::InvalidAlgebraicRoot);
+                    }
+                    ByzNode::AlgebraicRoot { polynomial, index }
+                },
+                _ => {
+                    return Err(DecodeByzNodeError::InvalidOpcode(opcode));
+                }
+            };
+
+            let node = ByzRc::new(node);
+            nodes.push(ByzRc::clone(&node));
+            stack.push(node);
+        }
+
+        let root = pop(&mut stack)?;
+        if !stack.is_empty() {
+            return Err(DecodeByzNodeError::UnbalancedStack);
+        }
+
+        return Ok(ByzRc::unwrap_or_clone(root));
+    }
+}
@@ -0,0 +1,269 @@
+use num_bigint::BigUint;
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
+use crate::byznode_sorted_vec::ByzNodeVec;
+use crate::rational::Rational;
+
+/// The syntaxes ByzNode can be exported to, everything is written as explicit function calls so no precedence rules come into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CasSyntax {
+    /// Input for sympy.sympify (or parse_expr), e.g. Add(Mul(2, Symbol('x')), Rational(1, 2))
+    SymPy,
+    /// Mathematica FullForm, e.g. Plus[Times[2, x], Rational[1, 2]]
+    Mathematica
+}
+
+impl CasSyntax {
+    fn call(&self, head: &str, args: &[String]) -> String {
+        match self {
+            CasSyntax::SymPy => {
+                return format!("{}({})", head, args.join(", "));
+            },
+            CasSyntax::Mathematica => {
+                return format!("{}[{}]", head, args.join(", "));
+            }
+        }
+    }
+
+    /// The (SymPy, Mathematica) names of a head
+    fn pick<'a>(&self, sympy: &'a str, mathematica: &'a str) -> &'a str {
+        match self {
+            CasSyntax::SymPy => {
+                return sympy;
+            },
+            CasSyntax::Mathematica => {
+                return mathematica;
+            }
+        }
+    }
+
+    fn rational(&self, rational: &Rational) -> String {
+        let mut rational = rational.clone();
+        rational.simplify();
+
+        if rational.is_int() {
+            return rational.to_string();
+        }
+
+        let sign = if rational.is_negative() { "-" } else { "" };
+        let args = [format!("{}{}", sign, rational.numer()), rational.denom().to_string()];
+        return self.call("Rational", &args);
+    }
+
+    fn constant(&self, transitive_const: &TransitiveConsts) -> String {
+        match transitive_const {
+            TransitiveConsts::Pi => {
+                return self.pick("pi", "Pi").to_string();
+            },
+            TransitiveConsts::Euler => {
+                return "E".to_string();
+            },
+            TransitiveConsts::Ln2 => {
+                return self.call(self.pick("log", "Log"), &["2".to_string()]);
+            },
+            TransitiveConsts::GoldenRatio => {
+                return "GoldenRatio".to_string();
+            },
+            TransitiveConsts::EulerMascheroni => {
+                return "EulerGamma".to_string();
+            },
+            TransitiveConsts::Catalan => {
+                return "Catalan".to_string();
+            },
+            TransitiveConsts::Apery => {
+                return self.call(self.pick("zeta", "Zeta"), &["3".to_string()]);
+            }
+        }
+    }
+
+    fn variable(&self, name: &str) -> String {
+        match self {
+            CasSyntax::SymPy => {
+                return format!("Symbol('{}')", name.replace('\\', "\\\\").replace('\'', "\\'"));
+            },
+            CasSyntax::Mathematica => {
+                // Mathematica symbols are letters and digits only (an underscore would make a pattern), anything else has to go through Symbol
+                let mut chars = name.chars();
+                if chars.next().is_some_and(|c| c.is_alphabetic()) && chars.all(|c| c.is_alphanumeric()) {
+                    return name.to_string();
+                }
+
+                return format!("Symbol[\"{}\"]", name.replace('\\', "\\\\").replace('"', "\\\""));
+            }
+        }
+    }
+
+    /// coefficient * node, leaving out a coefficient of 1
+    fn scaled(&self, coefficient: &Rational, node: &ByzNode) -> String {
+        if coefficient.is_one() {
+            return self.export(node);
+        }
+
+        return self.call(self.pick("Mul", "Times"), &[self.rational(coefficient), self.export(node)]);
+    }
+
+    /// node^power, leaving out a power of 1
+    /// Neoprene takes odd roots of negative numbers to be real, where both CASes would take the principal (complex) root
+    /// So x^(p/q) with an odd q is written as the real qth root of x, raised to p
+    fn power(&self, node: &ByzNode, power: &Rational) -> String {
+        if power.is_one() {
+            return self.export(node);
+        }
+
+        let mut power = power.clone();
+        power.simplify();
+
+        if !power.is_int() && power.denom().bit(0) {
+            let root = self.call(self.pick("real_root", "Surd"), &[self.export(node), power.denom().to_string()]);
+            let numer = Rational::new(power.sign(), power.numer().into_owned(), BigUint::from(1_u8));
+            if numer.is_one() {
+                return root;
+            }
+
+            return self.call(self.pick("Pow", "Power"), &[root, self.rational(&numer)]);
+        }
+
+        return self.call(self.pick("Pow", "Power"), &[self.export(node), self.rational(&power)]);
+    }
+
+    fn unary(&self, sympy: &str, mathematica: &str, arg: &ByzNode) -> String {
+        return self.call(self.pick(sympy, mathematica), &[self.export(arg)]);
+    }
+
+    fn args(&self, sympy: &str, mathematica: &str, args: &[ByzRc<ByzNode>]) -> String {
+        let args: Vec<String> = args.iter().map(|x| self.export(x)).collect();
+        return self.call(self.pick(sympy, mathematica), &args);
+    }
+
+    fn export(&self, node: &ByzNode) -> String {
+        match node {
+            ByzNode::Rational { rational } => {
+                return self.rational(rational);
+            },
+            ByzNode::TransitiveConst { transitive_const } => {
+                return self.constant(transitive_const);
+            },
+            ByzNode::Add { addends } => {
+                let mut args: Vec<String> = addends.get_vec().iter().map(|x| self.scaled(&x.0, &x.1)).collect();
+                if !addends.get_rational_part().is_zero() || args.is_empty() {
+                    args.push(self.rational(addends.get_rational_part()));
+                }
+
+                return self.call(self.pick("Add", "Plus"), &args);
+            },
+            ByzNode::Mul { products } => {
+                let mut args: Vec<String> = Vec::new();
+                if !products.get_rational_part().is_one() || products.get_vec().is_empty() {
+                    args.push(self.rational(products.get_rational_part()));
+                }
+                args.extend(products.get_vec().iter().map(|x| self.power(&x.1, &x.0)));
+
+                return self.call(self.pick("Mul", "Times"), &args);
+            },
+            ByzNode::Pow { base, exp, .. } => {
+                return self.power(base, exp);
+            },
+            ByzNode::Exp { arg, .. } => {
+                // FullForm has no Exp, e^x is just a power of E
+                if *self == CasSyntax::Mathematica {
+                    return self.call("Power", &["E".to_string(), self.export(arg)]);
+                }
+                return self.call("exp", &[self.export(arg)]);
+            },
+            ByzNode::Ln { arg, .. } => {
+                return self.unary("log", "Log", arg);
+            },
+            ByzNode::Sin { arg, .. } => {
+                return self.unary("sin", "Sin", arg);
+            },
+            ByzNode::Cos { arg, .. } => {
+                return self.unary("cos", "Cos", arg);
+            },
+            ByzNode::Tan { arg, .. } => {
+                return self.unary("tan", "Tan", arg);
+            },
+            ByzNode::Arctan { arg, .. } => {
+                return self.unary("atan", "ArcTan", arg);
+            },
+            ByzNode::Arcsin { arg, .. } => {
+                return self.unary("asin", "ArcSin", arg);
+            },
+            ByzNode::Arccos { arg, .. } => {
+                return self.unary("acos", "ArcCos", arg);
+            },
+            ByzNode::Sinh { arg, .. } => {
+                return self.unary("sinh", "Sinh", arg);
+            },
+            ByzNode::Cosh { arg, .. } => {
+                return self.unary("cosh", "Cosh", arg);
+            },
+            ByzNode::Tanh { arg, .. } => {
+                return self.unary("tanh", "Tanh", arg);
+            },
+            ByzNode::Abs { arg, .. } => {
+                return self.unary("Abs", "Abs", arg);
+            },
+            ByzNode::Min { args, .. } => {
+                return self.args("Min", "Min", args);
+            },
+            ByzNode::Max { args, .. } => {
+                return self.args("Max", "Max", args);
+            },
+            ByzNode::Floor { arg, .. } => {
+                return self.unary("floor", "Floor", arg);
+            },
+            ByzNode::Ceil { arg, .. } => {
+                return self.unary("ceiling", "Ceiling", arg);
+            },
+            ByzNode::Frac { arg, .. } => {
+                // Mathematica's FractionalPart rounds towards zero, so -1/4 would come out as -1/4 instead of 3/4
+                if *self == CasSyntax::Mathematica {
+                    let arg = self.export(arg);
+                    let floor = self.call("Times", &["-1".to_string(), self.call("Floor", std::slice::from_ref(&arg))]);
+                    return self.call("Plus", &[arg, floor]);
+                }
+                return self.call("frac", &[self.export(arg)]);
+            },
+            ByzNode::Log { base, arg, .. } => {
+                // SymPy takes the base second
+                if *self == CasSyntax::SymPy {
+                    return self.call("log", &[self.export(arg), self.export(base)]);
+                }
+                return self.call("Log", &[self.export(base), self.export(arg)]);
+            },
+            ByzNode::Variable { name } => {
+                return self.variable(name);
+            },
+            ByzNode::RealPow { base, exp, .. } => {
+                return self.call(self.pick("Pow", "Power"), &[self.export(base), self.export(exp)]);
+            },
+            ByzNode::AlgebraicRoot { polynomial, index } => {
+                // Both number the real roots first and in increasing order, so the index carries over (from 1 in Mathematica)
+                let polynomial = self.export(&polynomial.to_byznode(&ByzNode::Variable { name: "x".to_string() }));
+                if *self == CasSyntax::Mathematica {
+                    return self.call("Root", &[self.call("Function", &["x".to_string(), polynomial]), (index + 1).to_string()]);
+                }
+                return self.call("CRootOf", &[polynomial, index.to_string()]);
+            }
+        }
+    }
+}
+
+impl ByzNode {
+    /// The expression as a string sympy.sympify understands, built from Add, Mul, Pow, Rational and Symbol calls
+    /// Frac becomes sympy's frac and Log { base, arg } becomes log(arg, base), which both mean the same thing as here
+    /// Odd roots go through real_root, so the cube root of a negative number stays real like it is in Neoprene
+    /// An AlgebraicRoot becomes CRootOf(p, k) with p in Symbol('x')
+    pub fn to_sympy(&self) -> String {
+        return CasSyntax::SymPy.export(self);
+    }
+
+    /// The expression in Mathematica FullForm, e.g. Plus[Times[2, x], Power[E, Pi]]
+    /// Frac is written out as x - Floor[x], since FractionalPart disagrees with it for negative arguments
+    /// Odd roots go through Surd for the same reason as in to_sympy, which makes the result ordinary input rather than strict FullForm
+    /// Variables whose names aren't valid symbols (like x_1) are written as Symbol["x_1"]
+    /// An AlgebraicRoot becomes Root[Function[x, p], k + 1]
+    pub fn to_mathematica(&self) -> String {
+        return CasSyntax::Mathematica.export(self);
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigUint;
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
+use crate::byznode_sorted_vec::ByzNodeVec;
+use crate::byzantine_parse::named_constant;
+use crate::rational::Rational;
+
+/// How tightly a printed expression holds together, a child that binds looser than its context needs parentheses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Precedence {
+    Sum,
+    Product,
+    /// A leading minus sign
+    Negation,
+    Power,
+    Atom
+}
+
+pub(crate) fn constant_name(transitive_const: &TransitiveConsts) -> &'static str {
+    match transitive_const {
+        TransitiveConsts::Pi => {
+            return "pi";
+        },
+        TransitiveConsts::Euler => {
+            return "e";
+        },
+        TransitiveConsts::Ln2 => {
+            return "ln2";
+        },
+        TransitiveConsts::GoldenRatio => {
+            return "phi";
+        },
+        TransitiveConsts::EulerMascheroni => {
+            return "gamma";
+        },
+        TransitiveConsts::Catalan => {
+            return "catalan";
+        },
+        TransitiveConsts::Apery => {
+            return "apery";
+        }
+    }
+}
+
+/// Everything the printer needs from a term, k * x^a * y^b * ...
+/// A sum's term c*x is read as the product c * x, so its sign can be pulled out in front of the term
+pub(crate) fn product_parts<'a>(coefficient: &Rational, node: &'a ByzRc<ByzNode>) -> (Rational, Vec<(Rational, &'a ByzRc<ByzNode>)>) {
+    match node.as_ref() {
+        ByzNode::Rational { rational } => {
+            return (coefficient * rational, Vec::new());
+        },
+        ByzNode::Mul { products } => {
+            return (coefficient * products.get_rational_part(), products.get_vec().iter().map(|x| (x.0.clone(), &x.1)).collect());
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            return (coefficient.clone(), vec![(exp.clone(), base)]);
+        },
+        _ => {
+            return (coefficient.clone(), vec![(Rational::one(), node)]);
+        }
+    }
+}
+
+/// A piece of printed output, either text as it is or a node that still has to be printed
+enum Piece<'a> {
+    Text(String),
+    /// The node, in parentheses if it binds looser than the precedence
+    Node(&'a ByzNode, Precedence)
+}
+
+/// What a node prints as, one level deep
+enum Layout<'a> {
+    Pieces(Vec<Piece<'a>>, Precedence),
+    /// Exactly what this child prints as (like 1*x^1 printing as x)
+    Same(&'a ByzNode)
+}
+
+/// Whether name can be written as it is and still parse back as a Variable with that name
+fn is_plain_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    return (first.is_alphabetic() || first == '_') && chars.all(|c| c.is_alphanumeric() || c == '_') && named_constant(name).is_none();
+}
+
+/// name as the parser reads it, in double quotes with '"' and '\\' escaped if it isn't a plain name or is the name of a constant
+pub(crate) fn variable_name(name: &str) -> String {
+    if is_plain_name(name) {
+        return name.to_string();
+    }
+    return format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+struct Printer {
+    /// Whether x^(1/2) is written sqrt(x)
+    sqrt: bool
+}
+
+impl Printer {
+    /// Prints node into out, working through the pieces on a heap stack so that deep trees don't overflow
+    fn print(&self, node: &ByzNode, out: &mut String) {
+        let mut stack = vec![Piece::Node(node, Precedence::Sum)];
+
+        while let Some(piece) = stack.pop() {
+            match piece {
+                Piece::Text(s) => {
+                    out.push_str(&s);
+                },
+                Piece::Node(mut node, min) => {
+                    let (pieces, precedence) = loop {
+                        match self.layout(node) {
+                            Layout::Same(x) => {
+                                node = x;
+                            },
+                            Layout::Pieces(pieces, precedence) => {
+                                break (pieces, precedence);
+                            }
+                        }
+                    };
+
+                    if precedence < min {
+                        stack.push(Piece::Text(")".to_string()));
+                        stack.extend(pieces.into_iter().rev());
+                        stack.push(Piece::Text("(".to_string()));
+                    } else {
+                        stack.extend(pieces.into_iter().rev());
+                    }
+                }
+            }
+        }
+    }
+
+    fn print_args<'a>(args: &'a [ByzRc<ByzNode>], pieces: &mut Vec<Piece<'a>>) {
+        for (i, x) in args.iter().enumerate() {
+            if i != 0 {
+                pieces.push(Piece::Text(", ".to_string()));
+            }
+            pieces.push(Piece::Node(x, Precedence::Sum));
+        }
+    }
+
+    /// x^p for a positive p
+    fn print_factor<'a>(&self, power: &Rational, node: &'a ByzNode) -> Layout<'a> {
+        if power.is_one() {
+            return Layout::Same(node);
+        }
+        if self.sqrt && *power == Rational::from((1, 2)) {
+            return Layout::Pieces(vec![Piece::Text("sqrt(".to_string()), Piece::Node(node, Precedence::Sum), Piece::Text(")".to_string())], Precedence::Atom);
+        }
+
+        let exp = if power.is_int() { format!("^{}", power) } else { format!("^({})", power) };
+        return Layout::Pieces(vec![Piece::Node(node, Precedence::Atom), Piece::Text(exp)], Precedence::Power);
+    }
+
+    /// |k| * x^a * y^b * ..., with the negative powers moved under a '/' (the sign of k is left to the caller)
+    fn print_product<'a>(&self, k: &Rational, factors: Vec<(Rational, &'a ByzRc<ByzNode>)>) -> Layout<'a> {
+        let mut k = k.abs();
+        k.simplify();
+
+        let mut numer: Vec<Layout<'a>> = Vec::new();
+        let mut denom: Vec<Layout<'a>> = Vec::new();
+
+        if *k.numer() != BigUint::from(1_u8) || factors.iter().all(|x| x.0.is_negative()) {
+            numer.push(Layout::Pieces(vec![Piece::Text(k.numer().to_string())], Precedence::Atom));
+        }
+        if *k.denom() != BigUint::from(1_u8) {
+            denom.push(Layout::Pieces(vec![Piece::Text(k.denom().to_string())], Precedence::Atom));
+        }
+
+        for (power, node) in factors {
+            if power.is_negative() {
+                denom.push(self.print_factor(&power.abs(), node));
+            } else {
+                numer.push(self.print_factor(&power, node));
+            }
+        }
+
+        // Factors are joined with '*', each in parentheses if it binds looser than a power
+        let join = |parts: Vec<Layout<'a>>, pieces: &mut Vec<Piece<'a>>| {
+            for (i, part) in parts.into_iter().enumerate() {
+                if i != 0 {
+                    pieces.push(Piece::Text("*".to_string()));
+                }
+
+                match part {
+                    Layout::Same(node) => {
+                        pieces.push(Piece::Node(node, Precedence::Power));
+                    },
+                    Layout::Pieces(part, _) => {
+                        // Factors print as atoms or powers, which never need parentheses here
+                        pieces.extend(part);
+                    }
+                }
+            }
+        };
+
+        let mut pieces = Vec::new();
+        match (numer.len(), denom.len()) {
+            (1, 0) => {
+                return numer.remove(0);
+            },
+            (_, 0) => {
+                join(numer, &mut pieces);
+            },
+            (_, 1) => {
+                join(numer, &mut pieces);
+                pieces.push(Piece::Text("/".to_string()));
+                join(denom, &mut pieces);
+            },
+            _ => {
+                join(numer, &mut pieces);
+                pieces.push(Piece::Text("/(".to_string()));
+                join(denom, &mut pieces);
+                pieces.push(Piece::Text(")".to_string()));
+            }
+        }
+
+        return Layout::Pieces(pieces, Precedence::Product);
+    }
+
+    /// layout with a minus sign in front, and in parentheses if it's a sum
+    fn negate(layout: Layout<'_>) -> Layout<'_> {
+        match layout {
+            Layout::Same(node) => {
+                return Layout::Pieces(vec![Piece::Text("-".to_string()), Piece::Node(node, Precedence::Product)], Precedence::Negation);
+            },
+            Layout::Pieces(mut pieces, precedence) => {
+                if precedence < Precedence::Product {
+                    pieces.insert(0, Piece::Text("-(".to_string()));
+                    pieces.push(Piece::Text(")".to_string()));
+                } else {
+                    pieces.insert(0, Piece::Text("-".to_string()));
+                }
+                return Layout::Pieces(pieces, Precedence::Negation);
+            }
+        }
+    }
+
+    fn print_function<'a>(name: &str, arg: &'a ByzNode) -> Layout<'a> {
+        return Layout::Pieces(vec![Piece::Text(format!("{}(", name)), Piece::Node(arg, Precedence::Sum), Piece::Text(")".to_string())], Precedence::Atom);
+    }
+
+    fn layout<'a>(&self, node: &'a ByzNode) -> Layout<'a> {
+        match node {
+            ByzNode::Rational { rational } => {
+                let layout = self.print_product(rational, Vec::new());
+
+                if rational.is_negative() {
+                    return Printer::negate(layout);
+                }
+                return layout;
+            },
+            ByzNode::TransitiveConst { transitive_const } => {
+                return Layout::Pieces(vec![Piece::Text(constant_name(transitive_const).to_string())], Precedence::Atom);
+            },
+            ByzNode::Add { addends } => {
+                // (negative, magnitude) of every term, with the rational part going last
+                let mut terms: Vec<(bool, Layout<'a>)> = addends.get_vec().iter()
+                    .map(|x| {
+                        let (k, factors) = product_parts(&x.0, &x.1);
+                        return (k.is_negative(), self.print_product(&k, factors));
+                    })
+                    .collect();
+
+                let rational_part = addends.get_rational_part();
+                if !rational_part.is_zero() || terms.is_empty() {
+                    terms.push((rational_part.is_negative(), self.print_product(rational_part, Vec::new())));
+                }
+
+                if terms.len() == 1 {
+                    let (negative, layout) = terms.remove(0);
+
+                    if negative {
+                        return Printer::negate(layout);
+                    }
+                    return layout;
+                }
+
+                let mut pieces = Vec::new();
+                for (i, (negative, term)) in terms.into_iter().enumerate() {
+                    match (i, negative) {
+                        (0, false) => {},
+                        (0, true) => {
+                            pieces.push(Piece::Text("-".to_string()));
+                        },
+                        (_, false) => {
+                            pieces.push(Piece::Text(" + ".to_string()));
+                        },
+                        (_, true) => {
+                            pieces.push(Piece::Text(" - ".to_string()));
+                        }
+                    }
+
+                    // A term after a sign is in parentheses if it's a sum itself, so that the sign applies to all of it
+                    let min = if negative { Precedence::Product } else { Precedence::Sum };
+                    match term {
+                        Layout::Same(node) => {
+                            pieces.push(Piece::Node(node, min));
+                        },
+                        Layout::Pieces(term, precedence) if precedence < min => {
+                            pieces.push(Piece::Text("(".to_string()));
+                            pieces.extend(term);
+                            pieces.push(Piece::Text(")".to_string()));
+                        },
+                        Layout::Pieces(term, _) => {
+                            pieces.extend(term);
+                        }
+                    }
+                }
+
+                return Layout::Pieces(pieces, Precedence::Sum);
+            },
+            ByzNode::Mul { products } => {
+                let layout = self.print_product(products.get_rational_part(), products.get_vec().iter().map(|x| (x.0.clone(), &x.1)).collect());
+
+                if products.get_rational_part().is_negative() {
+                    return Printer::negate(layout);
+                }
+                return layout;
+            },
+            ByzNode::Pow { base, exp, .. } => {
+                return self.print_product(&Rational::one(), vec![(exp.clone(), base)]);
+            },
+            ByzNode::Exp { arg, .. } => {
+                return Printer::print_function("exp", arg);
+            },
+            ByzNode::Ln { arg, .. } => {
+                return Printer::print_function("ln", arg);
+            },
+            ByzNode::Sin { arg, .. } => {
+                return Printer::print_function("sin", arg);
+            },
+            ByzNode::Cos { arg, .. } => {
+                return Printer::print_function("cos", arg);
+            },
+            ByzNode::Tan { arg, .. } => {
+                return Printer::print_function("tan", arg);
+            },
+            ByzNode::Arctan { arg, .. } => {
+                return Printer::print_function("arctan", arg);
+            },
+            ByzNode::Arcsin { arg, .. } => {
+                return Printer::print_function("arcsin", arg);
+            },
+            ByzNode::Arccos { arg, .. } => {
+                return Printer::print_function("arccos", arg);
+            },
+            ByzNode::Sinh { arg, .. } => {
+                return Printer::print_function("sinh", arg);
+            },
+            ByzNode::Cosh { arg, .. } => {
+                return Printer::print_function("cosh", arg);
+            },
+            ByzNode::Tanh { arg, .. } => {
+                return Printer::print_function("tanh", arg);
+            },
+            ByzNode::Abs { arg, .. } => {
+                return Printer::print_function("abs", arg);
+            },
+            ByzNode::Min { args, .. } => {
+                let mut pieces = vec![Piece::Text("min(".to_string())];
+                Printer::print_args(args, &mut pieces);
+                pieces.push(Piece::Text(")".to_string()));
+                return Layout::Pieces(pieces, Precedence::Atom);
+            },
+            ByzNode::Max { args, .. } => {
+                let mut pieces = vec![Piece::Text("max(".to_string())];
+                Printer::print_args(args, &mut pieces);
+                pieces.push(Piece::Text(")".to_string()));
+                return Layout::Pieces(pieces, Precedence::Atom);
+            },
+            ByzNode::Floor { arg, .. } => {
+                return Printer::print_function("floor", arg);
+            },
+            ByzNode::Ceil { arg, .. } => {
+                return Printer::print_function("ceil", arg);
+            },
+            ByzNode::Frac { arg, .. } => {
+                return Printer::print_function("frac", arg);
+            },
+            ByzNode::Log { base, arg, .. } => {
+                return Layout::Pieces(vec![
+                    Piece::Text("log(".to_string()), Piece::Node(base, Precedence::Sum), Piece::Text(", ".to_string()), Piece::Node(arg, Precedence::Sum), Piece::Text(")".to_string())
+                ], Precedence::Atom);
+            },
+            ByzNode::Variable { name } => {
+                return Layout::Pieces(vec![Piece::Text(variable_name(name))], Precedence::Atom);
+            },
+            ByzNode::RealPow { base, exp, .. } => {
+                return Layout::Pieces(vec![Piece::Node(base, Precedence::Atom), Piece::Text("^".to_string()), Piece::Node(exp, Precedence::Power)], Precedence::Power);
+            },
+            ByzNode::AlgebraicRoot { polynomial, index } => {
+                return Layout::Pieces(vec![Piece::Text(format!("root({}, {})", index, polynomial))], Precedence::Atom);
+            }
+        }
+    }
+}
+
+/// Conventional infix notation with only the parentheses precedence needs, e.g. "x^2 - 2*pi*x/3 + 1"
+/// Terms are printed with their own signs rather than as "+ -1*x", and negative powers go in a denominator
+/// The alternate form {:#} writes x^(1/2) as sqrt(x)
+/// The output uses the same names as FromStr, so it parses back to the same value
+/// (Variables whose names aren't plain identifiers, or are taken by a constant like pi, are written in double quotes)
+/// The tree is printed without recursing, so this works on trees of any depth
+impl Display for ByzNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let printer = Printer { sqrt: f.alternate() };
+
+        let mut out = String::new();
+        printer.print(self, &mut out);
+        return write!(f, "{}", out);
+    }
+}
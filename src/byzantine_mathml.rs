@@ -0,0 +1,264 @@
+use num_bigint::BigUint;
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
+use crate::byzantine_format::{product_parts, Precedence};
+use crate::byznode_sorted_vec::ByzNodeVec;
+use crate::rational::Rational;
+
+/// Invisible times, so 2x is marked up as a product without anything being drawn between the factors
+const INVISIBLE_TIMES: &str = "<mo>&#x2062;</mo>";
+/// Function application, what separates sin from its argument
+const APPLY_FUNCTION: &str = "<mo>&#x2061;</mo>";
+
+fn escape(s: &str) -> String {
+    return s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+fn fenced(open: &str, s: &str, close: &str) -> String {
+    return format!("<mrow><mo>{}</mo>{}<mo>{}</mo></mrow>", open, s, close);
+}
+
+/// node's markup, in parentheses if it binds looser than min
+fn mathml_at(node: &ByzNode, min: Precedence) -> String {
+    let (s, precedence) = mathml(node);
+
+    if precedence < min {
+        return fenced("(", &s, ")");
+    }
+    return s;
+}
+
+fn constant_mathml(transitive_const: &TransitiveConsts) -> String {
+    match transitive_const {
+        TransitiveConsts::Pi => {
+            return "<mi>&#x3C0;</mi>".to_string();
+        },
+        TransitiveConsts::Euler => {
+            return "<mi>e</mi>".to_string();
+        },
+        TransitiveConsts::Ln2 => {
+            return format!("<mrow><mi>ln</mi>{}<mn>2</mn></mrow>", APPLY_FUNCTION);
+        },
+        TransitiveConsts::GoldenRatio => {
+            return "<mi>&#x3C6;</mi>".to_string();
+        },
+        TransitiveConsts::EulerMascheroni => {
+            return "<mi>&#x3B3;</mi>".to_string();
+        },
+        TransitiveConsts::Catalan => {
+            return "<mi>G</mi>".to_string();
+        },
+        TransitiveConsts::Apery => {
+            return format!("<mrow><mi>&#x3B6;</mi>{}{}</mrow>", APPLY_FUNCTION, fenced("(", "<mn>3</mn>", ")"));
+        }
+    }
+}
+
+fn function_mathml(name: &str, arg: &ByzNode) -> (String, Precedence) {
+    return (format!("<mrow><mi>{}</mi>{}{}</mrow>", name, APPLY_FUNCTION, fenced("(", &mathml_at(arg, Precedence::Sum), ")")), Precedence::Atom);
+}
+
+fn args_mathml(name: &str, args: &[ByzRc<ByzNode>]) -> (String, Precedence) {
+    let args = args.iter().map(|x| mathml_at(x, Precedence::Sum)).collect::<Vec<String>>().join("<mo>,</mo>");
+    return (format!("<mrow><mi>{}</mi>{}{}</mrow>", name, APPLY_FUNCTION, fenced("(", &args, ")")), Precedence::Atom);
+}
+
+/// x^p for a positive p, with square roots drawn as radicals
+fn factor_mathml(power: &Rational, node: &ByzNode) -> (String, Precedence) {
+    if power.is_one() {
+        return mathml(node);
+    }
+    if *power == Rational::from((1, 2)) {
+        return (format!("<msqrt>{}</msqrt>", mathml_at(node, Precedence::Sum)), Precedence::Atom);
+    }
+
+    return (format!("<msup>{}{}</msup>", mathml_at(node, Precedence::Atom), rational_mathml(power).0), Precedence::Power);
+}
+
+fn rational_mathml(rational: &Rational) -> (String, Precedence) {
+    let (s, precedence) = product_mathml(rational, &[]);
+
+    if rational.is_negative() {
+        return (format!("<mrow><mo>-</mo>{}</mrow>", s), precedence.min(Precedence::Negation));
+    }
+    return (s, precedence);
+}
+
+/// |k| * x^a * y^b * ..., as a fraction if anything ends up in the denominator (the sign of k is left to the caller)
+fn product_mathml(k: &Rational, factors: &[(Rational, &ByzRc<ByzNode>)]) -> (String, Precedence) {
+    let mut k = k.abs();
+    k.simplify();
+
+    let mut numer: Vec<(String, Precedence)> = Vec::new();
+    let mut denom: Vec<(String, Precedence)> = Vec::new();
+
+    if *k.numer() != BigUint::from(1_u8) || factors.iter().all(|x| x.0.is_negative()) {
+        numer.push((format!("<mn>{}</mn>", k.numer()), Precedence::Atom));
+    }
+    if *k.denom() != BigUint::from(1_u8) {
+        denom.push((format!("<mn>{}</mn>", k.denom()), Precedence::Atom));
+    }
+
+    for (power, node) in factors {
+        if power.is_negative() {
+            denom.push(factor_mathml(&power.abs(), node));
+        } else {
+            numer.push(factor_mathml(power, node));
+        }
+    }
+
+    // A lone factor needs no parentheses inside a fraction, the bar already groups it
+    let join = |parts: &[(String, Precedence)]| {
+        if parts.len() == 1 {
+            return parts[0].0.clone();
+        }
+
+        let parts = parts.iter()
+            .map(|x| if x.1 < Precedence::Power { fenced("(", &x.0, ")") } else { x.0.clone() })
+            .collect::<Vec<String>>()
+            .join(INVISIBLE_TIMES);
+        return format!("<mrow>{}</mrow>", parts);
+    };
+
+    if denom.is_empty() {
+        if numer.len() == 1 {
+            return numer.remove(0);
+        }
+        return (join(&numer), Precedence::Product);
+    }
+
+    return (format!("<mfrac>{}{}</mfrac>", join(&numer), join(&denom)), Precedence::Product);
+}
+
+fn mathml(node: &ByzNode) -> (String, Precedence) {
+    match node {
+        ByzNode::Rational { rational } => {
+            return rational_mathml(rational);
+        },
+        ByzNode::TransitiveConst { transitive_const } => {
+            return (constant_mathml(transitive_const), Precedence::Atom);
+        },
+        ByzNode::Add { addends } => {
+            // (negative, magnitude) of every term, with the rational part going last
+            let mut terms: Vec<(bool, (String, Precedence))> = addends.get_vec().iter()
+                .map(|x| {
+                    let (k, factors) = product_parts(&x.0, &x.1);
+                    return (k.is_negative(), product_mathml(&k, &factors));
+                })
+                .collect();
+
+            let rational_part = addends.get_rational_part();
+            if !rational_part.is_zero() || terms.is_empty() {
+                terms.push((rational_part.is_negative(), product_mathml(rational_part, &[])));
+            }
+
+            if terms.len() == 1 {
+                let (negative, (s, precedence)) = terms.remove(0);
+
+                if negative {
+                    return (format!("<mrow><mo>-</mo>{}</mrow>", s), precedence.min(Precedence::Negation));
+                }
+                return (s, precedence);
+            }
+
+            let mut s = String::new();
+            for (i, (negative, (term, _))) in terms.iter().enumerate() {
+                if *negative {
+                    s.push_str("<mo>-</mo>");
+                } else if i != 0 {
+                    s.push_str("<mo>+</mo>");
+                }
+                s.push_str(term);
+            }
+
+            return (format!("<mrow>{}</mrow>", s), Precedence::Sum);
+        },
+        ByzNode::Mul { products } => {
+            let (s, precedence) = product_mathml(products.get_rational_part(), &products.get_vec().iter().map(|x| (x.0.clone(), &x.1)).collect::<Vec<_>>());
+
+            if products.get_rational_part().is_negative() {
+                return (format!("<mrow><mo>-</mo>{}</mrow>", s), precedence.min(Precedence::Negation));
+            }
+            return (s, precedence);
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            return product_mathml(&Rational::one(), &[(exp.clone(), base)]);
+        },
+        ByzNode::Exp { arg, .. } => {
+            return (format!("<msup><mi>e</mi>{}</msup>", mathml(arg).0), Precedence::Power);
+        },
+        ByzNode::Ln { arg, .. } => {
+            return function_mathml("ln", arg);
+        },
+        ByzNode::Sin { arg, .. } => {
+            return function_mathml("sin", arg);
+        },
+        ByzNode::Cos { arg, .. } => {
+            return function_mathml("cos", arg);
+        },
+        ByzNode::Tan { arg, .. } => {
+            return function_mathml("tan", arg);
+        },
+        ByzNode::Arctan { arg, .. } => {
+            return function_mathml("arctan", arg);
+        },
+        ByzNode::Arcsin { arg, .. } => {
+            return function_mathml("arcsin", arg);
+        },
+        ByzNode::Arccos { arg, .. } => {
+            return function_mathml("arccos", arg);
+        },
+        ByzNode::Sinh { arg, .. } => {
+            return function_mathml("sinh", arg);
+        },
+        ByzNode::Cosh { arg, .. } => {
+            return function_mathml("cosh", arg);
+        },
+        ByzNode::Tanh { arg, .. } => {
+            return function_mathml("tanh", arg);
+        },
+        ByzNode::Abs { arg, .. } => {
+            return (fenced("|", &mathml_at(arg, Precedence::Sum), "|"), Precedence::Atom);
+        },
+        ByzNode::Min { args, .. } => {
+            return args_mathml("min", args);
+        },
+        ByzNode::Max { args, .. } => {
+            return args_mathml("max", args);
+        },
+        ByzNode::Floor { arg, .. } => {
+            return (fenced("&#x230A;", &mathml_at(arg, Precedence::Sum), "&#x230B;"), Precedence::Atom);
+        },
+        ByzNode::Ceil { arg, .. } => {
+            return (fenced("&#x2308;", &mathml_at(arg, Precedence::Sum), "&#x2309;"), Precedence::Atom);
+        },
+        ByzNode::Frac { arg, .. } => {
+            return function_mathml("frac", arg);
+        },
+        ByzNode::Log { base, arg, .. } => {
+            let log = format!("<msub><mi>log</mi>{}</msub>", mathml(base).0);
+            return (format!("<mrow>{}{}{}</mrow>", log, APPLY_FUNCTION, fenced("(", &mathml_at(arg, Precedence::Sum), ")")), Precedence::Atom);
+        },
+        ByzNode::Variable { name } => {
+            return (format!("<mi>{}</mi>", escape(name)), Precedence::Atom);
+        },
+        ByzNode::RealPow { base, exp, .. } => {
+            return (format!("<msup>{}{}</msup>", mathml_at(base, Precedence::Atom), mathml(exp).0), Precedence::Power);
+        },
+        ByzNode::AlgebraicRoot { polynomial, index } => {
+            let polynomial = polynomial.to_byznode(&ByzNode::Variable { name: "x".to_string() });
+            let root = format!("<msub><mi>root</mi><mn>{}</mn></msub>", index);
+            return (format!("<mrow>{}{}{}</mrow>", root, APPLY_FUNCTION, fenced("(", &mathml(&polynomial).0, ")")), Precedence::Atom);
+        }
+    }
+}
+
+impl ByzNode {
+    /// Presentation MathML for the expression, a whole <math> element that browsers can render as is
+    /// Laid out like Display (signs pulled out of sums, negative powers in a denominator) but with real fractions, radicals and superscripts
+    /// Constants are drawn with their usual symbols, so Ln2 is ln 2 and Apery is zeta(3)
+    /// An AlgebraicRoot is drawn as root_k(p) with its polynomial p written in x, like Display
+    pub fn to_mathml(&self) -> String {
+        return format!("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>", mathml(self).0);
+    }
+}
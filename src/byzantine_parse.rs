@@ -0,0 +1,430 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
+use crate::generate_byznode_utils::checked_algebraic_root;
+use crate::byznode_simplify::{finish_add, finish_mul, push_addend, push_factor};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec};
+use crate::rational::{Rational, Sign};
+use crate::rational_parse::parse_unsigned_decimal;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseByzNodeError {
+    /// The string ended where more was expected (like after a trailing '+' or an unclosed parenthesis)
+    UnexpectedEnd,
+    /// Something that doesn't fit at this byte offset
+    UnexpectedChar(usize),
+    /// A malformed number starting at this byte offset, like "1.2.3"
+    InvalidNumber(usize),
+    UnknownFunction(String),
+    /// The function was given the wrong number of arguments
+    ArgumentCount(String),
+    /// root(k, p) where k isn't a whole number or p isn't a polynomial in x with more than k real roots
+    InvalidRoot,
+    /// Parentheses, calls, signs or powers nested more than MAX_PARSE_DEPTH deep, at this byte offset
+    TooDeep(usize)
+}
+
+impl Display for ParseByzNodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ParseByzNodeError::UnexpectedEnd => {
+                return write!(f, "unexpected end of expression");
+            },
+            ParseByzNodeError::UnexpectedChar(position) => {
+                return write!(f, "unexpected character at position {}", position);
+            },
+            ParseByzNodeError::InvalidNumber(position) => {
+                return write!(f, "invalid number at position {}", position);
+            },
+            ParseByzNodeError::UnknownFunction(name) => {
+                return write!(f, "unknown function \"{}\"", name);
+            },
+            ParseByzNodeError::ArgumentCount(name) => {
+                return write!(f, "wrong number of arguments for \"{}\"", name);
+            },
+            ParseByzNodeError::InvalidRoot => {
+                return write!(f, "root(k, p) needs a whole number k and a polynomial p in x with more than k real roots");
+            },
+            ParseByzNodeError::TooDeep(position) => {
+                return write!(f, "expression nested more than {} deep at position {}", MAX_PARSE_DEPTH, position);
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseByzNodeError {}
+
+/// The names that parse as transitive constants rather than variables
+pub(crate) fn named_constant(name: &str) -> Option<TransitiveConsts> {
+    match name {
+        "pi" => {
+            return Some(TransitiveConsts::Pi);
+        },
+        "e" => {
+            return Some(TransitiveConsts::Euler);
+        },
+        "ln2" => {
+            return Some(TransitiveConsts::Ln2);
+        },
+        "phi" => {
+            return Some(TransitiveConsts::GoldenRatio);
+        },
+        "gamma" => {
+            return Some(TransitiveConsts::EulerMascheroni);
+        },
+        "catalan" => {
+            return Some(TransitiveConsts::Catalan);
+        },
+        "apery" => {
+            return Some(TransitiveConsts::Apery);
+        },
+        _ => {
+            return None;
+        }
+    }
+}
+
+fn unary_function(name: &str, arg: ByzNode) -> Option<ByzNode> {
+    let arg = ByzRc::new(arg);
+
+    let node = match name {
+        "exp" => ByzNode::Exp { arg, hash_cache: OnceLock::new() },
+        "ln" => ByzNode::Ln { arg, hash_cache: OnceLock::new() },
+        "sin" => ByzNode::Sin { arg, hash_cache: OnceLock::new() },
+        "cos" => ByzNode::Cos { arg, hash_cache: OnceLock::new() },
+        "tan" => ByzNode::Tan { arg, hash_cache: OnceLock::new() },
+        "arctan" | "atan" => ByzNode::Arctan { arg, hash_cache: OnceLock::new() },
+        "arcsin" | "asin" => ByzNode::Arcsin { arg, hash_cache: OnceLock::new() },
+        "arccos" | "acos" => ByzNode::Arccos { arg, hash_cache: OnceLock::new() },
+        "sinh" => ByzNode::Sinh { arg, hash_cache: OnceLock::new() },
+        "cosh" => ByzNode::Cosh { arg, hash_cache: OnceLock::new() },
+        "tanh" => ByzNode::Tanh { arg, hash_cache: OnceLock::new() },
+        "abs" => ByzNode::Abs { arg, hash_cache: OnceLock::new() },
+        "floor" => ByzNode::Floor { arg, hash_cache: OnceLock::new() },
+        "ceil" => ByzNode::Ceil { arg, hash_cache: OnceLock::new() },
+        "frac" => ByzNode::Frac { arg, hash_cache: OnceLock::new() },
+        "sqrt" => power(ByzRc::unwrap_or_clone(arg), ByzNode::Rational { rational: Rational::from((1, 2)) }),
+        _ => {
+            return None;
+        }
+    };
+
+    return Some(node);
+}
+
+/// root(index, polynomial), with polynomial written in x
+fn algebraic_root(index: &ByzNode, polynomial: &ByzNode) -> Option<ByzNode> {
+    let ByzNode::Rational { rational } = index else {
+        return None;
+    };
+    if !rational.is_int() || rational.is_negative() {
+        return None;
+    }
+
+    let index = usize::try_from(&*rational.numer()).ok()?;
+    let polynomial = polynomial.as_polynomial(&ByzNode::Variable { name: "x".to_string() })?;
+
+    return checked_algebraic_root(&polynomial, index);
+}
+
+/// base^exp, folding it into a Mul when exp is rational
+fn power(base: ByzNode, exp: ByzNode) -> ByzNode {
+    if let ByzNode::Rational { rational } = &exp {
+        let mut products = ByzNodePowerMulVec::new();
+        push_factor(&mut products, rational, ByzRc::new(base));
+        return finish_mul(products);
+    }
+
+    return ByzNode::RealPow { base: ByzRc::new(base), exp: ByzRc::new(exp), hash_cache: OnceLock::new() };
+}
+
+/// Builds the node for a call to name once its arguments are parsed, kept out of Parser::parse_call so the recursion doesn't carry its locals
+fn call(name: &str, args: Vec<ByzNode>) -> Result<ByzNode, ParseByzNodeError> {
+    match name {
+        "min" | "max" => {
+            let mut args: Vec<ByzRc<ByzNode>> = args.into_iter().map(ByzRc::new).collect();
+            args.sort();
+            args.dedup();
+
+            if name == "min" {
+                return Ok(ByzNode::Min { args, hash_cache: OnceLock::new() });
+            }
+            return Ok(ByzNode::Max { args, hash_cache: OnceLock::new() });
+        },
+        "log" => {
+            let [base, arg] = <[ByzNode; 2]>::try_from(args).map_err(|_| ParseByzNodeError::ArgumentCount(name.to_string()))?;
+            return Ok(ByzNode::Log { base: ByzRc::new(base), arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+        },
+        "root" => {
+            let [index, polynomial] = <[ByzNode; 2]>::try_from(args).map_err(|_| ParseByzNodeError::ArgumentCount(name.to_string()))?;
+            return algebraic_root(&index, &polynomial).ok_or(ParseByzNodeError::InvalidRoot);
+        },
+        _ => {
+            let [arg] = <[ByzNode; 1]>::try_from(args).map_err(|_| ParseByzNodeError::ArgumentCount(name.to_string()))?;
+            return unary_function(name, arg).ok_or_else(|| ParseByzNodeError::UnknownFunction(name.to_string()));
+        }
+    }
+}
+
+/// Expressions nested deeper than this are refused by from_str, as parsing them recurses
+pub const MAX_PARSE_DEPTH: usize = 128;
+
+/// Recursive descent over the grammar
+/// expr = term (('+' | '-') term)*, term = unary (('*' | '/') unary)*, unary = ('-' | '+') unary | power,
+/// power = atom ('^' unary)?, atom = number | name | '"' name '"' | name '(' expr (',' expr)* ')' | '(' expr ')'
+/// So ^ binds tighter than a leading minus (-2^2 is -4) and is right associative (2^3^2 is 2^9)
+struct Parser<'a> {
+    s: &'a str,
+    position: usize,
+    /// How many parse_unary calls are under way, every level of nesting goes through one
+    depth: usize
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.s[self.position..].chars().next() && c.is_whitespace() {
+            self.position += c.len_utf8();
+        }
+    }
+
+    /// The next character that isn't whitespace, without consuming it
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        return self.s[self.position..].chars().next();
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseByzNodeError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += c.len_utf8();
+                return Ok(());
+            },
+            Some(_) => {
+                return Err(ParseByzNodeError::UnexpectedChar(self.position));
+            },
+            None => {
+                return Err(ParseByzNodeError::UnexpectedEnd);
+            }
+        }
+    }
+
+    /// Consumes the longest run of characters matching f
+    fn take_while<F>(&mut self, f: F) -> &'a str where F: Fn(char) -> bool {
+        let start = self.position;
+        while let Some(c) = self.s[self.position..].chars().next() && f(c) {
+            self.position += c.len_utf8();
+        }
+
+        return &self.s[start..self.position];
+    }
+
+    fn parse_expr(&mut self) -> Result<ByzNode, ParseByzNodeError> {
+        let first = self.parse_term()?;
+
+        // A lone term stays as it is, wrapping (2*x) into a sum would keep it from flattening into a product around it
+        if !matches!(self.peek(), Some('+' | '-')) {
+            return Ok(first);
+        }
+
+        let mut addends = ByzNodeCoefficientAddVec::new();
+        push_addend(&mut addends, &Rational::one(), ByzRc::new(first));
+
+        loop {
+            let coefficient = match self.peek() {
+                Some('+') => Rational::one(),
+                Some('-') => Rational::from(-1),
+                _ => {
+                    return Ok(finish_add(addends));
+                }
+            };
+            self.position += 1;
+
+            push_addend(&mut addends, &coefficient, ByzRc::new(self.parse_term()?));
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<ByzNode, ParseByzNodeError> {
+        let mut products = ByzNodePowerMulVec::new();
+        push_factor(&mut products, &Rational::one(), ByzRc::new(self.parse_unary()?));
+
+        loop {
+            let power = match self.peek() {
+                Some('*') => Rational::one(),
+                Some('/') => Rational::from(-1),
+                _ => {
+                    return Ok(finish_mul(products));
+                }
+            };
+            self.position += 1;
+
+            push_factor(&mut products, &power, ByzRc::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<ByzNode, ParseByzNodeError> {
+        if self.depth == MAX_PARSE_DEPTH {
+            return Err(ParseByzNodeError::TooDeep(self.position));
+        }
+
+        self.depth += 1;
+        let node = match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                self.parse_unary().map(|x| x.negate())
+            },
+            Some('+') => {
+                self.position += 1;
+                self.parse_unary()
+            },
+            _ => {
+                self.parse_power()
+            }
+        };
+        self.depth -= 1;
+
+        return node;
+    }
+
+    fn parse_power(&mut self) -> Result<ByzNode, ParseByzNodeError> {
+        let base = self.parse_atom()?;
+
+        if self.peek() != Some('^') {
+            return Ok(base);
+        }
+        self.position += 1;
+
+        let exp = self.parse_unary()?;
+        return Ok(power(base, exp));
+    }
+
+    fn parse_atom(&mut self) -> Result<ByzNode, ParseByzNodeError> {
+        let start = self.position;
+
+        match self.peek() {
+            None => {
+                return Err(ParseByzNodeError::UnexpectedEnd);
+            },
+            Some('(') => {
+                self.position += 1;
+                let node = self.parse_expr()?;
+                self.expect(')')?;
+                return Ok(node);
+            },
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                return self.parse_number();
+            },
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+
+                if self.peek() == Some('(') {
+                    self.position += 1;
+                    return self.parse_call(name);
+                }
+
+                if let Some(transitive_const) = named_constant(name) {
+                    return Ok(ByzNode::TransitiveConst { transitive_const });
+                }
+                return Ok(ByzNode::Variable { name: name.to_string() });
+            },
+            Some('"') => {
+                self.position += 1;
+                return Ok(ByzNode::Variable { name: self.parse_quoted_name()? });
+            },
+            Some(_) => {
+                return Err(ParseByzNodeError::UnexpectedChar(start.max(self.position)));
+            }
+        }
+    }
+
+    /// An unsigned decimal literal like 12 or 0.25, kept out of parse_atom so the recursion doesn't carry its locals
+    fn parse_number(&mut self) -> Result<ByzNode, ParseByzNodeError> {
+        let start = self.position;
+        let digits = self.take_while(|c| c.is_ascii_digit() || c == '.');
+        let (numer, denom) = parse_unsigned_decimal(digits).map_err(|_| ParseByzNodeError::InvalidNumber(start))?;
+
+        return Ok(ByzNode::Rational { rational: Rational::new(Sign::Pos, numer, denom) });
+    }
+
+    /// A variable name written in double quotes, whose opening '"' was already consumed
+    /// A backslash escapes the character after it, so "a \"b\"" is the name a "b"
+    fn parse_quoted_name(&mut self) -> Result<String, ParseByzNodeError> {
+        let mut name = String::new();
+        let mut chars = self.s[self.position..].chars();
+
+        loop {
+            let c = chars.next().ok_or(ParseByzNodeError::UnexpectedEnd)?;
+            self.position += c.len_utf8();
+
+            match c {
+                '"' => {
+                    return Ok(name);
+                },
+                '\\' => {
+                    let escaped = chars.next().ok_or(ParseByzNodeError::UnexpectedEnd)?;
+                    self.position += escaped.len_utf8();
+                    name.push(escaped);
+                },
+                _ => {
+                    name.push(c);
+                }
+            }
+        }
+    }
+
+    /// The arguments of a call to name, whose '(' was already consumed
+    fn parse_call(&mut self, name: &str) -> Result<ByzNode, ParseByzNodeError> {
+        let mut args = vec![self.parse_expr()?];
+        while self.peek() == Some(',') {
+            self.position += 1;
+            args.push(self.parse_expr()?);
+        }
+        self.expect(')')?;
+
+        return call(name, args);
+    }
+}
+
+/// Parses expressions like "9801/(2206*2^(1/2)) - pi", with +, -, *, /, ^, parentheses and decimal literals
+/// The constants are pi, e, ln2, phi, gamma, catalan and apery, and any other name is a Variable
+/// A name in double quotes, like "pi" or "a b", is always a Variable, with '\\' escaping the character after it
+/// Functions are exp, ln, log(base, x), sqrt, sin, cos, tan, arctan, arcsin, arccos (or atan, asin, acos), sinh, cosh, tanh,
+/// abs, floor, ceil, frac, min and max with any number of arguments, and root(k, p) for the k-th smallest real root of a polynomial p in x
+/// Sums and products are flattened and rational parts are merged as they're parsed, so "2*3*x + 1 + 1" is 6*x + 2
+/// Parsing recurses, so anything nested more than MAX_PARSE_DEPTH deep is refused with ParseByzNodeError::TooDeep
+impl FromStr for ByzNode {
+    type Err = ParseByzNodeError;
+
+    fn from_str(s: &str) -> Result<ByzNode, ParseByzNodeError> {
+        let mut parser = Parser { s, position: 0, depth: 0 };
+        let node = parser.parse_expr()?;
+
+        if parser.peek().is_some() {
+            return Err(ParseByzNodeError::UnexpectedChar(parser.position));
+        }
+
+        return Ok(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<ByzNode, ParseByzNodeError> {
+        return s.parse();
+    }
+
+    #[test]
+    fn parse_depth_limit() {
+        let nested = |open: &str, depth: usize| format!("{}1{}", open.repeat(depth), ")".repeat(depth));
+
+        assert!(parse(&nested("(", MAX_PARSE_DEPTH - 1)).is_ok());
+        assert_eq!(parse(&nested("(", MAX_PARSE_DEPTH)), Err(ParseByzNodeError::TooDeep(MAX_PARSE_DEPTH)));
+        assert!(matches!(parse(&nested("(", 10_000)), Err(ParseByzNodeError::TooDeep(_))));
+        assert!(matches!(parse(&nested("sin(", 10_000)), Err(ParseByzNodeError::TooDeep(_))));
+        assert!(matches!(parse(&format!("{}x", "-".repeat(10_000))), Err(ParseByzNodeError::TooDeep(_))));
+        assert!(matches!(parse(&format!("{}2", "2^".repeat(10_000))), Err(ParseByzNodeError::TooDeep(_))));
+    }
+}
@@ -0,0 +1,335 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::generate_byznode_utils::is_valid_algebraic_root;
+use crate::rational::Rational;
+use crate::rational_polynomial::RationalPolynomial;
+
+/// The serialized form of the sorted vecs, the terms in the order they're stored
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ByzNodeVecRepr {
+    rational_part: Rational,
+    terms: Vec<(Rational, ByzNode)>
+}
+
+fn vec_repr<T>(vec: &T) -> ByzNodeVecRepr where T: ByzNodeVec {
+    return ByzNodeVecRepr {
+        rational_part: vec.get_rational_part().clone(),
+        terms: vec.get_vec().iter().map(|x| (x.0.clone(), x.1.as_ref().clone())).collect()
+    };
+}
+
+/// Rebuilt through insert, so terms come out sorted and merged whatever order (or duplicates) the input had
+fn vec_from_repr<T>(mut vec: T, repr: ByzNodeVecRepr) -> T where T: ByzNodeVec {
+    vec.insert_rational(repr.rational_part);
+    for term in repr.terms {
+        vec.insert(term);
+    }
+
+    return vec;
+}
+
+impl From<ByzNodeCoefficientAddVec> for ByzNodeVecRepr {
+    fn from(vec: ByzNodeCoefficientAddVec) -> ByzNodeVecRepr {
+        return vec_repr(&vec);
+    }
+}
+
+impl From<ByzNodeVecRepr> for ByzNodeCoefficientAddVec {
+    fn from(repr: ByzNodeVecRepr) -> ByzNodeCoefficientAddVec {
+        return vec_from_repr(ByzNodeCoefficientAddVec::new(), repr);
+    }
+}
+
+impl From<ByzNodePowerMulVec> for ByzNodeVecRepr {
+    fn from(vec: ByzNodePowerMulVec) -> ByzNodeVecRepr {
+        return vec_repr(&vec);
+    }
+}
+
+impl From<ByzNodeVecRepr> for ByzNodePowerMulVec {
+    fn from(repr: ByzNodeVecRepr) -> ByzNodePowerMulVec {
+        return vec_from_repr(ByzNodePowerMulVec::new(), repr);
+    }
+}
+
+/// The serialized form of a ByzNode, the same variants with children held by value
+#[derive(Serialize, Deserialize)]
+pub(crate) enum ByzNodeRepr {
+    Rational {
+        rational: Rational
+    },
+    TransitiveConst {
+        transitive_const: TransitiveConsts
+    },
+    Add {
+        addends: ByzNodeCoefficientAddVec
+    },
+    Mul {
+        products: ByzNodePowerMulVec
+    },
+    Pow {
+        base: ByzNode,
+        exp: Rational
+    },
+    Exp {
+        arg: ByzNode
+    },
+    Ln {
+        arg: ByzNode
+    },
+    Sin {
+        arg: ByzNode
+    },
+    Cos {
+        arg: ByzNode
+    },
+    Tan {
+        arg: ByzNode
+    },
+    Arctan {
+        arg: ByzNode
+    },
+    Arcsin {
+        arg: ByzNode
+    },
+    Arccos {
+        arg: ByzNode
+    },
+    Sinh {
+        arg: ByzNode
+    },
+    Cosh {
+        arg: ByzNode
+    },
+    Tanh {
+        arg: ByzNode
+    },
+    Abs {
+        arg: ByzNode
+    },
+    Min {
+        args: Vec<ByzNode>
+    },
+    Max {
+        args: Vec<ByzNode>
+    },
+    Floor {
+        arg: ByzNode
+    },
+    Ceil {
+        arg: ByzNode
+    },
+    Frac {
+        arg: ByzNode
+    },
+    Log {
+        base: ByzNode,
+        arg: ByzNode
+    },
+    Variable {
+        name: String
+    },
+    RealPow {
+        base: ByzNode,
+        exp: ByzNode
+    },
+    AlgebraicRoot {
+        coefficients: Vec<Rational>,
+        index: usize
+    }
+}
+
+impl From<ByzNode> for ByzNodeRepr {
+    fn from(byznode: ByzNode) -> ByzNodeRepr {
+        // ByzNode implements Drop so its fields can't be moved out, they're cloned instead (children are only Rcs)
+        match &byznode {
+            ByzNode::Rational { rational } => {
+                return ByzNodeRepr::Rational { rational: rational.clone() };
+            },
+            ByzNode::TransitiveConst { transitive_const } => {
+                return ByzNodeRepr::TransitiveConst { transitive_const: *transitive_const };
+            },
+            ByzNode::Add { addends } => {
+                return ByzNodeRepr::Add { addends: addends.clone() };
+            },
+            ByzNode::Mul { products } => {
+                return ByzNodeRepr::Mul { products: products.clone() };
+            },
+            ByzNode::Pow { base, exp, .. } => {
+                return ByzNodeRepr::Pow { base: base.as_ref().clone(), exp: exp.clone() };
+            },
+            ByzNode::Exp { arg, .. } => {
+                return ByzNodeRepr::Exp { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Ln { arg, .. } => {
+                return ByzNodeRepr::Ln { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Sin { arg, .. } => {
+                return ByzNodeRepr::Sin { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Cos { arg, .. } => {
+                return ByzNodeRepr::Cos { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Tan { arg, .. } => {
+                return ByzNodeRepr::Tan { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Arctan { arg, .. } => {
+                return ByzNodeRepr::Arctan { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Arcsin { arg, .. } => {
+                return ByzNodeRepr::Arcsin { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Arccos { arg, .. } => {
+                return ByzNodeRepr::Arccos { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Sinh { arg, .. } => {
+                return ByzNodeRepr::Sinh { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Cosh { arg, .. } => {
+                return ByzNodeRepr::Cosh { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Tanh { arg, .. } => {
+                return ByzNodeRepr::Tanh { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Abs { arg, .. } => {
+                return ByzNodeRepr::Abs { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Min { args, .. } => {
+                return ByzNodeRepr::Min { args: args.iter().map(|x| x.as_ref().clone()).collect() };
+            },
+            ByzNode::Max { args, .. } => {
+                return ByzNodeRepr::Max { args: args.iter().map(|x| x.as_ref().clone()).collect() };
+            },
+            ByzNode::Floor { arg, .. } => {
+                return ByzNodeRepr::Floor { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Ceil { arg, .. } => {
+                return ByzNodeRepr::Ceil { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Frac { arg, .. } => {
+                return ByzNodeRepr::Frac { arg: arg.as_ref().clone() };
+            },
+            ByzNode::Log { base, arg, .. } => {
+                return ByzNodeRepr::Log { base: base.as_ref().clone(), arg: arg.as_ref().clone() };
+            },
+            ByzNode::Variable { name } => {
+                return ByzNodeRepr::Variable { name: name.clone() };
+            },
+            ByzNode::RealPow { base, exp, .. } => {
+                return ByzNodeRepr::RealPow { base: base.as_ref().clone(), exp: exp.as_ref().clone() };
+            },
+            ByzNode::AlgebraicRoot { polynomial, index } => {
+                return ByzNodeRepr::AlgebraicRoot { coefficients: polynomial.coefficients().to_vec(), index: *index };
+            }
+        }
+    }
+}
+
+/// Min and Max keep their arguments sorted and deduplicated, and need at least one
+fn sorted_args(args: Vec<ByzNode>, name: &str) -> Result<Vec<ByzRc<ByzNode>>, String> {
+    if args.is_empty() {
+        return Err(format!("{} needs at least one argument", name));
+    }
+
+    let mut args: Vec<ByzRc<ByzNode>> = args.into_iter().map(ByzRc::new).collect();
+    args.sort();
+    args.dedup();
+
+    return Ok(args);
+}
+
+impl TryFrom<ByzNodeRepr> for ByzNode {
+    type Error = String;
+
+    fn try_from(repr: ByzNodeRepr) -> Result<ByzNode, String> {
+        match repr {
+            ByzNodeRepr::Rational { rational } => {
+                return Ok(ByzNode::Rational { rational });
+            },
+            ByzNodeRepr::TransitiveConst { transitive_const } => {
+                return Ok(ByzNode::TransitiveConst { transitive_const });
+            },
+            ByzNodeRepr::Add { addends } => {
+                return Ok(ByzNode::Add { addends });
+            },
+            ByzNodeRepr::Mul { products } => {
+                return Ok(ByzNode::Mul { products });
+            },
+            ByzNodeRepr::Pow { base, exp } => {
+                return Ok(ByzNode::Pow { base: ByzRc::new(base), exp, hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Exp { arg } => {
+                return Ok(ByzNode::Exp { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Ln { arg } => {
+                return Ok(ByzNode::Ln { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Sin { arg } => {
+                return Ok(ByzNode::Sin { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Cos { arg } => {
+                return Ok(ByzNode::Cos { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Tan { arg } => {
+                return Ok(ByzNode::Tan { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Arctan { arg } => {
+                return Ok(ByzNode::Arctan { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Arcsin { arg } => {
+                return Ok(ByzNode::Arcsin { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Arccos { arg } => {
+                return Ok(ByzNode::Arccos { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Sinh { arg } => {
+                return Ok(ByzNode::Sinh { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Cosh { arg } => {
+                return Ok(ByzNode::Cosh { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Tanh { arg } => {
+                return Ok(ByzNode::Tanh { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Abs { arg } => {
+                return Ok(ByzNode::Abs { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Min { args } => {
+                return Ok(ByzNode::Min { args: sorted_args(args, "Min")?, hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Max { args } => {
+                return Ok(ByzNode::Max { args: sorted_args(args, "Max")?, hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Floor { arg } => {
+                return Ok(ByzNode::Floor { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Ceil { arg } => {
+                return Ok(ByzNode::Ceil { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Frac { arg } => {
+                return Ok(ByzNode::Frac { arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Log { base, arg } => {
+                return Ok(ByzNode::Log { base: ByzRc::new(base), arg: ByzRc::new(arg), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::Variable { name } => {
+                return Ok(ByzNode::Variable { name });
+            },
+            ByzNodeRepr::RealPow { base, exp } => {
+                return Ok(ByzNode::RealPow { base: ByzRc::new(base), exp: ByzRc::new(exp), hash_cache: OnceLock::new() });
+            },
+            ByzNodeRepr::AlgebraicRoot { coefficients, index } => {
+                let polynomial = RationalPolynomial::new(coefficients);
+                if !is_valid_algebraic_root(&polynomial, index) {
+                    return Err(format!("AlgebraicRoot needs a square free monic polynomial with more than {} real roots", index));
+                }
+
+                return Ok(ByzNode::AlgebraicRoot { polynomial, index });
+            }
+        }
+    }
+}
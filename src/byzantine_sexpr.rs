@@ -0,0 +1,514 @@
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byzantine_format::constant_name;
+use crate::byzantine_parse::named_constant;
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::byznode_visit::ByzVisitor;
+use crate::generate_byznode_utils::is_valid_algebraic_root;
+use crate::rational::Rational;
+use crate::rational_polynomial::RationalPolynomial;
+
+/// Errors carry the byte offset of whatever couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSexprError {
+    /// The string ended inside a list or a quoted name
+    UnexpectedEnd,
+    /// A stray ')', or anything after the end of the expression
+    UnexpectedChar(usize),
+    /// Something that should have been a rational, like an Add's rational part or a Pow's exponent
+    InvalidRational(usize),
+    /// A list that doesn't start with one of the node names
+    UnknownHead(usize),
+    /// An atom that is neither a rational nor a constant (variables are written (var x))
+    UnknownSymbol(usize),
+    /// A node with the wrong number of arguments
+    ArgumentCount(usize),
+    /// A root whose index isn't a whole number, or whose polynomial isn't square free and monic with enough real roots
+    InvalidAlgebraicRoot(usize),
+    /// A list nested more than MAX_SEXPR_DEPTH deep
+    TooDeep(usize)
+}
+
+impl Display for ParseSexprError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ParseSexprError::UnexpectedEnd => {
+                return write!(f, "unexpected end of s-expression");
+            },
+            ParseSexprError::UnexpectedChar(position) => {
+                return write!(f, "unexpected character at position {}", position);
+            },
+            ParseSexprError::InvalidRational(position) => {
+                return write!(f, "expected a rational at position {}", position);
+            },
+            ParseSexprError::UnknownHead(position) => {
+                return write!(f, "unknown node name at position {}", position);
+            },
+            ParseSexprError::UnknownSymbol(position) => {
+                return write!(f, "unknown symbol at position {}", position);
+            },
+            ParseSexprError::ArgumentCount(position) => {
+                return write!(f, "wrong number of arguments for the node at position {}", position);
+            },
+            ParseSexprError::InvalidAlgebraicRoot(position) => {
+                return write!(f, "invalid algebraic root at position {}", position);
+            },
+            ParseSexprError::TooDeep(position) => {
+                return write!(f, "lists nested more than {} deep at position {}", MAX_SEXPR_DEPTH, position);
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseSexprError {}
+
+fn is_atom_char(c: char) -> bool {
+    return !c.is_whitespace() && c != '(' && c != ')' && c != '"';
+}
+
+fn unary_sexpr(node: &ByzNode) -> Option<(&'static str, &ByzRc<ByzNode>)> {
+    match node {
+        ByzNode::Exp { arg, .. } => Some(("exp", arg)),
+        ByzNode::Ln { arg, .. } => Some(("ln", arg)),
+        ByzNode::Sin { arg, .. } => Some(("sin", arg)),
+        ByzNode::Cos { arg, .. } => Some(("cos", arg)),
+        ByzNode::Tan { arg, .. } => Some(("tan", arg)),
+        ByzNode::Arctan { arg, .. } => Some(("arctan", arg)),
+        ByzNode::Arcsin { arg, .. } => Some(("arcsin", arg)),
+        ByzNode::Arccos { arg, .. } => Some(("arccos", arg)),
+        ByzNode::Sinh { arg, .. } => Some(("sinh", arg)),
+        ByzNode::Cosh { arg, .. } => Some(("cosh", arg)),
+        ByzNode::Tanh { arg, .. } => Some(("tanh", arg)),
+        ByzNode::Abs { arg, .. } => Some(("abs", arg)),
+        ByzNode::Floor { arg, .. } => Some(("floor", arg)),
+        ByzNode::Ceil { arg, .. } => Some(("ceil", arg)),
+        ByzNode::Frac { arg, .. } => Some(("frac", arg)),
+        _ => None
+    }
+}
+
+fn unary_node(name: &str, arg: ByzRc<ByzNode>) -> Option<ByzNode> {
+    match name {
+        "exp" => Some(ByzNode::Exp { arg, hash_cache: OnceLock::new() }),
+        "ln" => Some(ByzNode::Ln { arg, hash_cache: OnceLock::new() }),
+        "sin" => Some(ByzNode::Sin { arg, hash_cache: OnceLock::new() }),
+        "cos" => Some(ByzNode::Cos { arg, hash_cache: OnceLock::new() }),
+        "tan" => Some(ByzNode::Tan { arg, hash_cache: OnceLock::new() }),
+        "arctan" => Some(ByzNode::Arctan { arg, hash_cache: OnceLock::new() }),
+        "arcsin" => Some(ByzNode::Arcsin { arg, hash_cache: OnceLock::new() }),
+        "arccos" => Some(ByzNode::Arccos { arg, hash_cache: OnceLock::new() }),
+        "sinh" => Some(ByzNode::Sinh { arg, hash_cache: OnceLock::new() }),
+        "cosh" => Some(ByzNode::Cosh { arg, hash_cache: OnceLock::new() }),
+        "tanh" => Some(ByzNode::Tanh { arg, hash_cache: OnceLock::new() }),
+        "abs" => Some(ByzNode::Abs { arg, hash_cache: OnceLock::new() }),
+        "floor" => Some(ByzNode::Floor { arg, hash_cache: OnceLock::new() }),
+        "ceil" => Some(ByzNode::Ceil { arg, hash_cache: OnceLock::new() }),
+        "frac" => Some(ByzNode::Frac { arg, hash_cache: OnceLock::new() }),
+        _ => None
+    }
+}
+
+/// Lists nested deeper than this are refused by from_sexpr, as reading them recurses
+/// to_sexpr has no such limit, so a tree deeper than this can be written but not read back
+pub const MAX_SEXPR_DEPTH: usize = 256;
+
+/// A list that has been opened but not closed yet, with the text that goes before and after each of its children
+struct OpenList {
+    around: Vec<(String, &'static str)>,
+    next: usize,
+    close: String
+}
+
+impl OpenList {
+    /// A list whose children are just separated by spaces
+    fn plain(children: usize, close: String) -> OpenList {
+        return OpenList { around: vec![(" ".to_string(), ""); children], next: 0, close };
+    }
+}
+
+/// The opening text of a node with children and the list it leaves open
+/// The items of an Add or Mul come after its rational part, a weight of 1 is left out so x is short for (1 x)
+fn open_list(node: &ByzNode) -> (String, OpenList) {
+    if let Some((name, _)) = unary_sexpr(node) {
+        return (format!("({}", name), OpenList::plain(1, ")".to_string()));
+    }
+
+    match node {
+        ByzNode::Add { addends } => {
+            return (format!("(add {}", addends.get_rational_part()), weighted_list(addends));
+        },
+        ByzNode::Mul { products } => {
+            return (format!("(mul {}", products.get_rational_part()), weighted_list(products));
+        },
+        ByzNode::Pow { exp, .. } => {
+            return ("(pow".to_string(), OpenList::plain(1, format!(" {})", exp)));
+        },
+        ByzNode::Min { args, .. } => {
+            return ("(min".to_string(), OpenList::plain(args.len(), ")".to_string()));
+        },
+        ByzNode::Max { args, .. } => {
+            return ("(max".to_string(), OpenList::plain(args.len(), ")".to_string()));
+        },
+        ByzNode::Log { .. } => {
+            return ("(log".to_string(), OpenList::plain(2, ")".to_string()));
+        },
+        ByzNode::RealPow { .. } => {
+            return ("(realpow".to_string(), OpenList::plain(2, ")".to_string()));
+        },
+        _ => {
+            unreachable!("nodes without children are written by write_leaf");
+        }
+    }
+}
+
+fn weighted_list<T>(vec: &T) -> OpenList where T: ByzNodeVec {
+    let around = vec.get_vec().iter().map(|(weight, _)| {
+        if weight.is_one() {
+            return (" ".to_string(), "");
+        }
+        return (format!(" ({} ", weight), ")");
+    }).collect();
+
+    return OpenList { around, next: 0, close: ")".to_string() };
+}
+
+fn write_leaf(node: &ByzNode, s: &mut String) {
+    match node {
+        ByzNode::Rational { rational } => {
+            s.push_str(&rational.to_string());
+        },
+        ByzNode::TransitiveConst { transitive_const } => {
+            s.push_str(constant_name(transitive_const));
+        },
+        ByzNode::Variable { name } => {
+            s.push_str("(var ");
+            if !name.is_empty() && name.chars().all(is_atom_char) {
+                s.push_str(name);
+            } else {
+                s.push('"');
+                s.push_str(&name.replace('\\', "\\\\").replace('"', "\\\""));
+                s.push('"');
+            }
+            s.push(')');
+        },
+        ByzNode::AlgebraicRoot { polynomial, index } => {
+            s.push_str("(root ");
+            s.push_str(&index.to_string());
+            for c in polynomial.coefficients() {
+                s.push(' ');
+                s.push_str(&c.to_string());
+            }
+            s.push(')');
+        },
+        _ => {
+            unreachable!("nodes with children are written through open_list");
+        }
+    }
+}
+
+/// Writes the text on the way through walk, so to_sexpr works on trees of any depth
+struct SexprWriter {
+    out: String,
+    open: Vec<OpenList>
+}
+
+impl ByzVisitor for SexprWriter {
+    fn enter(&mut self, node: &ByzNode) -> bool {
+        if let Some(parent) = self.open.last() {
+            self.out.push_str(&parent.around[parent.next].0);
+        }
+
+        if node.has_children() {
+            let (text, list) = open_list(node);
+            self.out.push_str(&text);
+            self.open.push(list);
+        } else {
+            write_leaf(node, &mut self.out);
+        }
+
+        return true;
+    }
+
+    fn leave(&mut self, node: &ByzNode) {
+        if node.has_children() {
+            let list = self.open.pop().unwrap();
+            self.out.push_str(&list.close);
+        }
+
+        if let Some(parent) = self.open.last_mut() {
+            self.out.push_str(parent.around[parent.next].1);
+            parent.next += 1;
+        }
+    }
+}
+
+/// The generic tree the text is read into before it's turned into nodes, each part remembers where it started
+enum Sexpr {
+    Atom(String, usize),
+    /// A quoted name, only used by var
+    Str(String, usize),
+    List(Vec<Sexpr>, usize)
+}
+
+impl Sexpr {
+    fn position(&self) -> usize {
+        match self {
+            Sexpr::Atom(_, position) | Sexpr::Str(_, position) | Sexpr::List(_, position) => {
+                return *position;
+            }
+        }
+    }
+
+    fn rational(&self) -> Result<Rational, ParseSexprError> {
+        if let Sexpr::Atom(atom, _) = self && let Ok(rational) = atom.parse::<Rational>() {
+            return Ok(rational);
+        }
+
+        return Err(ParseSexprError::InvalidRational(self.position()));
+    }
+
+    /// An item of an Add or Mul, either (weight node) or just node
+    fn weighted(&self) -> Result<(Rational, ByzNode), ParseSexprError> {
+        if let Sexpr::List(items, _) = self && let [weight, node] = items.as_slice() && let Ok(weight) = weight.rational() {
+            return Ok((weight, node.to_byznode()?));
+        }
+
+        return Ok((Rational::one(), self.to_byznode()?));
+    }
+
+    fn to_byznode(&self) -> Result<ByzNode, ParseSexprError> {
+        let (items, position) = match self {
+            Sexpr::Atom(atom, position) => {
+                if let Ok(rational) = atom.parse::<Rational>() {
+                    return Ok(ByzNode::Rational { rational });
+                }
+                if let Some(transitive_const) = named_constant(atom) {
+                    return Ok(ByzNode::TransitiveConst { transitive_const });
+                }
+
+                return Err(ParseSexprError::UnknownSymbol(*position));
+            },
+            Sexpr::Str(_, position) => {
+                return Err(ParseSexprError::UnknownSymbol(*position));
+            },
+            Sexpr::List(items, position) => (items, *position)
+        };
+
+        let Some(Sexpr::Atom(head, _)) = items.first() else {
+            return Err(ParseSexprError::UnknownHead(position));
+        };
+        let args = &items[1..];
+
+        let nodes = || args.iter().map(|x| x.to_byznode().map(ByzRc::new)).collect::<Result<Vec<_>, _>>();
+        let arity = |n: usize| if args.len() == n { Ok(()) } else { Err(ParseSexprError::ArgumentCount(position)) };
+
+        match head.as_str() {
+            "add" | "mul" => {
+                let Some((rational_part, terms)) = args.split_first() else {
+                    return Err(ParseSexprError::ArgumentCount(position));
+                };
+
+                if head == "add" {
+                    let mut addends = ByzNodeCoefficientAddVec::new();
+                    addends.insert_rational(rational_part.rational()?);
+                    for x in terms {
+                        addends.insert(x.weighted()?);
+                    }
+                    return Ok(ByzNode::Add { addends });
+                }
+
+                let mut products = ByzNodePowerMulVec::new();
+                products.insert_rational(rational_part.rational()?);
+                for x in terms {
+                    products.insert(x.weighted()?);
+                }
+                return Ok(ByzNode::Mul { products });
+            },
+            "pow" => {
+                arity(2)?;
+                return Ok(ByzNode::Pow { base: ByzRc::new(args[0].to_byznode()?), exp: args[1].rational()?, hash_cache: OnceLock::new() });
+            },
+            "min" | "max" => {
+                if args.is_empty() {
+                    return Err(ParseSexprError::ArgumentCount(position));
+                }
+
+                let mut args = nodes()?;
+                args.sort();
+                args.dedup();
+
+                if head == "min" {
+                    return Ok(ByzNode::Min { args, hash_cache: OnceLock::new() });
+                }
+                return Ok(ByzNode::Max { args, hash_cache: OnceLock::new() });
+            },
+            "log" => {
+                arity(2)?;
+                let mut nodes = nodes()?;
+                let arg = nodes.pop().unwrap();
+                let base = nodes.pop().unwrap();
+                return Ok(ByzNode::Log { base, arg, hash_cache: OnceLock::new() });
+            },
+            "realpow" => {
+                arity(2)?;
+                let mut nodes = nodes()?;
+                let exp = nodes.pop().unwrap();
+                let base = nodes.pop().unwrap();
+                return Ok(ByzNode::RealPow { base, exp, hash_cache: OnceLock::new() });
+            },
+            "root" => {
+                let Some((index, coefficients)) = args.split_first() else {
+                    return Err(ParseSexprError::ArgumentCount(position));
+                };
+
+                let index = index.rational()?;
+                let polynomial = RationalPolynomial::new(coefficients.iter().map(|x| x.rational()).collect::<Result<Vec<Rational>, _>>()?);
+
+                let index = if index.is_int() && !index.is_negative() { usize::try_from(&*index.numer()).ok() } else { None };
+                let Some(index) = index.filter(|index| is_valid_algebraic_root(&polynomial, *index)) else {
+                    return Err(ParseSexprError::InvalidAlgebraicRoot(position));
+                };
+                return Ok(ByzNode::AlgebraicRoot { polynomial, index });
+            },
+            "var" => {
+                arity(1)?;
+                match &args[0] {
+                    Sexpr::Atom(name, _) | Sexpr::Str(name, _) => {
+                        return Ok(ByzNode::Variable { name: name.clone() });
+                    },
+                    Sexpr::List(_, position) => {
+                        return Err(ParseSexprError::UnexpectedChar(*position));
+                    }
+                }
+            },
+            _ => {
+                arity(1)?;
+                let arg = ByzRc::new(args[0].to_byznode()?);
+                return unary_node(head, arg).ok_or(ParseSexprError::UnknownHead(items[0].position()));
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    s: &'a str,
+    position: usize,
+    /// The number of lists the reader is inside of
+    depth: usize
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&mut self) -> Option<char> {
+        while let Some(c) = self.s[self.position..].chars().next() && c.is_whitespace() {
+            self.position += c.len_utf8();
+        }
+
+        return self.s[self.position..].chars().next();
+    }
+
+    fn read(&mut self) -> Result<Sexpr, ParseSexprError> {
+        let next = self.peek();
+        let start = self.position;
+
+        match next {
+            None => {
+                return Err(ParseSexprError::UnexpectedEnd);
+            },
+            Some(')') => {
+                return Err(ParseSexprError::UnexpectedChar(self.position));
+            },
+            Some('(') => {
+                if self.depth == MAX_SEXPR_DEPTH {
+                    return Err(ParseSexprError::TooDeep(start));
+                }
+
+                self.position += 1;
+                self.depth += 1;
+
+                let mut items = Vec::new();
+                while self.peek() != Some(')') {
+                    items.push(self.read()?);
+                }
+                self.position += 1;
+                self.depth -= 1;
+
+                return Ok(Sexpr::List(items, start));
+            },
+            Some('"') => {
+                self.position += 1;
+
+                let mut s = String::new();
+                let mut chars = self.s[self.position..].chars();
+                loop {
+                    let c = chars.next().ok_or(ParseSexprError::UnexpectedEnd)?;
+                    self.position += c.len_utf8();
+
+                    match c {
+                        '"' => {
+                            return Ok(Sexpr::Str(s, start));
+                        },
+                        '\\' => {
+                            let escaped = chars.next().ok_or(ParseSexprError::UnexpectedEnd)?;
+                            self.position += escaped.len_utf8();
+                            s.push(escaped);
+                        },
+                        _ => {
+                            s.push(c);
+                        }
+                    }
+                }
+            },
+            Some(_) => {
+                while let Some(c) = self.s[self.position..].chars().next() && is_atom_char(c) {
+                    self.position += c.len_utf8();
+                }
+
+                return Ok(Sexpr::Atom(self.s[start..self.position].to_string(), start));
+            }
+        }
+    }
+}
+
+impl ByzNode {
+    /// A lossless text form like "(mul 1/2 (pow 2 1/2))", meant for diffs, golden files and other tools to generate
+    /// Add and Mul always write their rational part first, then each item as (weight node), or just node when the weight is 1
+    /// Constants are bare names like pi, variables are (var x) (quoted, like (var "a b"), if the name isn't a plain atom)
+    /// An AlgebraicRoot is (root k c0 c1 ... cn), its index and then its polynomial's coefficients from the constant term up
+    pub fn to_sexpr(&self) -> String {
+        let mut writer = SexprWriter { out: String::new(), open: Vec::new() };
+        self.walk(&mut writer);
+        return writer.out;
+    }
+
+    /// Reads what to_sexpr writes, giving back a tree equal to the one written
+    /// Add and Mul items are inserted like anywhere else, so repeated items are merged rather than rejected
+    /// Lists nested more than MAX_SEXPR_DEPTH deep are refused with ParseSexprError::TooDeep
+    pub fn from_sexpr(s: &str) -> Result<ByzNode, ParseSexprError> {
+        let mut reader = Reader { s, position: 0, depth: 0 };
+        let sexpr = reader.read()?;
+
+        if reader.peek().is_some() {
+            return Err(ParseSexprError::UnexpectedChar(reader.position));
+        }
+
+        return sexpr.to_byznode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nested_sin(depth: usize) -> String {
+        return format!("{}(var x){}", "(sin ".repeat(depth - 1), ")".repeat(depth - 1));
+    }
+
+    #[test]
+    fn from_sexpr_depth_limit() {
+        assert!(ByzNode::from_sexpr(&nested_sin(MAX_SEXPR_DEPTH)).is_ok());
+        assert_eq!(ByzNode::from_sexpr(&nested_sin(MAX_SEXPR_DEPTH + 1)), Err(ParseSexprError::TooDeep(5 * MAX_SEXPR_DEPTH)));
+        assert!(matches!(ByzNode::from_sexpr(&nested_sin(10_000)), Err(ParseSexprError::TooDeep(_))));
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::byzantine::{ByzNode, ByzRc};
+
+/// Hands out one shared Rc for every distinct (structurally equal) subtree it's given, so repeated structure is only stored once
+/// E.g. interning an expanded polynomial in pi and e keeps a single copy of each power of pi, no matter how many terms use it
+/// Neoprene evaluates each shared subtree once per evaluation (see NeopreneContext), so the win is in time as well as memory
+pub struct ExprArena {
+    nodes: HashSet<ByzRc<ByzNode>>
+}
+
+impl ExprArena {
+    pub fn new() -> ExprArena {
+        return ExprArena { nodes: HashSet::new() };
+    }
+
+    /// The shared handle for node, interning all of its subtrees first so they're shared as well
+    pub fn intern(&mut self, node: ByzNode) -> ByzRc<ByzNode> {
+        if let Some(existing) = self.nodes.get(&node) {
+            return ByzRc::clone(existing);
+        }
+
+        let node = self.intern_children(&node).unwrap_or(node);
+        return self.insert(node);
+    }
+
+    /// Same as intern, but node is left alone (and is itself the handle if nothing equal to it was interned before)
+    pub fn intern_rc(&mut self, node: &ByzRc<ByzNode>) -> ByzRc<ByzNode> {
+        if let Some(existing) = self.nodes.get(node.as_ref()) {
+            return ByzRc::clone(existing);
+        }
+
+        match self.intern_children(node) {
+            Some(rebuilt) => {
+                return self.insert(rebuilt);
+            },
+            None => {
+                self.nodes.insert(ByzRc::clone(node));
+                return ByzRc::clone(node);
+            }
+        }
+    }
+
+    /// node rebuilt around interned children, None if all of its children already were the interned handles
+    fn intern_children(&mut self, node: &ByzNode) -> Option<ByzNode> {
+        return node.map_children(|x| {
+            let interned = self.intern_rc(x);
+
+            if ByzRc::ptr_eq(&interned, x) {
+                return None;
+            }
+            return Some(interned);
+        });
+    }
+
+    fn insert(&mut self, node: ByzNode) -> ByzRc<ByzNode> {
+        let node = ByzRc::new(node);
+        self.nodes.insert(ByzRc::clone(&node));
+        return node;
+    }
+
+    pub fn contains(&self, node: &ByzNode) -> bool {
+        return self.nodes.contains(node);
+    }
+
+    /// The number of distinct subtrees interned so far
+    pub fn len(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.nodes.is_empty();
+    }
+
+    /// Forgets every interned node, handles that were given out stay valid but are no longer shared with anything interned later
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+}
+
+impl Default for ExprArena {
+    fn default() -> ExprArena {
+        return ExprArena::new();
+    }
+}
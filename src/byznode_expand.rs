@@ -0,0 +1,127 @@
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::byznode_simplify::{collect_addends, finish_add, finish_mul, push_addend, push_factor};
+use crate::rational::Rational;
+
+/// Sums raised to a larger power than this are left alone, (a + b + c)^n already has (n + 1)(n + 2)/2 terms
+const MAX_EXPANDED_POWER: u32 = 16;
+
+/// The terms of node when it's read as a sum, None standing in for the rational part
+/// Anything that isn't an Add or a Rational is a sum of one term
+fn sum_terms(node: &ByzRc<ByzNode>) -> Vec<(Rational, Option<ByzRc<ByzNode>>)> {
+    match node.as_ref() {
+        ByzNode::Rational { rational } => {
+            return vec![(rational.clone(), None)];
+        },
+        ByzNode::Add { addends } => {
+            let mut terms = vec![(addends.get_rational_part().clone(), None)];
+            terms.extend(addends.get_vec().iter().map(|x| (x.0.clone(), Some(ByzRc::clone(&x.1)))));
+            return terms;
+        },
+        _ => {
+            return vec![(Rational::one(), Some(ByzRc::clone(node)))];
+        }
+    }
+}
+
+/// Multiplies every term of sum by every term of node, where node is already expanded
+fn mul_sum(sum: &ByzNodeCoefficientAddVec, node: &ByzRc<ByzNode>) -> ByzNodeCoefficientAddVec {
+    let mut sum_terms_a = vec![(sum.get_rational_part().clone(), None)];
+    sum_terms_a.extend(sum.get_vec().iter().map(|x| (x.0.clone(), Some(ByzRc::clone(&x.1)))));
+
+    let sum_terms_b = sum_terms(node);
+
+    let mut product = ByzNodeCoefficientAddVec::new();
+    for (a_coefficient, a) in &sum_terms_a {
+        for (b_coefficient, b) in &sum_terms_b {
+            let coefficient = a_coefficient * b_coefficient;
+            if coefficient.is_zero() {
+                continue;
+            }
+
+            match (a, b) {
+                (None, None) => {
+                    product.insert_rational(coefficient);
+                },
+                (Some(x), None) | (None, Some(x)) => {
+                    push_addend(&mut product, &coefficient, ByzRc::clone(x));
+                },
+                (Some(x), Some(y)) => {
+                    let mut term = ByzNodePowerMulVec::new();
+                    push_factor(&mut term, &Rational::one(), ByzRc::clone(x));
+                    push_factor(&mut term, &Rational::one(), ByzRc::clone(y));
+                    push_addend(&mut product, &coefficient, ByzRc::new(finish_mul(term)));
+                }
+            }
+        }
+    }
+
+    return product;
+}
+
+/// The power a factor is multiplied out to, if it's a sum raised to a small positive integer
+fn expanded_power(node: &ByzNode, power: &Rational) -> Option<u32> {
+    if !matches!(node, ByzNode::Add { .. }) || !power.is_int() || !power.is_positive() {
+        return None;
+    }
+
+    return u32::try_from(power.floor()).ok().filter(|&n| n <= MAX_EXPANDED_POWER);
+}
+
+/// Multiplies out products, whose children are already expanded
+fn expand_products(products: &ByzNodePowerMulVec) -> ByzNode {
+    let mut sum = ByzNodeCoefficientAddVec::new();
+    sum.insert_rational(products.get_rational_part().clone());
+
+    // Everything that isn't multiplied out is gathered up and multiplied in as a single term at the end
+    let mut rest = ByzNodePowerMulVec::new();
+
+    for (power, node) in products.get_vec() {
+        match expanded_power(node, power) {
+            Some(n) => {
+                for _ in 0..n {
+                    sum = mul_sum(&sum, node);
+                }
+            },
+            None => {
+                push_factor(&mut rest, power, ByzRc::clone(node));
+            }
+        }
+    }
+
+    sum = mul_sum(&sum, &ByzRc::new(finish_mul(rest)));
+
+    return finish_add(sum);
+}
+
+impl ByzNode {
+    /// Distributes products over sums and multiplies out sums raised to small positive integer powers
+    /// (Each power is multiplied out term by term, which gives the same terms as the multinomial theorem but merges like ones as it goes)
+    /// Like terms are merged, so parts that cancel disappear before anything is evaluated
+    /// Sums to negative, non-integer or large powers are left as factors, and arguments of functions are expanded but never pulled out
+    /// Works bottom up with fold, so it doesn't recurse and works on trees of any depth
+    pub fn expand(&self) -> ByzNode {
+        return self.fold(&mut expand_node);
+    }
+}
+
+/// One step of expand, node's children are already expanded
+fn expand_node(node: ByzNode) -> ByzNode {
+    match &node {
+        ByzNode::Add { addends } => {
+            return collect_addends(addends);
+        },
+        ByzNode::Mul { products } => {
+            return expand_products(products);
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            let mut new_products = ByzNodePowerMulVec::new();
+            new_products.insert_rc((exp.clone(), ByzRc::clone(base)));
+
+            return expand_products(&new_products);
+        },
+        _ => {
+            return node;
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::byznode_simplify::{finish_add, finish_mul, push_addend, push_factor};
+use crate::rational::Rational;
+
+/// k * (sum / k), where k is the content of every coefficient and the rational part of the sum
+/// None when there's nothing to pull out (k is 0 or 1)
+fn factor_rational_add(addends: &ByzNodeCoefficientAddVec) -> Option<ByzNode> {
+    let mut values: Vec<Rational> = addends.get_vec().iter().map(|x| x.0.clone()).collect();
+    values.push(addends.get_rational_part().clone());
+
+    let content = Rational::content(&values);
+    if content.is_zero() || content.is_one() {
+        return None;
+    }
+
+    let mut reduced = ByzNodeCoefficientAddVec::new();
+    reduced.insert_rational(addends.get_rational_part() / &content);
+    for x in addends.get_vec() {
+        reduced.insert_rc((&x.0 / &content, ByzRc::clone(&x.1)));
+    }
+
+    let mut products = ByzNodePowerMulVec::new();
+    products.insert_rational(content);
+    products.insert((Rational::one(), ByzNode::Add { addends: reduced }));
+
+    return Some(ByzNode::Mul { products });
+}
+
+/// The rational factor of a term and the factors it multiplies, x^p on its own is 1 * x^p
+fn term_factors(node: &ByzRc<ByzNode>) -> (Rational, Vec<(Rational, ByzRc<ByzNode>)>) {
+    match node.as_ref() {
+        ByzNode::Mul { products } => {
+            return (products.get_rational_part().clone(), products.get_vec().clone());
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            return (Rational::one(), vec![(exp.clone(), ByzRc::clone(base))]);
+        },
+        _ => {
+            return (Rational::one(), vec![(Rational::one(), ByzRc::clone(node))]);
+        }
+    }
+}
+
+/// x^p * (sum / x^p) for every x that's a factor of every term of the sum, with p the power closest to zero it appears with
+/// None when the sum has a rational part (which has no factors), or no factor is shared by all the terms with powers of the same sign
+fn factor_common_add(addends: &ByzNodeCoefficientAddVec) -> Option<ByzNode> {
+    let vec = addends.get_vec();
+    if !addends.get_rational_part().is_zero() || vec.len() < 2 {
+        return None;
+    }
+
+    // (coefficient, rational factor, other factors) for each term
+    let terms: Vec<_> = vec.iter()
+        .map(|x| {
+            let (k, factors) = term_factors(&x.1);
+            return (x.0.clone(), k, factors);
+        })
+        .collect();
+
+    // Candidates come from the first term and are whittled down by every other one
+    let mut common: Vec<(Rational, ByzRc<ByzNode>)> = terms[0].2.clone();
+    for (_, _, factors) in &terms[1..] {
+        common.retain_mut(|(power, node)| {
+            let Some((other_power, _)) = factors.iter().find(|x| x.1 == *node) else {
+                return false;
+            };
+
+            if power.is_positive() != other_power.is_positive() {
+                return false;
+            }
+            if other_power.abs() < power.abs() {
+                *power = other_power.clone();
+            }
+
+            return true;
+        });
+    }
+
+    if common.is_empty() {
+        return None;
+    }
+
+    let mut reduced = ByzNodeCoefficientAddVec::new();
+    for (coefficient, k, factors) in terms {
+        let mut quotient = ByzNodePowerMulVec::new();
+        quotient.insert_rational(k);
+
+        for (power, node) in factors {
+            let power = match common.iter().find(|x| x.1 == node) {
+                Some((common_power, _)) => &power - common_power,
+                None => power
+            };
+
+            if !power.is_zero() {
+                quotient.insert_rc((power, node));
+            }
+        }
+
+        push_addend(&mut reduced, &coefficient, ByzRc::new(finish_mul(quotient)));
+    }
+
+    let mut products = ByzNodePowerMulVec::new();
+    for x in common {
+        products.insert_rc(x);
+    }
+    push_factor(&mut products, &Rational::one(), ByzRc::new(finish_add(reduced)));
+
+    return Some(finish_mul(products));
+}
+
+impl ByzNode {
+    /// Pulls the gcd of the coefficients (and rational part) out of every sum in the tree, so 6*x + 4*y + 2 becomes 2*(3*x + 2*y + 1)
+    /// The gcd is always positive, and sums whose gcd is 1 are left alone
+    pub fn factor_rational(&self) -> ByzNode {
+        return self.fold(&mut |node: ByzNode| {
+            if let ByzNode::Add { addends } = &node && let Some(factored) = factor_rational_add(addends) {
+                return factored;
+            }
+
+            return node;
+        });
+    }
+
+    /// Pulls factors shared by every term out of every sum in the tree, so x^2 * y + 3*x * sin(y) becomes x * (x * y + 3*sin(y))
+    /// Only structurally equal factors are found, and a sum with a rational part is left alone since that part has no factors to share
+    /// A factor is taken out at the power closest to zero that it appears with, and only if it has powers of the same sign in every term
+    pub fn factor_common(&self) -> ByzNode {
+        return self.fold(&mut |node: ByzNode| {
+            if let ByzNode::Add { addends } = &node && let Some(factored) = factor_common_add(addends) {
+                return factored;
+            }
+
+            return node;
+        });
+    }
+}
@@ -0,0 +1,81 @@
+/// Builds a ByzNode from an expression written inline, e.g. byz!(pi/4 + 2^(1/2) * 3), with the same syntax as FromStr
+/// The expression is turned into GBU calls and ByzNode arithmetic at compile time, so nothing is parsed when it runs
+/// ^ binds tighter than a leading minus and is right associative, the names pi, e, ln2, phi, gamma, catalan and apery are constants,
+/// other bare names are Variables, and the functions are the ones FromStr knows (sqrt, log(base, x), min and max included)
+/// Literals have to be integers (write 1.5 as 3/2), and {expr} splices in a clone of any ByzNode (or ByzRc of one) without moving it
+/// Every token is a level of macro recursion, so very long expressions may need a higher #![recursion_limit]
+#[macro_export]
+macro_rules! byz {
+    // The operators Rust already gives the right precedence are passed straight through to ByzNode's own
+    (@expr [$($out:tt)*] + $($rest:tt)*) => { $crate::byz!(@expr [$($out)* +] $($rest)*) };
+    (@expr [$($out:tt)*] - $($rest:tt)*) => { $crate::byz!(@expr [$($out)* -] $($rest)*) };
+    (@expr [$($out:tt)*] * $($rest:tt)*) => { $crate::byz!(@expr [$($out)* *] $($rest)*) };
+    (@expr [$($out:tt)*] / $($rest:tt)*) => { $crate::byz!(@expr [$($out)* /] $($rest)*) };
+    (@expr [$($out:tt)*]) => { $($out)* };
+    (@expr [$($out:tt)*] $($rest:tt)+) => { $crate::byz!(@atom [$($out)*] [] $($rest)+) };
+
+    // a ^ b ^ -c ^ d is gathered up as the chain (a) (b) - (c) (d) and folded from the right once it ends, so the minus covers c ^ d
+    (@chain [$($out:tt)*] [$($chain:tt)*] ^ - $($rest:tt)+) => { $crate::byz!(@atom [$($out)*] [$($chain)* -] $($rest)+) };
+    (@chain [$($out:tt)*] [$($chain:tt)*] ^ $($rest:tt)+) => { $crate::byz!(@atom [$($out)*] [$($chain)*] $($rest)+) };
+    (@chain [$($out:tt)*] [$($chain:tt)*] $($rest:tt)*) => { $crate::byz!(@expr [$($out)* ($crate::byz!(@fold $($chain)*))] $($rest)*) };
+
+    (@fold $a:tt) => { $a };
+    (@fold $a:tt - $($rest:tt)+) => { $crate::generate_byznode_utils::real_pow($a, -$crate::byz!(@fold $($rest)+)) };
+    (@fold $a:tt $($rest:tt)+) => { $crate::generate_byznode_utils::real_pow($a, $crate::byz!(@fold $($rest)+)) };
+
+    (@atom [$($out:tt)*] [$($chain:tt)*] ($($inner:tt)+) $($rest:tt)*) => {
+        $crate::byz!(@chain [$($out)*] [$($chain)* ($crate::byz!($($inner)+))] $($rest)*)
+    };
+    (@atom [$($out:tt)*] [$($chain:tt)*] {$($splice:tt)+} $($rest:tt)*) => {
+        $crate::byz!(@chain [$($out)*] [$($chain)* ({ let spliced = &($($splice)+); $crate::byzantine::ByzNode::clone(spliced) })] $($rest)*)
+    };
+    (@atom [$($out:tt)*] [$($chain:tt)*] $f:ident ($($args:tt)+) $($rest:tt)*) => {
+        $crate::byz!(@chain [$($out)*] [$($chain)* ($crate::byz!(@args $f [] [] $($args)+))] $($rest)*)
+    };
+    (@atom [$($out:tt)*] [$($chain:tt)*] $name:ident $($rest:tt)*) => {
+        $crate::byz!(@chain [$($out)*] [$($chain)* ($crate::byz!(@name $name))] $($rest)*)
+    };
+    (@atom [$($out:tt)*] [$($chain:tt)*] $literal:literal $($rest:tt)*) => {
+        $crate::byz!(@chain [$($out)*] [$($chain)* ($crate::generate_byznode_utils::rational($crate::rational::Rational::from({ let literal: i128 = $literal; literal })))] $($rest)*)
+    };
+
+    (@name pi) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::Pi) };
+    (@name e) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::Euler) };
+    (@name ln2) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::Ln2) };
+    (@name phi) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::GoldenRatio) };
+    (@name gamma) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::EulerMascheroni) };
+    (@name catalan) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::Catalan) };
+    (@name apery) => { $crate::generate_byznode_utils::transitive($crate::byzantine::TransitiveConsts::Apery) };
+    (@name $name:ident) => { $crate::generate_byznode_utils::variable(stringify!($name)) };
+
+    // Splits the arguments of a call on its top level commas, each one is [tokens]
+    (@args $f:ident [$($done:tt)*] [$($arg:tt)+] , $($rest:tt)+) => { $crate::byz!(@args $f [$($done)* [$($arg)+]] [] $($rest)+) };
+    (@args $f:ident [$($done:tt)*] [$($arg:tt)*] $t:tt $($rest:tt)*) => { $crate::byz!(@args $f [$($done)*] [$($arg)* $t] $($rest)*) };
+    (@args $f:ident [$($done:tt)*] [$($arg:tt)+]) => { $crate::byz!(@call $f $($done)* [$($arg)+]) };
+
+    (@call exp [$($a:tt)+]) => { $crate::generate_byznode_utils::exp($crate::byz!($($a)+)) };
+    (@call ln [$($a:tt)+]) => { $crate::generate_byznode_utils::ln($crate::byz!($($a)+)) };
+    (@call sin [$($a:tt)+]) => { $crate::generate_byznode_utils::sin($crate::byz!($($a)+)) };
+    (@call cos [$($a:tt)+]) => { $crate::generate_byznode_utils::cos($crate::byz!($($a)+)) };
+    (@call tan [$($a:tt)+]) => { $crate::generate_byznode_utils::tan($crate::byz!($($a)+)) };
+    (@call arctan [$($a:tt)+]) => { $crate::generate_byznode_utils::arctan($crate::byz!($($a)+)) };
+    (@call atan [$($a:tt)+]) => { $crate::generate_byznode_utils::arctan($crate::byz!($($a)+)) };
+    (@call arcsin [$($a:tt)+]) => { $crate::generate_byznode_utils::arcsin($crate::byz!($($a)+)) };
+    (@call asin [$($a:tt)+]) => { $crate::generate_byznode_utils::arcsin($crate::byz!($($a)+)) };
+    (@call arccos [$($a:tt)+]) => { $crate::generate_byznode_utils::arccos($crate::byz!($($a)+)) };
+    (@call acos [$($a:tt)+]) => { $crate::generate_byznode_utils::arccos($crate::byz!($($a)+)) };
+    (@call sinh [$($a:tt)+]) => { $crate::generate_byznode_utils::sinh($crate::byz!($($a)+)) };
+    (@call cosh [$($a:tt)+]) => { $crate::generate_byznode_utils::cosh($crate::byz!($($a)+)) };
+    (@call tanh [$($a:tt)+]) => { $crate::generate_byznode_utils::tanh($crate::byz!($($a)+)) };
+    (@call abs [$($a:tt)+]) => { $crate::generate_byznode_utils::abs($crate::byz!($($a)+)) };
+    (@call floor [$($a:tt)+]) => { $crate::generate_byznode_utils::floor($crate::byz!($($a)+)) };
+    (@call ceil [$($a:tt)+]) => { $crate::generate_byznode_utils::ceil($crate::byz!($($a)+)) };
+    (@call frac [$($a:tt)+]) => { $crate::generate_byznode_utils::frac($crate::byz!($($a)+)) };
+    (@call sqrt [$($a:tt)+]) => { $crate::generate_byznode_utils::pow($crate::byz!($($a)+), $crate::rational::Rational::from((1, 2))) };
+    (@call log [$($base:tt)+] [$($a:tt)+]) => { $crate::generate_byznode_utils::log($crate::byz!($($base)+), $crate::byz!($($a)+)) };
+    (@call min $([$($a:tt)+])+) => { $crate::generate_byznode_utils::min(vec![$($crate::byz!($($a)+)),+]) };
+    (@call max $([$($a:tt)+])+) => { $crate::generate_byznode_utils::max(vec![$($crate::byz!($($a)+)),+]) };
+    (@call $f:ident $($args:tt)*) => { compile_error!(concat!("unknown function or wrong number of arguments: ", stringify!($f))) };
+
+    ($($t:tt)+) => { $crate::byz!(@expr [] $($t)+) };
+}
@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byznode_sorted_vec::ByzNodeVec;
+use crate::byznode_visit::ByzVisitor;
+
+/// How a subtree that several parents point to (the same Rc, e.g. from an ExprArena) is counted
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SharedSubtrees {
+    /// Once for every parent pointing at it, the size of the tree as written out
+    PerReference,
+    /// Only the first time it's reached, the number of distinct nodes actually in memory (and roughly what evaluation costs, see NeopreneContext)
+    Once
+}
+
+/// Calls f on every node it enters, skipping subtrees already seen when shared is Once
+struct Counter<'a, F> where F: FnMut(&ByzNode) {
+    shared: SharedSubtrees,
+    seen: HashSet<*const ByzNode>,
+    f: &'a mut F
+}
+
+impl<F> ByzVisitor for Counter<'_, F> where F: FnMut(&ByzNode) {
+    fn enter(&mut self, node: &ByzNode) -> bool {
+        if self.shared == SharedSubtrees::Once && !self.seen.insert(node) {
+            return false;
+        }
+
+        (self.f)(node);
+        return true;
+    }
+}
+
+/// Calls f on node and every node below it, without recursing
+fn visit<F>(node: &ByzNode, shared: SharedSubtrees, f: &mut F) where F: FnMut(&ByzNode) {
+    node.walk(&mut Counter { shared, seen: HashSet::new(), f });
+}
+
+/// The depth of every shared subtree is only worked out once, which makes this linear even when the tree written out would be exponential
+/// Each node's depth is worked out when it's left, from its children's which are all known by then
+struct DepthMemo {
+    memo: HashMap<*const ByzNode, usize>
+}
+
+impl ByzVisitor for DepthMemo {
+    fn enter(&mut self, node: &ByzNode) -> bool {
+        return !self.memo.contains_key(&(node as *const ByzNode));
+    }
+
+    fn leave(&mut self, node: &ByzNode) {
+        if self.memo.contains_key(&(node as *const ByzNode)) {
+            return;
+        }
+
+        let deepest = node.children().into_iter().map(|x| self.memo[&ByzRc::as_ptr(x)]).max().unwrap_or(0);
+        self.memo.insert(node, deepest + 1);
+    }
+}
+
+impl ByzNode {
+    /// The number of nodes in the tree, self included
+    pub fn node_count(&self, shared: SharedSubtrees) -> usize {
+        let mut count = 0;
+        visit(self, shared, &mut |_| count += 1);
+        return count;
+    }
+
+    /// The number of nodes on the longest path down from self, so a leaf has depth 1 and x + 1 has depth 2
+    /// Sharing doesn't change the depth, but shared subtrees are only walked once anyway
+    pub fn depth(&self) -> usize {
+        let mut depths = DepthMemo { memo: HashMap::new() };
+        self.walk(&mut depths);
+        return depths.memo[&(self as *const ByzNode)];
+    }
+
+    /// The number of terms of all the sums in the tree, a non-zero rational part counting as a term of its own
+    /// So x^2 + 2*x + 1 has 3 terms, and sin(x + 1) * (y + z) has 4
+    pub fn term_count(&self, shared: SharedSubtrees) -> usize {
+        let mut count = 0;
+        visit(self, shared, &mut |x| {
+            if let ByzNode::Add { addends } = x {
+                count += addends.get_vec().len();
+                if !addends.get_rational_part().is_zero() {
+                    count += 1;
+                }
+            }
+        });
+        return count;
+    }
+}
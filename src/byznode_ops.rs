@@ -0,0 +1,163 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::sync::OnceLock;
+
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byznode_simplify::{finish_add, finish_mul, push_addend, push_factor};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::rational::Rational;
+
+/// Anything that can stand on either side of an operator, turned into the shared handle the node is built from
+trait ByzOperand {
+    fn into_operand(self) -> ByzRc<ByzNode>;
+}
+
+impl ByzOperand for ByzNode {
+    fn into_operand(self) -> ByzRc<ByzNode> {
+        return ByzRc::new(self);
+    }
+}
+
+impl ByzOperand for &ByzNode {
+    fn into_operand(self) -> ByzRc<ByzNode> {
+        return ByzRc::new(self.clone());
+    }
+}
+
+impl ByzOperand for ByzRc<ByzNode> {
+    fn into_operand(self) -> ByzRc<ByzNode> {
+        return self;
+    }
+}
+
+impl ByzOperand for &ByzRc<ByzNode> {
+    fn into_operand(self) -> ByzRc<ByzNode> {
+        return ByzRc::clone(self);
+    }
+}
+
+impl ByzOperand for Rational {
+    fn into_operand(self) -> ByzRc<ByzNode> {
+        return ByzRc::new(ByzNode::Rational { rational: self });
+    }
+}
+
+impl ByzOperand for &Rational {
+    fn into_operand(self) -> ByzRc<ByzNode> {
+        return ByzRc::new(ByzNode::Rational { rational: self.clone() });
+    }
+}
+
+/// a + coefficient * b
+fn add_operands(a: ByzRc<ByzNode>, coefficient: &Rational, b: ByzRc<ByzNode>) -> ByzNode {
+    let mut addends = ByzNodeCoefficientAddVec::new();
+    push_addend(&mut addends, &Rational::one(), a);
+    push_addend(&mut addends, coefficient, b);
+
+    return finish_add(addends);
+}
+
+/// a * b^power
+fn mul_operands(a: ByzRc<ByzNode>, power: &Rational, b: ByzRc<ByzNode>) -> ByzNode {
+    let mut products = ByzNodePowerMulVec::new();
+    push_factor(&mut products, &Rational::one(), a);
+    push_factor(&mut products, power, b);
+
+    return finish_mul(products);
+}
+
+/// All four operators between lhs and rhs, every combination of the operand types is listed below
+/// Two Rc<ByzNode> can't be combined (neither the operator nor Rc are this crate's), &*a + &*b does the same thing just as cheaply
+macro_rules! byznode_ops {
+    ($($lhs:ty, $rhs:ty);* $(;)?) => {
+        $(
+            impl Add<$rhs> for $lhs {
+                type Output = ByzNode;
+
+                fn add(self, rhs: $rhs) -> ByzNode {
+                    return add_operands(self.into_operand(), &Rational::one(), rhs.into_operand());
+                }
+            }
+
+            impl Sub<$rhs> for $lhs {
+                type Output = ByzNode;
+
+                fn sub(self, rhs: $rhs) -> ByzNode {
+                    return add_operands(self.into_operand(), &Rational::from(-1), rhs.into_operand());
+                }
+            }
+
+            impl Mul<$rhs> for $lhs {
+                type Output = ByzNode;
+
+                fn mul(self, rhs: $rhs) -> ByzNode {
+                    return mul_operands(self.into_operand(), &Rational::one(), rhs.into_operand());
+                }
+            }
+
+            impl Div<$rhs> for $lhs {
+                type Output = ByzNode;
+
+                fn div(self, rhs: $rhs) -> ByzNode {
+                    return mul_operands(self.into_operand(), &Rational::from(-1), rhs.into_operand());
+                }
+            }
+        )*
+    };
+}
+
+byznode_ops!(
+    ByzNode, ByzNode; ByzNode, &ByzNode; &ByzNode, ByzNode; &ByzNode, &ByzNode;
+    ByzNode, ByzRc<ByzNode>; ByzNode, &ByzRc<ByzNode>; &ByzNode, ByzRc<ByzNode>; &ByzNode, &ByzRc<ByzNode>;
+    ByzRc<ByzNode>, ByzNode; &ByzRc<ByzNode>, ByzNode; ByzRc<ByzNode>, &ByzNode; &ByzRc<ByzNode>, &ByzNode;
+    ByzNode, Rational; ByzNode, &Rational; &ByzNode, Rational; &ByzNode, &Rational;
+    Rational, ByzNode; &Rational, ByzNode; Rational, &ByzNode; &Rational, &ByzNode;
+    ByzRc<ByzNode>, Rational; ByzRc<ByzNode>, &Rational; &ByzRc<ByzNode>, Rational; &ByzRc<ByzNode>, &Rational;
+    Rational, ByzRc<ByzNode>; &Rational, ByzRc<ByzNode>; Rational, &ByzRc<ByzNode>; &Rational, &ByzRc<ByzNode>;
+);
+
+impl Neg for ByzNode {
+    type Output = ByzNode;
+
+    fn neg(self) -> ByzNode {
+        return self.negate();
+    }
+}
+
+impl Neg for &ByzNode {
+    type Output = ByzNode;
+
+    fn neg(self) -> ByzNode {
+        return self.negate();
+    }
+}
+
+impl ByzNode {
+    /// -self, with the -1 folded into the node where there's somewhere to put it rather than wrapped around it
+    /// A rational is negated, every coefficient (and the rational part) of an Add is, and so is the rational factor of a Mul, anything else becomes -1*self
+    pub fn negate(&self) -> ByzNode {
+        if let ByzNode::Mul { products } = self {
+            let mut products = products.clone();
+            products.get_rational_part_mut().negate();
+            return finish_mul(products);
+        }
+
+        let mut addends = ByzNodeCoefficientAddVec::new();
+        push_addend(&mut addends, &Rational::from(-1), ByzRc::new(self.clone()));
+
+        return finish_add(addends);
+    }
+
+    /// 1/self, with the -1 folded into the exponents where there's somewhere to put it rather than wrapped around it
+    /// A rational is inverted, a Pow or RealPow gets its exponent negated and a Mul every power and its rational factor inverted, anything else becomes self^-1
+    /// The reciprocal of 0 is 0^-1, which fails to evaluate like any other division by zero
+    pub fn reciprocal(&self) -> ByzNode {
+        if let ByzNode::RealPow { base, exp, .. } = self {
+            return ByzNode::RealPow { base: ByzRc::clone(base), exp: ByzRc::new(exp.negate()), hash_cache: OnceLock::new() };
+        }
+
+        let mut products = ByzNodePowerMulVec::new();
+        push_factor(&mut products, &Rational::from(-1), ByzRc::new(self.clone()));
+
+        return finish_mul(products);
+    }
+}
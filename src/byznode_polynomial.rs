@@ -0,0 +1,108 @@
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byznode_simplify::{finish_add, finish_mul, push_addend, push_factor};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::rational::Rational;
+use crate::rational_polynomial::RationalPolynomial;
+
+/// The indeterminate as var_base^var_exp, so that powers of var_base can be read as powers of it
+struct Indeterminate<'a> {
+    var: &'a ByzNode,
+    var_base: &'a ByzNode,
+    var_exp: Rational
+}
+
+impl Indeterminate<'_> {
+    fn polynomial(&self, node: &ByzNode) -> Option<RationalPolynomial> {
+        if node == self.var {
+            return Some(RationalPolynomial::monomial(Rational::one(), 1));
+        }
+
+        match node {
+            ByzNode::Rational { rational } => {
+                return Some(RationalPolynomial::constant(rational.clone()));
+            },
+            ByzNode::Add { addends } => {
+                let mut sum = RationalPolynomial::constant(addends.get_rational_part().clone());
+                for x in addends.get_vec() {
+                    let mut term = self.polynomial(&x.1)?;
+                    term.scale(&x.0);
+                    sum += &term;
+                }
+
+                return Some(sum);
+            },
+            ByzNode::Mul { products } => {
+                let mut product = RationalPolynomial::constant(products.get_rational_part().clone());
+                for x in products.get_vec() {
+                    product *= &self.power(&x.1, &x.0)?;
+                }
+
+                return Some(product);
+            },
+            ByzNode::Pow { base, exp, .. } => {
+                return self.power(base, exp);
+            },
+            _ => {
+                return None;
+            }
+        }
+    }
+
+    /// node^power, which is a polynomial if node is a power of the indeterminate's base that's a whole power of the indeterminate,
+    /// or if node is a polynomial and power is a non-negative integer
+    fn power(&self, node: &ByzNode, power: &Rational) -> Option<RationalPolynomial> {
+        if node == self.var_base {
+            let degree = power / &self.var_exp;
+            if degree.is_int() && !degree.is_negative() {
+                return Some(RationalPolynomial::monomial(Rational::one(), usize::try_from(&*degree.numer()).ok()?));
+            }
+        }
+
+        if !power.is_int() || power.is_negative() {
+            return None;
+        }
+
+        return Some(self.polynomial(node)?.pow(u32::try_from(&*power.numer()).ok()?));
+    }
+}
+
+impl ByzNode {
+    /// self as a polynomial in var with rational coefficients, or None if it isn't one as written
+    /// Sums, products and non-negative integer powers of polynomials are polynomials, var itself is x and rationals are constants,
+    /// anything else (even sin(1), which doesn't involve var) isn't, and nothing is expanded or evaluated beforehand
+    /// var is usually a Variable, but can be anything, like 2^(1/2) to read 3 + 2^(1/2) + 2^(3/2) as x^3 + x + 3
+    /// A power of var's base that's a whole power of var counts, but a rational that happens to be one doesn't (the 2 in 2 + 2^(1/2) stays a constant),
+    /// so for an irrational var the polynomial is only one of the ways to write self, and the coefficients aren't unique
+    pub fn as_polynomial(&self, var: &ByzNode) -> Option<RationalPolynomial> {
+        let indeterminate = match var {
+            ByzNode::Pow { base, exp, .. } => Indeterminate { var, var_base: base, var_exp: exp.clone() },
+            _ => Indeterminate { var, var_base: var, var_exp: Rational::one() }
+        };
+
+        return indeterminate.polynomial(self);
+    }
+}
+
+impl RationalPolynomial {
+    /// The polynomial written out as a sum of rational multiples of powers of var, the other way from as_polynomial
+    pub fn to_byznode(&self, var: &ByzNode) -> ByzNode {
+        let var = ByzRc::new(var.clone());
+        let mut addends = ByzNodeCoefficientAddVec::new();
+
+        for (i, c) in self.coefficients().iter().enumerate() {
+            if i == 0 {
+                addends.insert_rational(c.clone());
+                continue;
+            }
+            if c.is_zero() {
+                continue;
+            }
+
+            let mut products = ByzNodePowerMulVec::new();
+            push_factor(&mut products, &Rational::from(i as i128), ByzRc::clone(&var));
+            push_addend(&mut addends, c, ByzRc::new(finish_mul(products)));
+        }
+
+        return finish_add(addends);
+    }
+}
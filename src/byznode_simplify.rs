@@ -0,0 +1,298 @@
+use std::sync::OnceLock;
+
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
+use crate::rational::Rational;
+
+/// Whether (x^a)^b can be written as x^(ab) without changing the value anywhere the left side is defined
+/// True when b is an integer, or when a has an odd numerator (then x^a has the sign of x and |x^a| = |x|^a)
+/// (x^2)^(1/2) = |x| is the kind of thing this rules out
+fn can_fold_powers(a: &Rational, b: &Rational) -> bool {
+    return b.is_int() || a.numer().bit(0);
+}
+
+/// Collapses an Add that has nothing left in it to its rational part, and one that is just 1*x to x
+pub(crate) fn finish_add(addends: ByzNodeCoefficientAddVec) -> ByzNode {
+    let vec = addends.get_vec();
+
+    if vec.is_empty() {
+        return ByzNode::Rational { rational: addends.get_rational_part().clone() };
+    }
+
+    if vec.len() == 1 && addends.get_rational_part().is_zero() && vec[0].0.is_one() {
+        return vec[0].1.as_ref().clone();
+    }
+
+    return ByzNode::Add { addends };
+}
+
+/// Collapses a Mul that has nothing left in it to its rational part, 1*x^1 to x and 1*x^p to a Pow
+pub(crate) fn finish_mul(products: ByzNodePowerMulVec) -> ByzNode {
+    let vec = products.get_vec();
+
+    if vec.is_empty() {
+        return ByzNode::Rational { rational: products.get_rational_part().clone() };
+    }
+
+    if vec.len() == 1 && products.get_rational_part().is_one() {
+        if vec[0].0.is_one() {
+            return vec[0].1.as_ref().clone();
+        }
+
+        return ByzNode::Pow { base: ByzRc::clone(&vec[0].1), exp: vec[0].0.clone(), hash_cache: OnceLock::new() };
+    }
+
+    return ByzNode::Mul { products };
+}
+
+/// Adds coefficient*node to addends, where node is already simplified
+pub(crate) fn push_addend(addends: &mut ByzNodeCoefficientAddVec, coefficient: &Rational, node: ByzRc<ByzNode>) {
+    match node.as_ref() {
+        ByzNode::Rational { rational } => {
+            addends.insert_rational(coefficient * rational);
+        },
+        ByzNode::Add { addends: inner } => {
+            addends.insert_rational(coefficient * inner.get_rational_part());
+
+            for x in inner.get_vec() {
+                addends.insert_rc((coefficient * &x.0, ByzRc::clone(&x.1)));
+            }
+        },
+        ByzNode::Mul { products } if !products.get_rational_part().is_one() && !products.get_rational_part().is_zero() => {
+            // k*f(x) as an addend is the same as f(x) with its coefficient multiplied by k
+            let mut stripped = ByzNodePowerMulVec::new();
+            for x in products.get_vec() {
+                stripped.insert_rc(x.clone());
+            }
+
+            let coefficient = coefficient * products.get_rational_part();
+            push_addend(addends, &coefficient, ByzRc::new(finish_mul(stripped)));
+        },
+        _ => {
+            addends.insert_rc((coefficient.clone(), node));
+        }
+    }
+}
+
+/// Multiplies products by node^power, where node is already simplified
+pub(crate) fn push_factor(products: &mut ByzNodePowerMulVec, power: &Rational, node: ByzRc<ByzNode>) {
+    match node.as_ref() {
+        ByzNode::Rational { rational } if let Some(value) = exact_pow(rational, power) => {
+            products.insert_rational(value);
+        },
+        ByzNode::Mul { products: inner } if power.is_int() => {
+            // (k * x^a * y^b)^n = k^n * x^(an) * y^(bn), which is only safe for integer n
+            push_factor(products, power, ByzRc::new(ByzNode::Rational { rational: inner.get_rational_part().clone() }));
+
+            for x in inner.get_vec() {
+                products.insert_rc((&x.0 * power, ByzRc::clone(&x.1)));
+            }
+        },
+        ByzNode::Pow { base, exp, .. } if can_fold_powers(exp, power) => {
+            push_factor(products, &(exp * power), ByzRc::clone(base));
+        },
+        _ => {
+            products.insert_rc((power.clone(), node));
+        }
+    }
+}
+
+impl ByzNode {
+    /// Cleans up redundant structure without evaluating anything (beyond rational arithmetic)
+    /// Flattens Add inside Add and Mul inside Mul, folds (x^a)^b into x^(ab) where that's safe, merges rational parts into coefficients
+    /// And drops identity elements, so x + 0, 1*x and x^1 all come out as x
+    /// The result is equal to self wherever self can be evaluated, though it may evaluate in places self can't (e.g. (x^(1/2))^2 becomes x)
+    /// Works bottom up with fold, so it doesn't recurse and works on trees of any depth
+    pub fn simplify(&self) -> ByzNode {
+        return self.fold(&mut simplify_node);
+    }
+}
+
+/// One step of simplify, node's children are already simplified
+fn simplify_node(node: ByzNode) -> ByzNode {
+    match &node {
+        ByzNode::Add { addends } => {
+            return collect_addends(addends);
+        },
+        ByzNode::Mul { products } => {
+            let mut new_products = ByzNodePowerMulVec::new();
+            new_products.insert_rational(products.get_rational_part().clone());
+
+            for x in products.get_vec() {
+                push_factor(&mut new_products, &x.0, ByzRc::clone(&x.1));
+            }
+
+            return finish_mul(new_products);
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            let mut new_products = ByzNodePowerMulVec::new();
+            push_factor(&mut new_products, exp, ByzRc::clone(base));
+
+            return finish_mul(new_products);
+        },
+        ByzNode::RealPow { base, exp: real_exp, .. } if let ByzNode::Rational { rational } = real_exp.as_ref() => {
+            let mut new_products = ByzNodePowerMulVec::new();
+            push_factor(&mut new_products, rational, ByzRc::clone(base));
+
+            return finish_mul(new_products);
+        },
+        _ => {
+            return node;
+        }
+    }
+}
+
+/// addends with every term pushed again, which flattens the Adds among them and merges like terms
+pub(crate) fn collect_addends(addends: &ByzNodeCoefficientAddVec) -> ByzNode {
+    let mut new_addends = ByzNodeCoefficientAddVec::new();
+    new_addends.insert_rational(addends.get_rational_part().clone());
+
+    for x in addends.get_vec() {
+        push_addend(&mut new_addends, &x.0, ByzRc::clone(&x.1));
+    }
+
+    return finish_add(new_addends);
+}
+
+/// node with its Rational children merged into its rational part, or just a Rational if nothing else is left
+/// None if there was nothing to fold, children that would fail to evaluate (like 0^-1) are never folded
+fn fold_rational_children(node: &ByzNode) -> Option<ByzNode> {
+    match node {
+        ByzNode::Add { addends } => {
+            let mut new_addends = ByzNodeCoefficientAddVec::new();
+            new_addends.insert_rational(addends.get_rational_part().clone());
+
+            for x in addends.get_vec() {
+                match x.1.as_ref() {
+                    ByzNode::Rational { rational } => {
+                        new_addends.insert_rational(&x.0 * rational);
+                    },
+                    _ => {
+                        new_addends.insert_rc(x.clone());
+                    }
+                }
+            }
+
+            if new_addends.get_vec().len() == addends.get_vec().len() {
+                return None;
+            }
+            if new_addends.get_vec().is_empty() {
+                return Some(ByzNode::Rational { rational: new_addends.get_rational_part().clone() });
+            }
+
+            return Some(ByzNode::Add { addends: new_addends });
+        },
+        ByzNode::Mul { products } => {
+            let mut new_products = ByzNodePowerMulVec::new();
+            new_products.insert_rational(products.get_rational_part().clone());
+
+            for x in products.get_vec() {
+                match x.1.as_ref() {
+                    ByzNode::Rational { rational } if let Some(value) = exact_pow(rational, &x.0) => {
+                        new_products.insert_rational(value);
+                    },
+                    _ => {
+                        new_products.insert_rc(x.clone());
+                    }
+                }
+            }
+
+            if new_products.get_vec().len() == products.get_vec().len() {
+                return None;
+            }
+            if new_products.get_vec().is_empty() {
+                return Some(ByzNode::Rational { rational: new_products.get_rational_part().clone() });
+            }
+
+            return Some(ByzNode::Mul { products: new_products });
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            let ByzNode::Rational { rational } = base.as_ref() else {
+                return None;
+            };
+
+            return exact_pow(rational, exp).map(|rational| ByzNode::Rational { rational });
+        },
+        _ => {
+            return None;
+        }
+    }
+}
+
+/// base^exp when exp is an integer (and base isn't zero with a negative exp)
+fn exact_pow(base: &Rational, exp: &Rational) -> Option<Rational> {
+    if !exp.is_int() || (base.is_zero() && exp.is_negative()) {
+        return None;
+    }
+
+    let mut value = base.clone();
+    value.pow_bigint(&exp.floor()).ok()?;
+
+    return Some(value);
+}
+
+impl ByzNode {
+    /// Replaces every subtree made of only Rational nodes, Add, Mul and integer Pows with the single Rational it's equal to
+    /// So neoprene doesn't spend interval arithmetic on values it could know exactly
+    /// Where only some children of an Add or Mul fold, they're merged into its rational part
+    pub fn fold_constants(&self) -> ByzNode {
+        return self.fold(&mut |node: ByzNode| fold_rational_children(&node).unwrap_or(node));
+    }
+}
+
+impl ByzNode {
+    /// Merges like terms of every sum in the tree, even when they started out in different branches
+    /// Nested Adds are flattened into the Add above them (scaling their coefficients) and rational factors of Mul terms are moved into the coefficient
+    /// So x + 2*(x + y) and (x + y) + 2*x both become 3*x + 2*y, nothing outside of sums is touched
+    pub fn collect_terms(&self) -> ByzNode {
+        return self.fold(&mut |node: ByzNode| {
+            if let ByzNode::Add { addends } = &node {
+                return collect_addends(addends);
+            }
+            return node;
+        });
+    }
+}
+
+/// canonicalize stops here even if the tree is still changing, usually the first round already reaches the fixed point
+const MAX_CANONICALIZE_ROUNDS: u32 = 16;
+
+impl ByzNode {
+    /// A normal form, so trees built differently compare Equal once canonicalized when they only differ by
+    /// Nesting, order of terms, constant subtrees, rational factors, identity elements or products and small powers of sums left unexpanded
+    /// (Terms are already kept in a fixed order by the sorted vecs, this makes the structure around them fixed as well)
+    /// Values that are equal for deeper reasons, like sin(x)^2 + cos(x)^2 and 1, still come out different
+    pub fn canonicalize(&self) -> ByzNode {
+        let mut node = self.fold_constants().simplify().expand();
+
+        for _ in 0..MAX_CANONICALIZE_ROUNDS {
+            let next = node.fold_constants().simplify().expand();
+            if next == node {
+                break;
+            }
+
+            node = next;
+        }
+
+        return node;
+    }
+}
+
+impl ByzNode {
+    /// Whether self and other are shown equal symbolically, by canonicalizing self - other and checking that it comes out as 0
+    /// False doesn't mean they differ, only that canonicalize couldn't cancel the difference (sin(x)^2 + cos(x)^2 and 1 aren't provably equal)
+    /// This is the half of deciding equality that neoprene_comp can't do, ranges around two equal irrational values never separate
+    /// Like simplify, equal means equal wherever both sides can be evaluated, so x/x is provably equal to 1
+    pub fn provably_eq(&self, other: &ByzNode) -> bool {
+        if self == other {
+            return true;
+        }
+
+        let mut difference = ByzNodeCoefficientAddVec::new();
+        push_addend(&mut difference, &Rational::one(), ByzRc::new(self.clone()));
+        push_addend(&mut difference, &Rational::from(-1), ByzRc::new(other.clone()));
+
+        let difference = finish_add(difference).canonicalize();
+        return matches!(&difference, ByzNode::Rational { rational } if rational.is_zero());
+    }
+}
@@ -1,8 +1,9 @@
 use std::cmp::Ordering;
-use std::rc::Rc;
 use std::fmt::{Debug, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::OnceLock;
 
-use crate::byzantine::ByzNode;
+use crate::byzantine::{ByzNode, ByzRc};
 use crate::rational::Rational;
 
 /// Know which type best fits your situation for storing an arbitrary equation.
@@ -14,28 +15,33 @@ pub trait ByzNodeVec {
     fn insert_rational(&mut self, rational: Rational);
 
     fn insert(&mut self, item: (Rational, ByzNode)) {
+        self.insert_rc((item.0, ByzRc::new(item.1)));
+    }
+
+    /// Same as insert, but the node can be shared with other trees
+    fn insert_rc(&mut self, item: (Rational, ByzRc<ByzNode>)) {
         let vec = self.get_vec_mut();
         
         
-        match vec.binary_search_by(|x| x.1.as_ref().cmp(&item.1)) {
+        match vec.binary_search_by(|x| x.1.as_ref().cmp(item.1.as_ref())) {
             Ok(index) => {
                 // Item was found, incrementing stored rational by supplied rational... (coefficient or power works for this)
                 vec[index].0 += &item.0;
 
-                if vec[index].0 == Rational::from(0) {
+                if vec[index].0 == 0 {
                     // If coefficient or power is 0, the item should be removed from the vec
                     vec.remove(index);
                 }
             },
             Err(index) => {
                 // Item was not found, adding to vec...
-                vec.insert(index, (item.0, Rc::new(item.1)));
+                vec.insert(index, item);
             },
         }
     }
 
-    fn get_vec(&self) -> &Vec<(Rational, Rc<ByzNode>)>;
-    fn get_vec_mut(&mut self) -> &mut Vec<(Rational, Rc<ByzNode>)>;
+    fn get_vec(&self) -> &Vec<(Rational, ByzRc<ByzNode>)>;
+    fn get_vec_mut(&mut self) -> &mut Vec<(Rational, ByzRc<ByzNode>)>;
 
     fn get_rational_part(&self) -> &Rational;
     fn get_rational_part_mut(&mut self) -> &mut Rational;
@@ -79,6 +85,20 @@ fn util_eq<T>(a: &T, b: &T) -> bool where T: ByzNodeVec {
     return true;
 }
 
+/// Hashes the same things util_eq compares, so vecs that are equal hash the same
+/// Each node contributes its own structural hash, which is cached so big trees aren't walked again
+fn util_hash<T>(a: &T) -> u64 where T: ByzNodeVec {
+    let mut hasher = DefaultHasher::new();
+
+    a.get_rational_part().hash(&mut hasher);
+    for x in a.get_vec() {
+        x.0.hash(&mut hasher);
+        hasher.write_u64(x.1.structural_hash());
+    }
+
+    return hasher.finish();
+}
+
 /// Note that this is comparing notation, not any kind of numeric value
 #[inline]
 fn util_cmp<T>(a: &T, b: &T) -> Ordering where T: ByzNodeVec {
@@ -109,27 +129,37 @@ fn util_cmp<T>(a: &T, b: &T) -> Ordering where T: ByzNodeVec {
 
 
 /// rational_summand + a*f_a() + b*f_b() + c*f_c() + ...
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "crate::byzantine_serde::ByzNodeVecRepr", into = "crate::byzantine_serde::ByzNodeVecRepr"))]
 pub struct ByzNodeCoefficientAddVec {
     rational_part: Rational,
-    vec: Vec<(Rational, Rc<ByzNode>)>
+    vec: Vec<(Rational, ByzRc<ByzNode>)>,
+    /// Filled in by structural_hash, and cleared whenever the vec or rational part may change
+    hash_cache: OnceLock<u64>
 }
 
 impl ByzNodeCoefficientAddVec {
     pub fn new() -> ByzNodeCoefficientAddVec {
-        return ByzNodeCoefficientAddVec { rational_part: Rational::from(0), vec: Vec::new() }
+        return ByzNodeCoefficientAddVec { rational_part: Rational::from(0), vec: Vec::new(), hash_cache: OnceLock::new() }
     }
 }
 
 /// rational_factor * f_a()^a * f_b()^b * f_c()^c + ...
 /// (Here"^ is used for exponent)
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "crate::byzantine_serde::ByzNodeVecRepr", into = "crate::byzantine_serde::ByzNodeVecRepr"))]
 pub struct ByzNodePowerMulVec {
     rational_part: Rational,
-    vec: Vec<(Rational, Rc<ByzNode>)>
+    vec: Vec<(Rational, ByzRc<ByzNode>)>,
+    /// Filled in by structural_hash, and cleared whenever the vec or rational part may change
+    hash_cache: OnceLock<u64>
 }
 
 impl ByzNodePowerMulVec {
     pub fn new() -> ByzNodePowerMulVec {
-        return ByzNodePowerMulVec { rational_part: Rational::from(1), vec: Vec::new() }
+        return ByzNodePowerMulVec { rational_part: Rational::from(1), vec: Vec::new(), hash_cache: OnceLock::new() }
     }
 }
 
@@ -142,10 +172,11 @@ impl ByzNodePowerMulVec {
 
 
 impl ByzNodeVec for ByzNodeCoefficientAddVec {
-    fn get_vec(&self) -> &Vec<(Rational, Rc<ByzNode>)> {
+    fn get_vec(&self) -> &Vec<(Rational, ByzRc<ByzNode>)> {
         return &self.vec;
     }
-    fn get_vec_mut(&mut self) -> &mut Vec<(Rational, Rc<ByzNode>)> {
+    fn get_vec_mut(&mut self) -> &mut Vec<(Rational, ByzRc<ByzNode>)> {
+        self.hash_cache.take();
         return &mut self.vec;
     }
 
@@ -153,11 +184,12 @@ impl ByzNodeVec for ByzNodeCoefficientAddVec {
         return &self.rational_part;
     }
     fn get_rational_part_mut(&mut self) -> &mut Rational {
+        self.hash_cache.take();
         return &mut self.rational_part;
     }
 
     fn insert_rational(&mut self, rational: Rational) {
-        self.rational_part += &rational;
+        *self.get_rational_part_mut() += &rational;
     } 
 }
 
@@ -169,6 +201,12 @@ impl PartialEq for ByzNodeCoefficientAddVec {
 
 impl Eq for ByzNodeCoefficientAddVec {}
 
+impl Hash for ByzNodeCoefficientAddVec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.structural_hash());
+    }
+}
+
 impl PartialOrd for ByzNodeCoefficientAddVec {
     fn partial_cmp(&self, other: &ByzNodeCoefficientAddVec) -> Option<Ordering> {
         return Some(util_cmp(self, other));
@@ -197,7 +235,7 @@ impl Debug for ByzNodeCoefficientAddVec {
             }
 
             let item = &vec[i];
-            if item.0 == Rational::from(1) {
+            if item.0 == 1 {
                 a.push_str(format!("{:?}", vec[i].1).as_str())
             } else {
                 a.push_str(format!("{:?}*{:?}", vec[i].0, vec[i].1).as_str());
@@ -212,15 +250,25 @@ impl ByzNodeCoefficientAddVec {
     pub fn deep_clone(&self) -> ByzNodeCoefficientAddVec {
         return ByzNodeCoefficientAddVec { 
             rational_part: self.rational_part.clone(), 
-            vec: self.vec.iter().map(|x| (x.0.clone(), Rc::new(x.1.deep_clone()))).collect()
+            vec: self.vec.iter().map(|x| (x.0.clone(), ByzRc::new(x.1.deep_clone()))).collect(),
+            hash_cache: self.hash_cache.clone()
         }
     }
 
+    /// A hash of the whole structure, computed once and kept until this is next changed
+    pub fn structural_hash(&self) -> u64 {
+        return *self.hash_cache.get_or_init(|| util_hash(self));
+    }
+
+    pub(crate) fn is_hash_cached(&self) -> bool {
+        return self.hash_cache.get().is_some();
+    }
+
     pub fn combine(&mut self, other: &ByzNodeCoefficientAddVec) {
-        self.rational_part += &other.rational_part;
+        self.insert_rational(other.rational_part.clone());
 
         for i in &other.vec {
-            self.insert((i.0.clone(), (*i.1).deep_clone()));
+            self.insert_rc(i.clone());
         }
     }
 }
@@ -232,10 +280,11 @@ impl ByzNodeCoefficientAddVec {
 
 
 impl ByzNodeVec for ByzNodePowerMulVec {
-    fn get_vec(&self) -> &Vec<(Rational, Rc<ByzNode>)> {
+    fn get_vec(&self) -> &Vec<(Rational, ByzRc<ByzNode>)> {
         return &self.vec;
     }
-    fn get_vec_mut(&mut self) -> &mut Vec<(Rational, Rc<ByzNode>)> {
+    fn get_vec_mut(&mut self) -> &mut Vec<(Rational, ByzRc<ByzNode>)> {
+        self.hash_cache.take();
         return &mut self.vec;
     }
 
@@ -243,11 +292,12 @@ impl ByzNodeVec for ByzNodePowerMulVec {
         return &self.rational_part;
     }
     fn get_rational_part_mut(&mut self) -> &mut Rational {
+        self.hash_cache.take();
         return &mut self.rational_part;
     }
 
     fn insert_rational(&mut self, rational: Rational) {
-        self.rational_part *= &rational;
+        *self.get_rational_part_mut() *= &rational;
     } 
 }
 
@@ -259,6 +309,12 @@ impl PartialEq for ByzNodePowerMulVec {
 
 impl Eq for ByzNodePowerMulVec {}
 
+impl Hash for ByzNodePowerMulVec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.structural_hash());
+    }
+}
+
 impl PartialOrd for ByzNodePowerMulVec {
     fn partial_cmp(&self, other: &ByzNodePowerMulVec) -> Option<Ordering> {
         return Some(util_cmp(self, other));
@@ -287,7 +343,7 @@ impl Debug for ByzNodePowerMulVec {
             }
 
             let item = &vec[i];
-            if item.0 == Rational::from(1) {
+            if item.0 == 1 {
                 a.push_str(format!("{:?}", vec[i].1).as_str())
             } else {
                 a.push_str(format!("{:?}^{:?}", vec[i].1, vec[i].0).as_str());
@@ -302,15 +358,25 @@ impl ByzNodePowerMulVec {
     pub fn deep_clone(&self) -> ByzNodePowerMulVec {
         return ByzNodePowerMulVec { 
             rational_part: self.rational_part.clone(), 
-            vec: self.vec.iter().map(|x| (x.0.clone(), Rc::new(x.1.deep_clone()))).collect()
+            vec: self.vec.iter().map(|x| (x.0.clone(), ByzRc::new(x.1.deep_clone()))).collect(),
+            hash_cache: self.hash_cache.clone()
         }
     }
 
+    /// A hash of the whole structure, computed once and kept until this is next changed
+    pub fn structural_hash(&self) -> u64 {
+        return *self.hash_cache.get_or_init(|| util_hash(self));
+    }
+
+    pub(crate) fn is_hash_cached(&self) -> bool {
+        return self.hash_cache.get().is_some();
+    }
+
     pub fn combine(&mut self, other: &ByzNodePowerMulVec) {
-        self.rational_part += &other.rational_part;
+        self.insert_rational(other.rational_part.clone());
 
         for i in &other.vec {
-            self.insert((i.0.clone(), (*i.1).deep_clone()));
+            self.insert_rc(i.clone());
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::byzantine::{ByzNode, ByzRc};
+
+/// Read-only traversal, see ByzNode::walk
+/// Both methods do nothing by default, so a visitor only has to implement the one it needs
+pub trait ByzVisitor {
+    /// Called on the way down, before any of node's children, returning false skips them (leave is still called)
+    fn enter(&mut self, _node: &ByzNode) -> bool {
+        return true;
+    }
+
+    /// Called on the way up, after all of node's children
+    fn leave(&mut self, _node: &ByzNode) {}
+}
+
+/// Bottom-up rewriting, see ByzNode::fold
+pub trait ByzFolder {
+    /// Rewrites node, whose children have already been folded
+    fn fold(&mut self, node: ByzNode) -> ByzNode;
+}
+
+/// Any closure from node to node is a folder, so node.fold(&mut |x| ...) works without a struct
+impl<F> ByzFolder for F where F: FnMut(ByzNode) -> ByzNode {
+    fn fold(&mut self, node: ByzNode) -> ByzNode {
+        return self(node);
+    }
+}
+
+/// Iterator over a tree in pre-order, see ByzNode::preorder
+pub struct Preorder<'a> {
+    stack: Vec<&'a ByzNode>
+}
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = &'a ByzNode;
+
+    fn next(&mut self) -> Option<&'a ByzNode> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().into_iter().rev().map(|x| x.as_ref()));
+
+        return Some(node);
+    }
+}
+
+impl ByzNode {
+    /// Every node in the tree, self first and each node before its children (in the same order as map_children)
+    /// The pending nodes are kept on a heap stack, so this works on trees of any depth, which is what walk and fold are built on as well
+    /// A subtree shared by several parents comes up once for each of them
+    pub fn preorder(&self) -> Preorder<'_> {
+        return Preorder { stack: vec![self] };
+    }
+
+    /// Walks the whole tree depth first, children in the same order as map_children, without recursing
+    /// A subtree shared by several parents is walked once for each of them
+    pub fn walk<T>(&self, visitor: &mut T) where T: ByzVisitor + ?Sized {
+        // The bool is whether the node was already entered, in which case it's waiting to be left
+        let mut stack = vec![(self, false)];
+
+        while let Some((node, entered)) = stack.pop() {
+            if entered || !visitor.enter(node) {
+                visitor.leave(node);
+                continue;
+            }
+
+            stack.push((node, true));
+            stack.extend(node.children().into_iter().rev().map(|x| (x.as_ref(), false)));
+        }
+    }
+
+    /// Rebuilds the tree from the bottom up, handing every node to folder once its children have been folded
+    /// So a pass only has to deal with the variants it cares about and can return everything else as it is, the rebuilding is done here
+    /// A subtree shared by several parents is folded once and the result is shared the same way, which assumes folder gives the same result for the same node
+    /// Like walk it doesn't recurse, so it works on trees of any depth
+    pub fn fold<T>(&self, folder: &mut T) -> ByzNode where T: ByzFolder + ?Sized {
+        // Folded nodes by address, children are always folded before their parent is rebuilt from them
+        let mut folded: HashMap<*const ByzNode, ByzRc<ByzNode>> = HashMap::new();
+        let mut stack = vec![(self, false)];
+
+        while let Some((node, children_folded)) = stack.pop() {
+            if !children_folded {
+                stack.push((node, true));
+                stack.extend(node.children().into_iter().rev().map(|x| x.as_ref()).filter(|x| !folded.contains_key(&(*x as *const ByzNode))).map(|x| (x, false)));
+                continue;
+            }
+
+            // A shared subtree can be pushed by several parents before the first of them gets to it
+            if folded.contains_key(&(node as *const ByzNode)) {
+                continue;
+            }
+
+            let rebuilt = node.map_children(|x| Some(ByzRc::clone(&folded[&ByzRc::as_ptr(x)])));
+            let result = folder.fold(rebuilt.unwrap_or_else(|| node.clone()));
+
+            if std::ptr::eq(node, self) {
+                return result;
+            }
+            folded.insert(node, ByzRc::new(result));
+        }
+
+        unreachable!("the root is always folded last");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_byznode_utils as GBU;
+
+    const DEEP: usize = 200_000;
+
+    /// x nested DEEP levels deep, through a mix of unary nodes, powers, sums and products
+    fn chain(variable: &str) -> ByzNode {
+        let mut a = GBU::variable(variable);
+        for i in 0..DEEP {
+            a = match i % 4 {
+                0 => GBU::sin(a),
+                1 => GBU::pow(a, (1, 2).into()),
+                2 => GBU::add((1, 1).into(), vec![(None, a)]),
+                _ => GBU::mul(Some((2, 1).into()), vec![(None, a), (None, GBU::variable("z"))])
+            };
+        }
+        return a;
+    }
+
+    #[test]
+    fn deep_chain_bytes() {
+        let a = chain("x");
+        assert!(ByzNode::from_bytes(&a.to_bytes()).unwrap() == a);
+    }
+
+    #[test]
+    fn deep_chain_sexpr() {
+        let sexpr = chain("x").to_sexpr();
+        assert_eq!(sexpr.matches("(sin ").count(), DEEP / 4);
+        assert!(sexpr.starts_with("(mul 2 ") && sexpr.contains("(var x)"));
+    }
+
+    #[test]
+    fn deep_chain_deep_clone() {
+        let a = chain("x");
+        let b = a.deep_clone();
+        assert!(b == a);
+        assert!(!std::ptr::eq(a.children()[0].as_ref(), b.children()[0].as_ref()));
+    }
+
+    #[test]
+    fn deep_chain_substitute() {
+        let map = HashMap::from([("x".to_string(), ByzRc::new(GBU::variable("y")))]);
+        assert!(chain("x").substitute(&map) == chain("y"));
+    }
+}
@@ -1,18 +1,128 @@
 /// This file should be imported as GBU; i.e. ```use generate_byznode_utils as GBU;```
 
-use std::rc::Rc;
+use std::sync::OnceLock;
 
-use crate::byzantine::ByzNode;
+use num_bigint::{BigInt, BigUint};
+
+use crate::byzantine::{ByzNode, ByzRc};
 use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
-use crate::rational::Rational;
+use crate::rational::{Rational, Sign};
 use crate::byzantine::TransitiveConsts;
+use crate::rational_polynomial::RationalPolynomial;
 
 pub fn rational(rat: Rational) -> ByzNode {
     return ByzNode::Rational { rational: rat };
 }
 
 pub fn pow(a: ByzNode, b: Rational) -> ByzNode {
-    return ByzNode::Pow { base: Rc::new(a), exp: b };
+    return ByzNode::Pow { base: ByzRc::new(a), exp: b, hash_cache: OnceLock::new() };
+}
+
+pub fn exp(a: ByzNode) -> ByzNode {
+    return ByzNode::Exp { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn ln(a: ByzNode) -> ByzNode {
+    return ByzNode::Ln { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn sin(a: ByzNode) -> ByzNode {
+    return ByzNode::Sin { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn cos(a: ByzNode) -> ByzNode {
+    return ByzNode::Cos { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn tan(a: ByzNode) -> ByzNode {
+    return ByzNode::Tan { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn arctan(a: ByzNode) -> ByzNode {
+    return ByzNode::Arctan { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn arcsin(a: ByzNode) -> ByzNode {
+    return ByzNode::Arcsin { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn arccos(a: ByzNode) -> ByzNode {
+    return ByzNode::Arccos { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn sinh(a: ByzNode) -> ByzNode {
+    return ByzNode::Sinh { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn cosh(a: ByzNode) -> ByzNode {
+    return ByzNode::Cosh { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn tanh(a: ByzNode) -> ByzNode {
+    return ByzNode::Tanh { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn abs(a: ByzNode) -> ByzNode {
+    return ByzNode::Abs { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+/// Sorts and deduplicates the arguments of Min and Max, panics if there are none
+fn min_max_args(args: Vec<ByzNode>) -> Vec<ByzRc<ByzNode>> {
+    if args.is_empty() {
+        panic!("Attempted to create Min or Max with no arguments");
+    }
+
+    let mut args: Vec<ByzRc<ByzNode>> = args.into_iter().map(ByzRc::new).collect();
+    args.sort();
+    args.dedup();
+
+    return args;
+}
+
+/// Panics if args is empty
+pub fn min(args: Vec<ByzNode>) -> ByzNode {
+    return ByzNode::Min { args: min_max_args(args), hash_cache: OnceLock::new() };
+}
+
+/// Panics if args is empty
+pub fn max(args: Vec<ByzNode>) -> ByzNode {
+    return ByzNode::Max { args: min_max_args(args), hash_cache: OnceLock::new() };
+}
+
+pub fn floor(a: ByzNode) -> ByzNode {
+    return ByzNode::Floor { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn ceil(a: ByzNode) -> ByzNode {
+    return ByzNode::Ceil { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+pub fn frac(a: ByzNode) -> ByzNode {
+    return ByzNode::Frac { arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+/// Logarithm of a to the given base
+pub fn log(base: ByzNode, a: ByzNode) -> ByzNode {
+    return ByzNode::Log { base: ByzRc::new(base), arg: ByzRc::new(a), hash_cache: OnceLock::new() };
+}
+
+/// a^b for any exponent, a rational b gives a plain Pow
+pub fn real_pow(a: ByzNode, b: ByzNode) -> ByzNode {
+    if let ByzNode::Rational { rational } = &b {
+        return pow(a, rational.clone());
+    }
+
+    return ByzNode::RealPow { base: ByzRc::new(a), exp: ByzRc::new(b), hash_cache: OnceLock::new() };
+}
+
+/// Integer power with an exponent of any size
+pub fn powi(a: ByzNode, b: BigInt) -> ByzNode {
+    let sign = match b.sign() {
+        num_bigint::Sign::Minus => Sign::Neg,
+        _ => Sign::Pos
+    };
+
+    return pow(a, Rational::new(sign, b.magnitude().clone(), BigUint::from(1_u8)));
 }
 
 pub fn add(rat: Rational, vec: Vec<(Option<Rational>, ByzNode)>) -> ByzNode {
@@ -58,6 +168,36 @@ pub fn mul(rat: Option<Rational>, vec: Vec<(Option<Rational>, ByzNode)>) -> ByzN
     return ByzNode::Mul { products: c };
 }
 
+pub fn variable(name: &str) -> ByzNode {
+    return ByzNode::Variable { name: name.to_string() };
+}
+
 pub fn transitive(transitive_const: TransitiveConsts) -> ByzNode {
     return ByzNode::TransitiveConst { transitive_const }
-}
\ No newline at end of file
+}
+
+/// The index-th smallest real root (counting from 0) of polynomial, with the polynomial made square free and monic
+/// A root of a linear polynomial comes out as the rational it is
+/// Panics if polynomial doesn't have that many distinct real roots (or is zero)
+pub fn algebraic_root(polynomial: &RationalPolynomial, index: usize) -> ByzNode {
+    return checked_algebraic_root(polynomial, index).unwrap_or_else(|| panic!("Attempted to take real root {} of {}, which doesn't have that many", index, polynomial));
+}
+
+/// algebraic_root, but None instead of a panic
+pub(crate) fn checked_algebraic_root(polynomial: &RationalPolynomial, index: usize) -> Option<ByzNode> {
+    if polynomial.is_zero() || polynomial.count_real_roots() <= index {
+        return None;
+    }
+
+    let polynomial = polynomial.square_free();
+    if polynomial.degree() == Some(1) {
+        return Some(rational(-polynomial.coefficient(0)));
+    }
+
+    return Some(ByzNode::AlgebraicRoot { polynomial, index });
+}
+
+/// Whether polynomial and index make a valid AlgebraicRoot as they are (square free, monic and with enough real roots), for decoders that shouldn't change what they read
+pub(crate) fn is_valid_algebraic_root(polynomial: &RationalPolynomial, index: usize) -> bool {
+    return !polynomial.is_zero() && polynomial.square_free() == *polynomial && polynomial.count_real_roots() > index;
+}
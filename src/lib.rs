@@ -1,21 +1,86 @@
 pub mod rational;
+pub mod rational_parse;
+pub mod rational_format;
+pub mod rational_continued_fraction;
+pub mod rational_dyadic;
+pub mod rational_bytes;
+#[cfg(feature = "rand")]
+pub mod rational_rand;
+#[cfg(feature = "serde")]
+mod rational_serde;
+#[cfg(feature = "num-rational")]
+pub mod rational_num_rational;
 pub mod rational_range;
+pub mod rational_range_set;
+pub mod rational_centered_range;
+pub mod rational_polynomial;
 pub mod byzantine;
+pub mod byzantine_bytes;
+pub mod byzantine_parse;
+pub mod byzantine_format;
+pub mod byzantine_mathml;
+pub mod byzantine_cas;
+pub mod byzantine_sexpr;
+#[cfg(feature = "serde")]
+mod byzantine_serde;
 pub mod byznode_sorted_vec;
 pub mod generate_byznode_utils;
+pub mod byznode_simplify;
+pub mod byznode_expand;
+pub mod byznode_factor;
+pub mod byznode_arena;
+pub mod byznode_ops;
+mod byznode_macro;
+pub mod byznode_metrics;
+pub mod byznode_visit;
+pub mod byznode_polynomial;
 pub mod neoprene;
+pub mod neoprene_config;
 pub mod neoprene_taylor;
+pub mod neoprene_trig;
 pub mod neoprene_comp;
+pub mod neoprene_digits;
+pub mod neoprene_batch;
+#[cfg(feature = "disk_cache")]
+pub mod neoprene_disk_cache;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub mod arbitrary;
 
 /*
 pub use crate::rational::*;
+pub use crate::rational_parse::*;
+pub use crate::rational_format::*;
+pub use crate::rational_continued_fraction::*;
+pub use crate::rational_dyadic::*;
+pub use crate::rational_bytes::*;
 pub use crate::rational_range::*;
+pub use crate::rational_range_set::*;
+pub use crate::rational_centered_range::*;
+pub use crate::rational_polynomial::*;
 pub use crate::byzantine::*;
+pub use crate::byzantine_bytes::*;
+pub use crate::byzantine_parse::*;
+pub use crate::byzantine_format::*;
+pub use crate::byzantine_mathml::*;
+pub use crate::byzantine_cas::*;
+pub use crate::byzantine_sexpr::*;
 pub use byznode_sorted_vec::*;
 pub use generate_byznode_utils::*;
+pub use byznode_simplify::*;
+pub use byznode_expand::*;
+pub use byznode_factor::*;
+pub use byznode_arena::*;
+pub use byznode_ops::*;
+pub use byznode_metrics::*;
+pub use byznode_visit::*;
+pub use byznode_polynomial::*;
 pub use neoprene::*;
+pub use neoprene_config::*;
 pub use neoprene_taylor::*;
+pub use neoprene_trig::*;
 pub use neoprene_comp::*;
+pub use neoprene_digits::*;
+pub use neoprene_batch::*;
 */
 
 /*
@@ -1,79 +1,581 @@
-use num_bigint::BigUint;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
-use crate::byzantine::{ByzNode, TransitiveConsts};
+use num_bigint::{BigInt, BigUint};
+
+use crate::byzantine::{ByzNode, ByzRc, TransitiveConsts};
 use crate::rational::Rational;
 use crate::rational_range::RationalRange;
+use crate::rational_polynomial::RationalPolynomial;
 use crate::byznode_sorted_vec::{ByzNodeCoefficientAddVec, ByzNodePowerMulVec, ByzNodeVec};
 use crate::neoprene_taylor;
+use crate::neoprene_trig;
+use crate::neoprene_config::{DenomLimit, NeopreneConfig};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NeopreneError {
+    /// The ranges produced during evaluation added up to more than NeopreneConfig::memory_limit_bits
+    MemoryLimitExceeded,
+    /// A power with an even root was taken of a range that may be negative
+    ComplexResult,
+    /// A range that may be zero was raised to a negative (or possibly negative) power, or was otherwise divided by (like cos in tan)
+    ZeroReciprocal,
+    /// The logarithm of a range that may be zero or negative was taken
+    NonPositiveLogarithm,
+    /// The argument of arcsin or arccos may be outside [-1, 1]
+    OutsideDomain,
+    /// The integer part needed by Floor, Ceil or Frac couldn't be pinned down, even after refining the argument up to max_iterations
+    IndeterminateInteger,
+    /// A Variable with this name was left in the tree, substitute it first
+    UnboundVariable(String),
+    /// An AlgebraicRoot's polynomial doesn't have as many real roots as its index asks for
+    MissingRoot,
+    /// A Min or Max with no arguments, which has no value
+    EmptyArgs,
+    /// approximation_iterations is above neoprene_taylor::MAX_APPROXIMATION_ITERATIONS, or a power takes a root that doesn't fit in a u32
+    TooLarge
+}
+
+/// The result of neoprene_evaluate_soft
+#[derive(Debug, Clone)]
+pub struct NeopreneSoftResult {
+    /// None when nothing at all could be said about the value
+    pub range: Option<RationalRange>,
+    /// Everything that went wrong along the way, in the order it happened
+    pub diagnostics: Vec<NeopreneError>
+}
+
+/// Enclosures of transitive constants that can be shared between evaluations (and threads), so each constant is only computed once per precision
+pub struct NeopreneConstantCache {
+    ranges: Mutex<HashMap<(TransitiveConsts, BigUint, DenomLimit), RationalRange>>
+}
+
+impl NeopreneConstantCache {
+    pub fn new() -> NeopreneConstantCache {
+        return NeopreneConstantCache { ranges: Mutex::new(HashMap::new()) };
+    }
+
+    pub fn get_or_compute(&self, transitive_const: TransitiveConsts, config: &NeopreneConfig) -> RationalRange {
+        let key = (transitive_const, config.approximation_iterations.clone(), config.denom_limit.clone());
+
+        if let Some(range) = self.ranges.lock().unwrap().get(&key) {
+            return range.clone();
+        }
+
+        // Computed without holding the lock so other threads aren't held up, at worst two threads compute the same constant
+        let range = neoprene_taylor::compute_transitive(transitive_const, config);
+        self.ranges.lock().unwrap().insert(key, range.clone());
+
+        return range;
+    }
+}
 
-pub fn neoprene_transitive(transitive_const: TransitiveConsts, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    // These are perfectly accurate and do not need to be fixed later
-    match transitive_const {
-        TransitiveConsts::Pi => {
-            return neoprene_taylor::compute_pi(approximation_iterations, limit_denom);
+impl Default for NeopreneConstantCache {
+    fn default() -> NeopreneConstantCache {
+        return NeopreneConstantCache::new();
+    }
+}
+
+/// State for a single evaluation of a ByzNode at a fixed precision
+/// A subtree that several parents point to (the same Rc, e.g. from an ExprArena) is only evaluated once per call to neoprene_byznode
+pub struct NeopreneContext<'a> {
+    pub config: &'a NeopreneConfig,
+    constant_cache: Option<&'a NeopreneConstantCache>,
+    memory_bits: u64,
+    soft_fail: bool,
+    diagnostics: Vec<NeopreneError>,
+    /// The ranges of the shared subtrees evaluated so far, by address
+    /// Only kept for the duration of the outermost neoprene_byznode, while the tree is borrowed and no address can be reused
+    shared_ranges: HashMap<*const ByzNode, Option<RationalRange>>,
+    /// How many calls to neoprene_byznode deep the evaluation is
+    depth: usize
+}
+
+impl<'a> NeopreneContext<'a> {
+    pub fn new(config: &'a NeopreneConfig) -> NeopreneContext<'a> {
+        return NeopreneContext { config, constant_cache: None, memory_bits: 0, soft_fail: false, diagnostics: Vec::new(), shared_ranges: HashMap::new(), depth: 0 };
+    }
+
+    /// Transitive constants are taken from (and stored in) the cache instead of being computed by every evaluation
+    pub fn with_constant_cache(config: &'a NeopreneConfig, constant_cache: &'a NeopreneConstantCache) -> NeopreneContext<'a> {
+        return NeopreneContext { config, constant_cache: Some(constant_cache), memory_bits: 0, soft_fail: false, diagnostics: Vec::new(), shared_ranges: HashMap::new(), depth: 0 };
+    }
+
+    /// Approximate memory used so far, as the sum of the bit lengths of every numerator and denominator in the produced ranges
+    /// (This counts every intermediate range rather than what is alive at once, so it is an upper bound on the real heap usage)
+    pub fn memory_bits(&self) -> u64 {
+        return self.memory_bits;
+    }
+
+    /// In soft fail mode, conditions that still leave something sound to say about the value are recorded as diagnostics instead of failing
+    pub fn set_soft_fail(&mut self, soft_fail: bool) {
+        self.soft_fail = soft_fail;
+    }
+
+    pub fn diagnostics(&self) -> &Vec<NeopreneError> {
+        return &self.diagnostics;
+    }
+
+    /// Accounts for a range produced during evaluation, failing once the configured cap is passed
+    pub fn track(&mut self, range: &RationalRange) -> Result<(), NeopreneError> {
+        self.memory_bits += range.min.numer_bits() + range.min.denom_bits();
+        self.memory_bits += range.max.numer_bits() + range.max.denom_bits();
+
+        if let Some(limit) = self.config.memory_limit_bits && self.memory_bits > limit {
+            return Err(NeopreneError::MemoryLimitExceeded);
+        }
+
+        return Ok(());
+    }
+}
+
+pub fn neoprene_transitive(transitive_const: TransitiveConsts, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    match ctx.constant_cache {
+        Some(constant_cache) => {
+            return Ok(constant_cache.get_or_compute(transitive_const, ctx.config));
         },
-        TransitiveConsts::Euler => {
-            return neoprene_taylor::compute_euler(approximation_iterations, limit_denom);
+        None => {
+            return Ok(neoprene_taylor::compute_transitive(transitive_const, ctx.config));
         }
     }
 }
 
-pub fn neoprene_add(addends: &ByzNodeCoefficientAddVec, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
+pub fn neoprene_add(addends: &ByzNodeCoefficientAddVec, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
     let rat = addends.get_rational_part();
     let vec = addends.get_vec();
 
-    let mut range = RationalRange::from((rat.clone(), rat.clone()));
+    let mut range = RationalRange::from(rat);
 
     for i in vec {
-        let mut i_range = neoprene_byznode(i.1.as_ref(), approximation_iterations, limit_denom);
-        i_range.min *= &i.0;
-        i_range.max *= &i.0;
+        let mut i_range = neoprene_byznode(i.1.as_ref(), ctx)?;
+        i_range *= &i.0;
 
         range += &i_range;
+        ctx.config.denom_limit.apply(&mut range);
+        ctx.track(&range)?;
     }
 
-    return range;
+    return Ok(range);
+}
+
+/// rational_range_pow, except that in soft fail mode an even root of a possibly negative range is taken of its non-negative part only
+/// (That encloses every real value the power can take, but not the complex ones, so a diagnostic is recorded)
+fn neoprene_range_pow(range: &RationalRange, exp: &Rational, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    match neoprene_taylor::rational_range_pow(range, exp, ctx.config) {
+        Err(NeopreneError::ComplexResult) if ctx.soft_fail && !range.max.is_negative() => {
+            ctx.diagnostics.push(NeopreneError::ComplexResult);
+
+            let non_negative_part = RationalRange::from((Rational::zero(), range.max.clone()));
+            return neoprene_taylor::rational_range_pow(&non_negative_part, exp, ctx.config);
+        },
+        result => {
+            return result;
+        }
+    }
 }
 
-pub fn neoprene_mul(products: &ByzNodePowerMulVec, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
+pub fn neoprene_mul(products: &ByzNodePowerMulVec, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
     let rat = products.get_rational_part();
     let vec = products.get_vec();
 
-    let mut range = RationalRange::from((rat.clone(), rat.clone()));
+    let mut range = RationalRange::from(rat);
 
     for i in vec {
-        let mut i_range = neoprene_byznode(i.1.as_ref(), approximation_iterations, limit_denom);
+        let mut i_range = neoprene_byznode(i.1.as_ref(), ctx)?;
         
-        i_range = neoprene_taylor::rational_range_pow(&mut i_range, &i.0, approximation_iterations, limit_denom);
+        i_range = neoprene_range_pow(&i_range, &i.0, ctx)?;
 
         range *= &i_range;
+        ctx.config.denom_limit.apply(&mut range);
+        ctx.track(&range)?;
     }
 
-    return range;
+    return Ok(range);
+}
+
+pub fn neoprene_pow(byznode: &ByzNode, exp: &Rational, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let mut range = neoprene_byznode(byznode, ctx)?;
+    range = neoprene_range_pow(&range, exp, ctx)?;
+    ctx.config.denom_limit.apply(&mut range);
+    return Ok(range);
+}
+
+/// exp(exp ln(base)), unless exp comes out as a single rational in which case it's just neoprene_pow
+pub fn neoprene_real_pow(base: &ByzNode, exp: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let exp_range = neoprene_byznode(exp, ctx)?;
+
+    if exp_range.is_point() {
+        return neoprene_pow(base, &exp_range.min, ctx);
+    }
+
+    let base_range = neoprene_byznode(base, ctx)?;
+
+    let mut range = neoprene_taylor::rational_range_ln(&base_range, ctx.config)?;
+    range *= &exp_range;
+    ctx.config.denom_limit.apply(&mut range);
+    ctx.track(&range)?;
+
+    return Ok(neoprene_taylor::rational_range_exp(&range, ctx.config));
 }
 
-pub fn neoprene_pow(byznode: &ByzNode, exp: &Rational, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    let mut range = neoprene_byznode(byznode, approximation_iterations, limit_denom);
-    range = neoprene_taylor::rational_range_pow(&mut range, exp, approximation_iterations, limit_denom);
+pub fn neoprene_exp(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    return Ok(neoprene_taylor::rational_range_exp(&range, ctx.config));
+}
+
+pub fn neoprene_ln(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    return neoprene_taylor::rational_range_ln(&range, ctx.config);
+}
+
+pub fn neoprene_sinh(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    return Ok(neoprene_taylor::rational_range_sinh(&range, ctx.config));
+}
+
+pub fn neoprene_cosh(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    return Ok(neoprene_taylor::rational_range_cosh(&range, ctx.config));
+}
+
+pub fn neoprene_tanh(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    return Ok(neoprene_taylor::rational_range_tanh(&range, ctx.config));
+}
+
+pub fn neoprene_abs(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    return Ok(range.abs());
+}
+
+/// The envelope of the arguments' ranges, every value min could take is at least the smallest min and at most the smallest max
+pub fn neoprene_min(args: &[ByzRc<ByzNode>], ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let Some((first, rest)) = args.split_first() else {
+        return Err(NeopreneError::EmptyArgs);
+    };
+    let mut range = neoprene_byznode(first, ctx)?;
+
+    for arg in rest {
+        range.pointwise_min(&neoprene_byznode(arg, ctx)?);
+    }
+
+    return Ok(range);
+}
+
+/// See neoprene_min
+pub fn neoprene_max(args: &[ByzRc<ByzNode>], ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let Some((first, rest)) = args.split_first() else {
+        return Err(NeopreneError::EmptyArgs);
+    };
+    let mut range = neoprene_byznode(first, ctx)?;
+
+    for arg in rest {
+        range.pointwise_max(&neoprene_byznode(arg, ctx)?);
+    }
+
+    return Ok(range);
+}
+
+/// ln(arg)/ln(base), where ln(base) must not contain zero (so base can't be 1)
+pub fn neoprene_log(base: &ByzNode, arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let base_range = neoprene_byznode(base, ctx)?;
+    let arg_range = neoprene_byznode(arg, ctx)?;
+
+    let ln_base = neoprene_taylor::rational_range_ln(&base_range, ctx.config)?;
+    let ln_arg = neoprene_taylor::rational_range_ln(&arg_range, ctx.config)?;
+
+    let mut range = ln_arg.try_div(&ln_base).map_err(|_| NeopreneError::ZeroReciprocal)?;
+    ctx.config.denom_limit.apply(&mut range);
+
+    return Ok(range);
+}
+
+/// Evaluates byznode, then evaluates it again at more and more precise steps of the config (for this subtree only) until done is true of its range
+/// Gives up once the config can't be refined any further, returning the last range along with whether done was reached
+fn neoprene_refine_until<F>(byznode: &ByzNode, ctx: &mut NeopreneContext, done: F) -> Result<(RationalRange, bool), NeopreneError> where F: Fn(&RationalRange) -> bool {
+    let mut range = neoprene_byznode(byznode, ctx)?;
+    let mut config = ctx.config.clone();
+
+    while !done(&range) {
+        config.refine();
+        if !config.can_refine() {
+            return Ok((range, false));
+        }
+
+        let mut refined_ctx = NeopreneContext {
+            config: &config,
+            constant_cache: ctx.constant_cache,
+            memory_bits: ctx.memory_bits,
+            soft_fail: ctx.soft_fail,
+            diagnostics: Vec::new(),
+            shared_ranges: HashMap::new(),
+            depth: 0
+        };
+        range = neoprene_byznode(byznode, &mut refined_ctx)?;
+
+        ctx.memory_bits = refined_ctx.memory_bits;
+        ctx.diagnostics.append(&mut refined_ctx.diagnostics);
+    }
+
+    return Ok((range, true));
+}
+
+/// Refines arg until round gives the same integer at both of its ends, returning its range and that integer
+/// If that never happens this fails with IndeterminateInteger, or in soft fail mode records it and returns None for the integer
+fn neoprene_integer_part(arg: &ByzNode, ctx: &mut NeopreneContext, round: fn(&Rational) -> BigInt) -> Result<(RationalRange, Option<BigInt>), NeopreneError> {
+    let (range, determined) = neoprene_refine_until(arg, ctx, |x| round(&x.min) == round(&x.max))?;
+
+    if determined {
+        let n = round(&range.min);
+        return Ok((range, Some(n)));
+    }
+
+    if !ctx.soft_fail {
+        return Err(NeopreneError::IndeterminateInteger);
+    }
+    ctx.diagnostics.push(NeopreneError::IndeterminateInteger);
+
+    return Ok((range, None));
+}
+
+pub fn neoprene_floor(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    match neoprene_integer_part(arg, ctx, Rational::floor)? {
+        (_, Some(n)) => {
+            return Ok(RationalRange::from(Rational::from(n)));
+        },
+        (range, None) => {
+            return Ok(RationalRange::from((Rational::from(range.min.floor()), Rational::from(range.max.floor()))));
+        }
+    }
+}
+
+pub fn neoprene_ceil(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    match neoprene_integer_part(arg, ctx, Rational::ceil)? {
+        (_, Some(n)) => {
+            return Ok(RationalRange::from(Rational::from(n)));
+        },
+        (range, None) => {
+            return Ok(RationalRange::from((Rational::from(range.min.ceil()), Rational::from(range.max.ceil()))));
+        }
+    }
+}
+
+pub fn neoprene_frac(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    match neoprene_integer_part(arg, ctx, Rational::floor)? {
+        (mut range, Some(n)) => {
+            range -= &Rational::from(n);
+            return Ok(range);
+        },
+        (_, None) => {
+            // Every fractional part is in [0, 1)
+            return Ok(RationalRange::from((Rational::zero(), Rational::one())));
+        }
+    }
+}
+
+/// The trig functions take pi from neoprene_transitive, so they share the cached enclosure of it when there is one
+pub fn neoprene_sin(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    let pi = neoprene_transitive(TransitiveConsts::Pi, ctx)?;
+    return Ok(neoprene_trig::rational_range_sin(&range, &pi, ctx.config));
+}
+
+pub fn neoprene_cos(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    let pi = neoprene_transitive(TransitiveConsts::Pi, ctx)?;
+    return Ok(neoprene_trig::rational_range_cos(&range, &pi, ctx.config));
+}
+
+pub fn neoprene_tan(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    let pi = neoprene_transitive(TransitiveConsts::Pi, ctx)?;
+    return neoprene_trig::rational_range_tan(&range, &pi, ctx.config);
+}
+
+pub fn neoprene_arctan(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    let pi = neoprene_transitive(TransitiveConsts::Pi, ctx)?;
+    return Ok(neoprene_trig::rational_range_arctan(&range, &pi, ctx.config));
+}
+
+pub fn neoprene_arcsin(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    let pi = neoprene_transitive(TransitiveConsts::Pi, ctx)?;
+    return neoprene_trig::rational_range_arcsin(&range, &pi, ctx.config);
+}
+
+pub fn neoprene_arccos(arg: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let range = neoprene_byznode(arg, ctx)?;
+    let pi = neoprene_transitive(TransitiveConsts::Pi, ctx)?;
+    return neoprene_trig::rational_range_arccos(&range, &pi, ctx.config);
+}
+
+pub fn neoprene_algebraic_root(polynomial: &RationalPolynomial, index: usize, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let mut range = neoprene_taylor::algebraic_root(polynomial, index, ctx.config)?;
+    ctx.config.denom_limit.apply(&mut range);
+    return Ok(range);
+}
+
+/// The nodes below byznode that more than one parent points to, each with an empty slot for its range
+fn find_shared_subtrees(byznode: &ByzNode) -> HashMap<*const ByzNode, Option<RationalRange>> {
+    let mut seen = HashSet::new();
+    let mut shared = HashMap::new();
+    let mut stack = vec![byznode];
+
+    while let Some(node) = stack.pop() {
+        for x in node.children() {
+            if !x.has_children() {
+                continue;
+            }
+
+            if seen.insert(ByzRc::as_ptr(x)) {
+                stack.push(x);
+            } else {
+                shared.insert(ByzRc::as_ptr(x), None);
+            }
+        }
+    }
+
+    return shared;
+}
+
+/// Evaluates byznode, taking the ranges of shared subtrees from ctx after the first time (see NeopreneContext)
+pub fn neoprene_byznode(byznode: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    let key = byznode as *const ByzNode;
+
+    if ctx.depth == 0 {
+        ctx.shared_ranges = find_shared_subtrees(byznode);
+    } else if let Some(Some(range)) = ctx.shared_ranges.get(&key) {
+        return Ok(range.clone());
+    }
+
+    ctx.depth += 1;
+    let range = neoprene_byznode_uncached(byznode, ctx);
+    ctx.depth -= 1;
+
+    if ctx.depth == 0 {
+        ctx.shared_ranges.clear();
+    } else if let Ok(range) = &range && let Some(slot) = ctx.shared_ranges.get_mut(&key) {
+        *slot = Some(range.clone());
+    }
+
     return range;
 }
 
-pub fn neoprene_byznode(byznode: &ByzNode, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    match byznode {
+fn neoprene_byznode_uncached(byznode: &ByzNode, ctx: &mut NeopreneContext) -> Result<RationalRange, NeopreneError> {
+    // The series below all need their term counts to fit in a u32, and would panic otherwise
+    if ctx.config.approximation_iterations > BigUint::from(neoprene_taylor::MAX_APPROXIMATION_ITERATIONS) {
+        return Err(NeopreneError::TooLarge);
+    }
+
+    let range = match byznode {
         ByzNode::Rational { rational } => {
-            return RationalRange::from((rational.clone(), rational.clone()));
+            RationalRange::from(rational)
         }
         ByzNode::TransitiveConst {transitive_const} => {
-            return neoprene_transitive(*transitive_const, &approximation_iterations, limit_denom);
+            neoprene_transitive(*transitive_const, ctx)?
         },
         ByzNode::Add { addends } => {
-            return neoprene_add(addends, approximation_iterations, limit_denom);
+            neoprene_add(addends, ctx)?
         },
         ByzNode::Mul { products } => {
-            return neoprene_mul(products, approximation_iterations, limit_denom);
+            neoprene_mul(products, ctx)?
+        },
+        ByzNode::Pow { base, exp, .. } => {
+            neoprene_pow(base, exp, ctx)?
+        },
+        ByzNode::Exp { arg, .. } => {
+            neoprene_exp(arg, ctx)?
+        },
+        ByzNode::Ln { arg, .. } => {
+            neoprene_ln(arg, ctx)?
+        },
+        ByzNode::Sin { arg, .. } => {
+            neoprene_sin(arg, ctx)?
+        },
+        ByzNode::Cos { arg, .. } => {
+            neoprene_cos(arg, ctx)?
+        },
+        ByzNode::Tan { arg, .. } => {
+            neoprene_tan(arg, ctx)?
+        },
+        ByzNode::Arctan { arg, .. } => {
+            neoprene_arctan(arg, ctx)?
+        },
+        ByzNode::Arcsin { arg, .. } => {
+            neoprene_arcsin(arg, ctx)?
+        },
+        ByzNode::Arccos { arg, .. } => {
+            neoprene_arccos(arg, ctx)?
+        },
+        ByzNode::Sinh { arg, .. } => {
+            neoprene_sinh(arg, ctx)?
+        },
+        ByzNode::Cosh { arg, .. } => {
+            neoprene_cosh(arg, ctx)?
+        },
+        ByzNode::Tanh { arg, .. } => {
+            neoprene_tanh(arg, ctx)?
+        },
+        ByzNode::Abs { arg, .. } => {
+            neoprene_abs(arg, ctx)?
+        },
+        ByzNode::Min { args, .. } => {
+            neoprene_min(args, ctx)?
+        },
+        ByzNode::Max { args, .. } => {
+            neoprene_max(args, ctx)?
+        },
+        ByzNode::Floor { arg, .. } => {
+            neoprene_floor(arg, ctx)?
+        },
+        ByzNode::Ceil { arg, .. } => {
+            neoprene_ceil(arg, ctx)?
+        },
+        ByzNode::Frac { arg, .. } => {
+            neoprene_frac(arg, ctx)?
+        },
+        ByzNode::Log { base, arg, .. } => {
+            neoprene_log(base, arg, ctx)?
+        },
+        ByzNode::Variable { name } => {
+            return Err(NeopreneError::UnboundVariable(name.clone()));
+        },
+        ByzNode::RealPow { base, exp, .. } => {
+            neoprene_real_pow(base, exp, ctx)?
+        },
+        ByzNode::AlgebraicRoot { polynomial, index } => {
+            neoprene_algebraic_root(polynomial, *index, ctx)?
+        }
+    };
+
+    ctx.track(&range)?;
+
+    return Ok(range);
+}
+
+/// Evaluates a ByzNode once at the precision given by config
+pub fn neoprene_evaluate(byznode: &ByzNode, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+    let mut ctx = NeopreneContext::new(config);
+    return neoprene_byznode(byznode, &mut ctx);
+}
+
+/// Evaluates without ever failing, degraded answers come with diagnostics explaining what went wrong
+/// When the value can't be bounded at all (e.g. the memory limit was hit or a range containing zero was inverted) the range is None
+pub fn neoprene_evaluate_soft(byznode: &ByzNode, config: &NeopreneConfig) -> NeopreneSoftResult {
+    let mut ctx = NeopreneContext::new(config);
+    ctx.set_soft_fail(true);
+
+    let range = neoprene_byznode(byznode, &mut ctx);
+    let mut diagnostics = ctx.diagnostics;
+
+    match range {
+        Ok(range) => {
+            return NeopreneSoftResult { range: Some(range), diagnostics };
         },
-        ByzNode::Pow { base, exp } => {
-            return neoprene_pow(base, exp, approximation_iterations, limit_denom);
+        Err(err) => {
+            diagnostics.push(err);
+            return NeopreneSoftResult { range: None, diagnostics };
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use std::cmp::Ordering;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::thread;
+
+use crate::byzantine::{ByzNode, ByzRc};
+use crate::neoprene::NeopreneConstantCache;
+use crate::neoprene_comp::{neoprene_comp_with_constant_cache, NeopreneCompError};
+use crate::neoprene_config::NeopreneConfig;
+
+/// ByzNode is built on Rc unless the sync feature is on, and then it can't be sent to another thread
+/// Without sync each tree goes over as its byte encoding, which keeps shared subtrees shared, and each worker decodes a copy of its own
+/// With sync the trees already are Arcs, so the workers are just handed those
+#[cfg(not(feature = "sync"))]
+type SendableByzNode = Vec<u8>;
+#[cfg(feature = "sync")]
+type SendableByzNode = ByzRc<ByzNode>;
+
+#[cfg(not(feature = "sync"))]
+fn to_sendable(node: &ByzRc<ByzNode>) -> SendableByzNode {
+    return node.to_bytes();
+}
+
+#[cfg(feature = "sync")]
+fn to_sendable(node: &ByzRc<ByzNode>) -> SendableByzNode {
+    return ByzRc::clone(node);
+}
+
+#[cfg(not(feature = "sync"))]
+fn from_sendable(node: &SendableByzNode) -> ByzRc<ByzNode> {
+    return ByzRc::new(ByzNode::from_bytes(node).expect("bytes written by to_bytes always decode"));
+}
+
+#[cfg(feature = "sync")]
+fn from_sendable(node: &SendableByzNode) -> ByzRc<ByzNode> {
+    return ByzRc::clone(node);
+}
+
+/// Runs neoprene_comp_with_config on every pair using n_threads worker threads, returning the results in the same order as the pairs
+/// All workers share one constant cache, so each transitive constant is only computed once per precision across the whole batch
+pub fn neoprene_comp_batch(pairs: &[(ByzRc<ByzNode>, ByzRc<ByzNode>)], config: &NeopreneConfig, n_threads: usize) -> Vec<Result<Ordering, NeopreneCompError>> {
+    let sendable_pairs: Vec<(SendableByzNode, SendableByzNode)> = pairs.iter().map(|x| (to_sendable(&x.0), to_sendable(&x.1))).collect();
+
+    let constant_cache = NeopreneConstantCache::new();
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<(usize, Result<Ordering, NeopreneCompError>)>> = Mutex::new(Vec::with_capacity(pairs.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..n_threads.max(1) {
+            scope.spawn(|| {
+                loop {
+                    let index = next_index.fetch_add(1, AtomicOrdering::Relaxed);
+                    if index >= sendable_pairs.len() {
+                        return;
+                    }
+
+                    let a = from_sendable(&sendable_pairs[index].0);
+                    let b = from_sendable(&sendable_pairs[index].1);
+                    let result = neoprene_comp_with_constant_cache(&a, &b, config, &constant_cache);
+
+                    results.lock().unwrap().push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|x| x.0);
+
+    return results.into_iter().map(|x| x.1).collect();
+}
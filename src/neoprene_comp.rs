@@ -3,34 +3,133 @@ use std::cmp::Ordering;
 use num_bigint::BigUint;
 
 use crate::byzantine::ByzNode;
-use crate::neoprene::neoprene_byznode;
+use crate::neoprene::{neoprene_byznode, NeopreneConstantCache, NeopreneContext, NeopreneError};
+use crate::neoprene_digits::NeopreneDigitStream;
+use crate::neoprene_config::NeopreneConfig;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NeopreneCompError {
-    FailedToConverge
+    FailedToConverge,
+    Evaluation(NeopreneError)
+}
+
+impl From<NeopreneError> for NeopreneCompError {
+    fn from(err: NeopreneError) -> NeopreneCompError {
+        return NeopreneCompError::Evaluation(err);
+    }
 }
 
 pub fn neoprene_comp(a: &ByzNode, b: &ByzNode, max_iterations: &BigUint) -> Result<Ordering, NeopreneCompError> {
-    let mut current_iterations = BigUint::from(3 as u8);
-    let mut current_limit_denom = BigUint::from(6091 as u32); // 795th prime :^)
+    return neoprene_comp_with_config(a, b, &NeopreneConfig::new(max_iterations));
+}
+
+/// Starts comparing at the precision given by config, refining it until the ranges separate or config.max_iterations is passed
+pub fn neoprene_comp_with_config(a: &ByzNode, b: &ByzNode, config: &NeopreneConfig) -> Result<Ordering, NeopreneCompError> {
+    return neoprene_comp_impl(a, b, config, None);
+}
+
+/// Same as neoprene_comp_with_config, with transitive constants shared through the cache
+pub fn neoprene_comp_with_constant_cache(a: &ByzNode, b: &ByzNode, config: &NeopreneConfig, constant_cache: &NeopreneConstantCache) -> Result<Ordering, NeopreneCompError> {
+    return neoprene_comp_impl(a, b, config, Some(constant_cache));
+}
+
+fn neoprene_comp_impl(a: &ByzNode, b: &ByzNode, config: &NeopreneConfig, constant_cache: Option<&NeopreneConstantCache>) -> Result<Ordering, NeopreneCompError> {
+    let mut current_config = config.clone();
     loop {
-        let a_range = neoprene_byznode(&a, &current_iterations, &current_limit_denom);
-        let b_range = neoprene_byznode(&b, &current_iterations, &current_limit_denom);
+        let mut a_ctx = match constant_cache {
+            Some(constant_cache) => NeopreneContext::with_constant_cache(&current_config, constant_cache),
+            None => NeopreneContext::new(&current_config)
+        };
+        let a_range = neoprene_byznode(a, &mut a_ctx)?;
 
-        println!("a_range = {:?}", a_range);
-        println!("b_range = {:?}", b_range);
+        let mut b_ctx = match constant_cache {
+            Some(constant_cache) => NeopreneContext::with_constant_cache(&current_config, constant_cache),
+            None => NeopreneContext::new(&current_config)
+        };
+        let b_range = neoprene_byznode(b, &mut b_ctx)?;
 
-        if a_range.min > b_range.max {
+        if a_range.certainly_gt(&b_range) {
             return Ok(Ordering::Greater);
         }
-        if a_range.max < b_range.min {
+        if a_range.certainly_lt(&b_range) {
             return Ok(Ordering::Less);
         }
+        // Only when both are the same exact value, so Equal is never a guess
+        if a_range.certainly_eq(&b_range) {
+            return Ok(Ordering::Equal);
+        }
         
-        if &current_iterations > max_iterations {
+        if !current_config.can_refine() || current_config.below_min_width(&a_range, &b_range) {
             return Err(NeopreneCompError::FailedToConverge);
         }
 
-        current_iterations += 1 as u8;
-        current_limit_denom *= 3 as u8; // Might be better to find the next prime, but this is probably fine
+        current_config.refine();
     }
-}
\ No newline at end of file
+}
+
+/// Compares a and b by streaming the decimal digits of both and stopping at the first digit that differs
+/// Alongside the ordering this returns the position of that digit as a margin, where 0 is the integer part and 1 is the first digit after the decimal point
+/// (355/113 and pi first differ at the 7th digit, so that comparison has a margin of 7)
+pub fn neoprene_comp_digits(a: &ByzNode, b: &ByzNode, max_iterations: &BigUint) -> Result<(Ordering, usize), NeopreneCompError> {
+    return neoprene_comp_digits_with_config(a, b, &NeopreneConfig::new(max_iterations));
+}
+
+/// Returns Equal only when both are the same exact value, and gives up with FailedToConverge once config.max_iterations digits agree
+pub fn neoprene_comp_digits_with_config(a: &ByzNode, b: &ByzNode, config: &NeopreneConfig) -> Result<(Ordering, usize), NeopreneCompError> {
+    let mut a_stream = NeopreneDigitStream::with_config(a, config)?;
+    let mut b_stream = NeopreneDigitStream::with_config(b, config)?;
+
+    let a_int = a_stream.integer_part()?;
+    let b_int = b_stream.integer_part()?;
+
+    if a_int != b_int {
+        return Ok((a_int.cmp(&b_int), 0));
+    }
+
+    loop {
+        // Both are the same exact value, so the digits would agree forever
+        if a_stream.range().certainly_eq(b_stream.range()) {
+            return Ok((Ordering::Equal, a_stream.position()));
+        }
+
+        if BigUint::from(a_stream.position()) >= config.max_iterations {
+            return Err(NeopreneCompError::FailedToConverge);
+        }
+
+        let a_digit = a_stream.next_digit()?;
+        let b_digit = b_stream.next_digit()?;
+
+        if a_digit != b_digit {
+            return Ok((a_digit.cmp(&b_digit), a_stream.position()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> ByzNode {
+        return s.parse().unwrap();
+    }
+
+    #[test]
+    fn comp_digits_equal_exact_values() {
+        let max_iterations = BigUint::from(8_u8);
+
+        assert_eq!(neoprene_comp_digits(&parse("1/2"), &parse("1/2"), &max_iterations), Ok((Ordering::Equal, 0)));
+        assert_eq!(neoprene_comp_digits(&parse("1/3"), &parse("2/6"), &max_iterations).map(|(ordering, _)| ordering), Ok(Ordering::Equal));
+    }
+
+    #[test]
+    fn comp_digits_gives_up_at_max_iterations() {
+        let result = neoprene_comp_digits(&parse("sqrt(2)"), &parse("sqrt(2)"), &BigUint::from(8_u8));
+        assert_eq!(result, Err(NeopreneCompError::FailedToConverge));
+    }
+
+    #[test]
+    fn comp_digits_finds_first_differing_digit() {
+        let result = neoprene_comp_digits(&parse("1/3"), &parse("333/1000"), &BigUint::from(8_u8));
+        assert_eq!(result, Ok((Ordering::Greater, 4)));
+    }
+}
@@ -0,0 +1,111 @@
+use num_bigint::BigUint;
+
+use crate::rational::{Rational, RoundingMode};
+use crate::rational_range::RationalRange;
+
+/// How the denominators of intermediate ranges are kept from growing during evaluation
+/// Different workloads trade precision against bignum growth very differently, so this is a policy rather than a single number
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DenomLimit {
+    /// Every endpoint is forced onto this exact denominator
+    Denominator(BigUint),
+    /// Endpoints with a denominator longer than this many bits are forced onto a denominator of 2^bits, shorter ones are left alone
+    BitLength(u64),
+    /// No limiting at all, ranges stay as tight as the arithmetic allows but the bignums can grow without bound
+    Exact
+}
+
+fn limit_rational_directed(x: &mut Rational, denom: &BigUint, round_up: bool) {
+    x.to_with_denominator(denom, if round_up { RoundingMode::Ceil } else { RoundingMode::Floor });
+}
+
+impl DenomLimit {
+    /// Applies the policy to a single value, rounding towards positive infinity when round_up is true and towards negative infinity otherwise
+    pub fn apply_rational(&self, x: &mut Rational, round_up: bool) {
+        match self {
+            DenomLimit::Denominator(denom) => {
+                limit_rational_directed(x, denom, round_up);
+            },
+            DenomLimit::BitLength(bits) => {
+                if x.denom_bits() > *bits {
+                    let denom = BigUint::from(1_u8) << *bits;
+                    limit_rational_directed(x, &denom, round_up);
+                }
+            },
+            DenomLimit::Exact => ()
+        }
+    }
+
+    /// Applies the policy to a range, this is done after every interval operation in neoprene
+    /// The min is always pushed down and the max is always pushed up, so the limited range still contains the original
+    pub fn apply(&self, range: &mut RationalRange) {
+        match self {
+            DenomLimit::Denominator(denom) => {
+                range.to_with_denominator(denom);
+            },
+            _ => {
+                self.apply_rational(&mut range.min, false);
+                self.apply_rational(&mut range.max, true);
+            }
+        }
+    }
+
+    /// Loosens the limit for the next refinement step
+    pub fn refine(&mut self) {
+        match self {
+            DenomLimit::Denominator(denom) => {
+                *denom *= 3_u8; // Might be better to find the next prime, but this is probably fine
+            },
+            DenomLimit::BitLength(bits) => {
+                *bits += 2;
+            },
+            DenomLimit::Exact => ()
+        }
+    }
+}
+
+/// Everything that controls how precisely (and for how long) neoprene evaluates
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NeopreneConfig {
+    /// Controls the number of terms in series and the number of Newton steps for roots
+    pub approximation_iterations: BigUint,
+    pub denom_limit: DenomLimit,
+    /// Refinement loops like neoprene_comp give up once approximation_iterations passes this
+    pub max_iterations: BigUint,
+    /// Evaluation fails with NeopreneError::MemoryLimitExceeded once the ranges it produced add up to more than this many bits (see NeopreneContext::memory_bits)
+    pub memory_limit_bits: Option<u64>,
+    /// Refinement loops like neoprene_comp also give up once both ranges are narrower than this, as the values are then equal as far as this tolerance can tell
+    /// This stops much sooner than max_iterations for values that really are equal
+    pub min_width: Option<Rational>
+}
+
+impl NeopreneConfig {
+    /// The schedule neoprene_comp has always used, starting at 3 iterations with denominators limited to 6091
+    pub fn new(max_iterations: &BigUint) -> NeopreneConfig {
+        return NeopreneConfig {
+            approximation_iterations: BigUint::from(3_u8),
+            denom_limit: DenomLimit::Denominator(BigUint::from(6091_u32)), // 795th prime :^)
+            max_iterations: max_iterations.clone(),
+            memory_limit_bits: None,
+            min_width: None
+        };
+    }
+
+    pub fn can_refine(&self) -> bool {
+        return self.approximation_iterations <= self.max_iterations;
+    }
+
+    /// True if both ranges are narrower than min_width (never true if it's None)
+    pub fn below_min_width(&self, a: &RationalRange, b: &RationalRange) -> bool {
+        return match &self.min_width {
+            Some(min_width) => a.width() < *min_width && b.width() < *min_width,
+            None => false
+        };
+    }
+
+    /// Moves on to the next, more precise, step
+    pub fn refine(&mut self) {
+        self.approximation_iterations += 1_u8;
+        self.denom_limit.refine();
+    }
+}
@@ -0,0 +1,113 @@
+use num_bigint::BigUint;
+
+use crate::byzantine::ByzNode;
+use crate::rational::{Rational, Sign};
+use crate::rational_range::RationalRange;
+use crate::neoprene::neoprene_evaluate;
+use crate::neoprene_comp::NeopreneCompError;
+use crate::neoprene_config::NeopreneConfig;
+
+/// Streams the decimal digits of a ByzNode, refining its enclosure only when the next digit isn't determined yet
+/// The expansion is floor based, meaning x = integer_part + 0.d1 d2 d3 ... with every digit in 0..=9
+/// (So -0.25 streams as an integer part of -1 followed by the digits 7, 5, 0, 0, ...)
+pub struct NeopreneDigitStream<'a> {
+    byznode: &'a ByzNode,
+    current_config: NeopreneConfig,
+    range: RationalRange,
+    /// floor(x * 10^position), which is everything streamed so far
+    prefix: Option<Rational>,
+    position: usize
+}
+
+impl<'a> NeopreneDigitStream<'a> {
+    pub fn new(byznode: &'a ByzNode, max_iterations: &BigUint) -> Result<NeopreneDigitStream<'a>, NeopreneCompError> {
+        return NeopreneDigitStream::with_config(byznode, &NeopreneConfig::new(max_iterations));
+    }
+
+    /// Starts streaming at the precision given by config, which is refined whenever a digit can't be determined
+    pub fn with_config(byznode: &'a ByzNode, config: &NeopreneConfig) -> Result<NeopreneDigitStream<'a>, NeopreneCompError> {
+        let range = neoprene_evaluate(byznode, config)?;
+
+        return Ok(NeopreneDigitStream {
+            byznode,
+            current_config: config.clone(),
+            range,
+            prefix: None,
+            position: 0
+        });
+    }
+
+    /// The number of digits after the decimal point that have been streamed
+    pub fn position(&self) -> usize {
+        return self.position;
+    }
+
+    /// The enclosure the stream is currently working from
+    pub fn range(&self) -> &RationalRange {
+        return &self.range;
+    }
+
+    fn refine(&mut self) -> Result<(), NeopreneCompError> {
+        if !self.current_config.can_refine() {
+            return Err(NeopreneCompError::FailedToConverge);
+        }
+
+        self.current_config.refine();
+        self.range = neoprene_evaluate(self.byznode, &self.current_config)?;
+
+        return Ok(());
+    }
+
+    /// Refines until floor(x * 10^position) is the same for both ends of the enclosure
+    fn determine_prefix(&mut self, position: usize) -> Result<Rational, NeopreneCompError> {
+        let scale = Rational::new(Sign::Pos, BigUint::from(10_u8).pow(position as u32), BigUint::from(1_u8));
+
+        loop {
+            let mut min = self.range.min.clone();
+            min *= &scale;
+            let mut max = self.range.max.clone();
+            max *= &scale;
+
+            let min_floor = Rational::from(min.floor());
+            let max_floor = Rational::from(max.floor());
+
+            if min_floor == max_floor {
+                return Ok(min_floor);
+            }
+
+            self.refine()?;
+        }
+    }
+
+    /// The integer part of the value, rounded towards negative infinity
+    pub fn integer_part(&mut self) -> Result<Rational, NeopreneCompError> {
+        if self.prefix.is_none() {
+            self.prefix = Some(self.determine_prefix(0)?);
+        }
+
+        let mut a = self.prefix.clone().unwrap();
+        a /= &Rational::new(Sign::Pos, BigUint::from(10_u8).pow(self.position as u32), BigUint::from(1_u8));
+
+        return Ok(Rational::from(a.floor()));
+    }
+
+    /// The next digit after the decimal point, the integer part is determined first if it hasn't been already
+    pub fn next_digit(&mut self) -> Result<u8, NeopreneCompError> {
+        if self.prefix.is_none() {
+            self.integer_part()?;
+        }
+
+        let new_prefix = self.determine_prefix(self.position + 1)?;
+
+        let mut digit = self.prefix.clone().unwrap();
+        digit *= &Rational::from(10);
+        digit -= &new_prefix;
+        digit.negate();
+
+        self.prefix = Some(new_prefix);
+        self.position += 1;
+
+        // The difference is always an integer between 0 and 9
+        return Ok(digit.numer().to_u32_digits().first().copied().unwrap_or(0) as u8);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use num_bigint::BigUint;
+
+use crate::byzantine::ByzNode;
+use crate::rational::{Rational, Sign};
+use crate::rational_range::RationalRange;
+use crate::neoprene::{neoprene_evaluate, NeopreneError};
+use crate::neoprene_config::NeopreneConfig;
+
+/// FNV-1a, which unlike std's DefaultHasher is stable between runs and compiler versions (which matters for something stored on disk)
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    return hash;
+}
+
+/// The s-expression of the canonical form, which is lossless and doesn't depend on how Debug happens to print things
+/// So expressions that only differ in how they were built (nesting, term order, unexpanded products) share an entry
+/// Like simplify this can also match an expression that would fail to evaluate with one that doesn't, e.g. (x^(1/2))^2 with x
+fn expression_key(byznode: &ByzNode) -> String {
+    return byznode.canonicalize().to_sexpr();
+}
+
+/// Only the parts of the config that change the computed enclosure
+fn precision_key(config: &NeopreneConfig) -> String {
+    return format!("{} {:?}", config.approximation_iterations, config.denom_limit);
+}
+
+fn rational_to_line(rational: &Rational) -> String {
+    return format!("{:?} {} {}", rational.sign(), rational.numer(), rational.denom());
+}
+
+fn rational_from_line(line: &str) -> Option<Rational> {
+    let mut parts = line.split(' ');
+
+    let sign = match parts.next()? {
+        "+" => Sign::Pos,
+        "-" => Sign::Neg,
+        _ => {
+            return None;
+        }
+    };
+    let numer: BigUint = parts.next()?.parse().ok()?;
+    let denom: BigUint = parts.next()?.parse().ok()?;
+
+    if denom == BigUint::ZERO || parts.next().is_some() {
+        return None;
+    }
+
+    return Some(Rational::new(sign, numer, denom));
+}
+
+/// A directory of computed enclosures keyed by (expression, precision), so repeated batch runs can skip recomputation
+/// Every entry stores its full key as well, so hash collisions are detected and treated as misses
+pub struct NeopreneDiskCache {
+    dir: PathBuf
+}
+
+impl NeopreneDiskCache {
+    /// Creates the directory if it doesn't exist yet
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<NeopreneDiskCache> {
+        fs::create_dir_all(dir.as_ref())?;
+
+        return Ok(NeopreneDiskCache { dir: dir.as_ref().to_path_buf() });
+    }
+
+    fn entry_path(&self, expression: &str, precision: &str) -> PathBuf {
+        let hash = fnv1a_64(format!("{}\n{}", expression, precision).as_bytes());
+
+        return self.dir.join(format!("{:016x}.range", hash));
+    }
+
+    /// Unreadable or malformed entries are treated as misses
+    pub fn get(&self, byznode: &ByzNode, config: &NeopreneConfig) -> Option<RationalRange> {
+        let expression = expression_key(byznode);
+        let precision = precision_key(config);
+
+        let contents = fs::read_to_string(self.entry_path(&expression, &precision)).ok()?;
+        let mut lines = contents.lines();
+
+        if lines.next()? != expression || lines.next()? != precision {
+            // Hash collision
+            return None;
+        }
+
+        let min = rational_from_line(lines.next()?)?;
+        let max = rational_from_line(lines.next()?)?;
+
+        if min > max {
+            return None;
+        }
+
+        return Some(RationalRange::from((min, max)));
+    }
+
+    pub fn insert(&self, byznode: &ByzNode, config: &NeopreneConfig, range: &RationalRange) -> io::Result<()> {
+        let expression = expression_key(byznode);
+        let precision = precision_key(config);
+        let path = self.entry_path(&expression, &precision);
+
+        let contents = format!("{}\n{}\n{}\n{}\n", expression, precision, rational_to_line(&range.min), rational_to_line(&range.max));
+
+        // Written to the side first so a concurrent reader never sees half an entry
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, contents)?;
+        fs::rename(&temp_path, &path)?;
+
+        return Ok(());
+    }
+}
+
+/// Same as neoprene_evaluate, but looks the result up in the cache first and stores it there when it had to be computed
+/// Failing to write the entry doesn't fail the evaluation, it just means the next run computes it again
+pub fn neoprene_evaluate_cached(byznode: &ByzNode, config: &NeopreneConfig, cache: &NeopreneDiskCache) -> Result<RationalRange, NeopreneError> {
+    if let Some(range) = cache.get(byznode, config) {
+        return Ok(range);
+    }
+
+    let range = neoprene_evaluate(byznode, config)?;
+    let _ = cache.insert(byznode, config, &range);
+
+    return Ok(range);
+}
@@ -1,61 +1,76 @@
 use num_bigint::BigUint;
 use crate::rational::{Rational, Sign};
-use crate::rational_range::{RationalRange, RationalRangeDescriptor};
-
-/// Panics when given a value that cannot fit in a u32
-fn biguint_to_u32(x: &BigUint) -> u32 {
-    let a = x.to_u32_digits();
-
-    if a.len() != 1 {
-        panic!("Attempted to run biguint_to_u32(..) on a BigUint that cannot be expressed as a u32");
+use crate::rational_range::RationalRange;
+use crate::neoprene_config::NeopreneConfig;
+use crate::byzantine::TransitiveConsts;
+use crate::neoprene::NeopreneError;
+use crate::rational_polynomial::RationalPolynomial;
+
+/// The most approximation_iterations the series here can be run with
+/// Every series takes some small multiple of it in terms, and that has to fit in a u32
+pub const MAX_APPROXIMATION_ITERATIONS: u32 = u32::MAX / 16;
+
+/// config.approximation_iterations as a u32
+/// Panics when it's above MAX_APPROXIMATION_ITERATIONS, neoprene_byznode checks for that first and fails with NeopreneError::TooLarge instead
+fn iterations(config: &NeopreneConfig) -> u32 {
+    match u32::try_from(&config.approximation_iterations) {
+        Ok(k) if k <= MAX_APPROXIMATION_ITERATIONS => {
+            return k;
+        },
+        _ => {
+            panic!("Attempted to approximate with more than MAX_APPROXIMATION_ITERATIONS iterations");
+        }
     }
-
-    return a[0];
 }
 
-fn rational_range_midpoint(x: &RationalRange) -> Rational {
-    let mut a = x.min.clone();
-    a += &x.max;
+pub fn compute_transitive(transitive_const: TransitiveConsts, config: &NeopreneConfig) -> RationalRange {
+    // These are perfectly accurate and do not need to be fixed later
+    match transitive_const {
+        TransitiveConsts::Pi => {
+            return compute_pi(config);
+        },
+        TransitiveConsts::Euler => {
+            return compute_euler(config);
+        },
+        TransitiveConsts::Ln2 => {
+            return compute_ln2(config);
+        },
+        TransitiveConsts::GoldenRatio => {
+            return compute_golden_ratio(config);
+        },
+        TransitiveConsts::EulerMascheroni => {
+            return compute_euler_mascheroni(config);
+        },
+        TransitiveConsts::Catalan => {
+            return compute_catalan(config);
+        },
+        TransitiveConsts::Apery => {
+            return compute_apery(config);
+        }
+    }
+}
 
-    a.denom *= 2 as u8;
-    a.simplify();
+/// The nth term after the leading 3, +-4/(2n(2n+1)(2n+2))
+fn pi_series_term(n: u32) -> Rational {
+    let mut c = BigUint::from(2*n);
+    c *= 2*n+1;
+    c *= 2*n+2;
 
-    return a;
+    return Rational::from_parts(if n%2 == 1 { Sign::Pos } else { Sign::Neg }, BigUint::from(4 as u8), c);
 }
 
 /// Using the Gregory-Leibniz series
-pub fn compute_pi(approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    let k = biguint_to_u32(approximation_iterations) * 8;
+pub fn compute_pi(config: &NeopreneConfig) -> RationalRange {
+    let k = iterations(config) * 8;
 
     let mut a = Rational::from(3);
-    let b: Rational;
-
-    for n in 1..(k+1) {
-        let mut c = BigUint::from(2*n);
-        c *= 2*n+1;
-        c *= 2*n+2;
-        let d = Rational {
-            sign: if n%2 == 1 { Sign::Pos } else  { Sign::Neg },
-            numer: BigUint::from(4 as u8),
-            denom: c
-        };
-
-        a += &d;
-    }
+    a += &(1..(k+1)).map(pi_series_term).sum::<Rational>();
 
     // Gregory-Leibniz alternates between an underapproximation and an overapproximation,
     // So I need to use both the 2nd-to-last and the last value
-    b = a.clone();
-
-    let mut c = BigUint::from(2*k+2);
-    c *= 2*k+3;
-    c *= 2*k+4;
-    let d = Rational {
-        sign: if k%2 == 0 { Sign::Pos } else  { Sign::Neg },
-        numer: BigUint::from(4 as u8),
-        denom: c
-    };
-    a += &d;
+    let b = a.clone();
+
+    a += &pi_series_term(k+1);
 
     // Gregory-Leibniz alternates between an underapproximation and an overapproximation,
     // So which approximation is min or max depends on if k is odd or even
@@ -70,33 +85,358 @@ pub fn compute_pi(approximation_iterations: &BigUint, limit_denom: &BigUint) ->
     }
 
     let mut range = RationalRange::from((min, max));
-    range.to_with_denominator(limit_denom);
+    config.denom_limit.apply(&mut range);
     return range;
 }
 
 /// Using the taylor expansion of e^x evaluated at x=1, meaning this is just the sum of the inverses of the factorials up to k
-pub fn compute_euler(approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    let k = biguint_to_u32(approximation_iterations);
+pub fn compute_euler(config: &NeopreneConfig) -> RationalRange {
+    let k = iterations(config);
 
-    let mut min = Rational::from(2);
-
-    // Note that addition of rationals is quite expensive, and there is definitely a way I can speed this up with the knowledge of how subsequent terms will add
-    for n in 2..(k+3) {
-        let mut a = factorial(n);
-        a.invert();
-        min += &a; 
-    }
+    // Every 1/n! has a denominator that divides the next one, so the sum never needs a denominator bigger than the last factorial
+    let mut min = (2..(k+3)).map(|n| Rational::new(Sign::Pos, BigUint::from(1_u8), factorial_biguint(n))).sum::<Rational>();
+    min += &Rational::from(2);
 
     let mut max = min.clone();
     // Error term is given by e/(k+1)!
     // I use the current approximation of e as the numerator, which seems to work just fine even if it results in an underestimate on the error term
     let mut error = min.clone();
-    error.denom *= factorial_biguint(k+3);
-    error.simplify();
+    error /= &factorial_biguint(k+3);
     max += &error;
 
     let mut range = RationalRange::from((min, max));
-    range.to_with_denominator(limit_denom);
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// Terms for the series below, which all gain at least a digit every term or two
+pub(crate) fn fast_series_terms(config: &NeopreneConfig) -> u32 {
+    return 4 * iterations(config) + 4;
+}
+
+/// 2 atanh(z) = ln((1 + z)/(1 - z)) for 0 <= z <= 1/3, from the series 2(z + z^3/3 + z^5/5 + ...)
+/// Every term is positive and at most 1/9 of the one before, so the tail is at most 9/8 of the first term left out
+fn two_atanh(z: &Rational, config: &NeopreneConfig) -> RationalRange {
+    let terms = fast_series_terms(config);
+    let z_squared = z * z;
+
+    let mut power = z.clone();
+    let mut series = Vec::with_capacity(terms as usize);
+    for j in 0..terms {
+        series.push(&power / (2*j + 1));
+        power *= &z_squared;
+    }
+
+    let mut min = series.into_iter().sum::<Rational>();
+    min *= 2_u32;
+
+    let mut tail = power / (2*terms + 1);
+    tail *= &Rational::from((9, 4));
+
+    let max = &min + &tail;
+
+    return RationalRange::from((min, max));
+}
+
+/// ln(x) for a positive x, as m ln(2) + ln(y) where y = x/2^m is in [1, 2)
+/// ln(y) is then 2 atanh((y - 1)/(y + 1)) with (y - 1)/(y + 1) below 1/3, so the series converges quickly for any x
+/// Panics if x isn't positive
+pub fn rational_ln(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    if x.is_negative() || x.is_zero() {
+        panic!("Attempted to take the logarithm of a non-positive rational");
+    }
+
+    if *x < 1 {
+        // ln(x) = -ln(1/x)
+        let mut a = x.clone();
+        a.invert();
+
+        let mut range = rational_ln(&a, config);
+        range.negate();
+        return range;
+    }
+
+    // x is in (2^(m-1), 2^(m+1)) here, so at most one halving of m is needed to get y into [1, 2)
+    let mut m = x.numer_bits() - x.denom_bits();
+    let mut y = x / &(BigUint::from(1_u8) << m);
+    if y < 1 {
+        m -= 1;
+        y *= 2_u32;
+    }
+
+    let mut z = &y - 1_u32;
+    z /= &(&y + 1_u32);
+
+    let mut range = compute_ln2_exact(config);
+    range.scale(&Rational::from(BigUint::from(m)));
+    range += &two_atanh(&z, config);
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// ln over a range, which is increasing so only the endpoints are needed
+pub fn rational_range_ln(x: &RationalRange, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+    if x.min.is_negative() || x.min.is_zero() {
+        return Err(NeopreneError::NonPositiveLogarithm);
+    }
+
+    return Ok(eval_monotone(|a| rational_ln(a, config), x));
+}
+
+/// e^x for |x| <= 1/2 from its taylor series
+/// The remainder after the x^(n-1) term is at most |x|^n/n! e^|x|, and e^|x| < 2
+fn exp_series(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    let terms = fast_series_terms(config);
+
+    let mut term = Rational::one();
+    let mut series = Vec::with_capacity(terms as usize);
+    for n in 1..(terms+1) {
+        series.push(term.clone());
+        term *= x;
+        term /= n;
+    }
+
+    let mut remainder = term.abs();
+    remainder *= 2_u32;
+
+    let mut range = RationalRange::from(series.into_iter().sum::<Rational>());
+    range.widen(&remainder);
+
+    return range;
+}
+
+/// e^x, by halving x until it's within [-1/2, 1/2] and squaring the result back up
+pub fn rational_exp(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    // |x| < 2^(numer bits - denom bits + 1), so this many halvings always gets it to 1/2 or below
+    let magnitude_bits = x.numer_bits() as i64 - x.denom_bits() as i64;
+    let halvings = (magnitude_bits + 2).max(0) as u64;
+
+    let power = BigUint::from(1_u8) << halvings;
+    let r = x / &power;
+
+    let mut range = exp_series(&r, config);
+    config.denom_limit.apply(&mut range);
+
+    if halvings > 0 {
+        range = rational_range_powi(&range, &power, config);
+    }
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// exp over a range, which is increasing so only the endpoints are needed
+pub fn rational_range_exp(x: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    return eval_monotone(|a| rational_exp(a, config), x);
+}
+
+/// (e^x - e^-x)/2
+fn sinh_point(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    let mut range = rational_exp(x, config);
+    range -= &rational_exp(&-x, config);
+    range.scale(&Rational::from((1, 2)));
+    return range;
+}
+
+/// (e^x + e^-x)/2
+fn cosh_point(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    let mut range = rational_exp(x, config);
+    range += &rational_exp(&-x, config);
+    range.scale(&Rational::from((1, 2)));
+    return range;
+}
+
+/// (e^2x - 1)/(e^2x + 1), which is increasing in e^2x so the endpoints of its enclosure give the endpoints of the result
+fn tanh_point(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    let e = rational_exp(&(x * 2_u32), config);
+    let f = |a: &Rational| -> Rational {
+        return (a - 1_u32) / (a + 1_u32);
+    };
+
+    return RationalRange::from((f(&e.min), f(&e.max)));
+}
+
+/// sinh over a range, which is increasing so only the endpoints are needed
+pub fn rational_range_sinh(x: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    let mut range = eval_monotone(|a| sinh_point(a, config), x);
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// cosh over a range, which is decreasing up to 0 and increasing after it, so when the range contains 0 its minimum of 1 is included too
+pub fn rational_range_cosh(x: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    let mut range = eval_monotone(|a| cosh_point(a, config), x);
+    if x.contains_zero() {
+        range.union(&RationalRange::from(Rational::one()));
+    }
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// tanh over a range, which is increasing so only the endpoints are needed
+pub fn rational_range_tanh(x: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    let mut range = eval_monotone(|a| tanh_point(a, config), x);
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// ln(2) = 2 atanh(1/3), without the denominator limit so rational_ln can scale it first
+fn compute_ln2_exact(config: &NeopreneConfig) -> RationalRange {
+    return two_atanh(&Rational::from((1, 3)), config);
+}
+
+pub fn compute_ln2(config: &NeopreneConfig) -> RationalRange {
+    let mut range = compute_ln2_exact(config);
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// (1 + sqrt(5))/2, using the same Newton iteration as the roots in powers
+pub fn compute_golden_ratio(config: &NeopreneConfig) -> RationalRange {
+    let mut range = nth_root(&Rational::from(5), &BigUint::from(2_u8), config);
+    range += &Rational::one();
+    range.scale(&Rational::from((1, 2)));
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// B_0 up to B_n, from the recurrence sum_{j=0}^{m} C(m+1, j) B_j = 0
+fn bernoulli_numbers(n: u32) -> Vec<Rational> {
+    let mut numbers = Vec::with_capacity(n as usize + 1);
+    numbers.push(Rational::one());
+
+    // Row m+1 of Pascal's triangle
+    let mut row = vec![BigUint::from(1_u8), BigUint::from(1_u8)];
+    for m in 1..(n as usize + 1) {
+        let mut next_row = Vec::with_capacity(row.len() + 1);
+        next_row.push(BigUint::from(1_u8));
+        for pair in row.windows(2) {
+            next_row.push(&pair[0] + &pair[1]);
+        }
+        next_row.push(BigUint::from(1_u8));
+        row = next_row;
+
+        if m > 1 && m % 2 == 1 {
+            // Every odd one after B_1 is zero
+            numbers.push(Rational::zero());
+            continue;
+        }
+
+        let mut b = Rational::zero();
+        for j in 0..m {
+            if !numbers[j].is_zero() {
+                b += &(&numbers[j] * &row[j]);
+            }
+        }
+        b /= (m + 1) as u32;
+        b.negate();
+
+        numbers.push(b);
+    }
+
+    return numbers;
+}
+
+/// Using the Euler-Maclaurin expansion gamma = H_n - ln(n) - 1/(2n) + sum_{k=1}^{K} B_2k/(2k n^2k) - R
+/// For 1/x the remainder R has the sign of the first term left out and is smaller than it, so gamma is between the sums with K and K+1 terms
+/// With n = 2K the terms shrink by about 1/17^2 each, and the only series that needs many terms is the harmonic number
+pub fn compute_euler_mascheroni(config: &NeopreneConfig) -> RationalRange {
+    let k = iterations(config) + 2;
+    let n = 2*k;
+
+    let mut a = (1..(n+1)).map(|i| Rational::new(Sign::Pos, BigUint::from(1_u8), BigUint::from(i))).sum::<Rational>();
+    a -= &Rational::new(Sign::Pos, BigUint::from(1_u8), BigUint::from(2*n));
+
+    let bernoulli = bernoulli_numbers(2*k + 2);
+    let n_squared = Rational::from(BigUint::from(n) * n);
+
+    let mut power = Rational::one();
+    let mut b = a.clone();
+    for i in 1..(k+2) {
+        power *= &n_squared;
+
+        let mut term = &bernoulli[2*i as usize] / (2*i);
+        term /= &power;
+
+        if i == k + 1 {
+            b += &term;
+        } else {
+            a += &term;
+            b += &term;
+        }
+    }
+
+    let mut range = RationalRange::swap_if_needed(a, b);
+    range -= &rational_ln(&Rational::from(BigUint::from(n)), config);
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// Using Lupas' series G = 1/64 sum_{n>=1} (-1)^(n-1) 2^(8n) (40n^2 - 24n + 3) (2n)!^3 n!^2 / (n^3 (2n - 1) (4n)!^2)
+/// The terms alternate and shrink by about 1/4 each, so like compute_pi the value is between the last two partial sums
+pub fn compute_catalan(config: &NeopreneConfig) -> RationalRange {
+    let terms = fast_series_terms(config);
+
+    let mut series = Vec::with_capacity(terms as usize + 1);
+    // 2^(8n) (2n)!^3 n!^2 / (4n)!^2, which is multiplied by 32n^3 (2n - 1) / ((4n - 1)^2 (4n - 3)^2) to get from n-1 to n
+    let mut factor = Rational::one();
+    for n in 1..(terms+2) {
+        let n = BigUint::from(n);
+        let n_cubed = n.pow(3);
+        let two_n_minus_1 = &n * 2_u8 - 1_u8;
+
+        factor *= &(&n_cubed * &two_n_minus_1 * 32_u8);
+        let a = &n * 4_u8 - 1_u8;
+        let b = &n * 4_u8 - 3_u8;
+        factor /= &(&a * &a * &b * &b);
+
+        let mut term = &factor * &(&n * &n * 40_u8 + 3_u8 - &n * 24_u8);
+        term /= &(n_cubed * two_n_minus_1);
+        if !n.bit(0) {
+            term.negate();
+        }
+
+        series.push(term);
+    }
+
+    let last = series.pop().unwrap();
+    let a = series.into_iter().sum::<Rational>();
+    let b = &a + &last;
+
+    let mut range = RationalRange::swap_if_needed(a, b);
+    range.scale(&Rational::from((1, 64)));
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// Using zeta(3) = 5/2 sum_{k>=1} (-1)^(k+1)/(k^3 C(2k, k))
+/// The terms alternate and shrink, so like compute_pi the value is between the last two partial sums
+pub fn compute_apery(config: &NeopreneConfig) -> RationalRange {
+    let terms = fast_series_terms(config);
+
+    let mut series = Vec::with_capacity(terms as usize + 1);
+    let mut central_binomial = BigUint::from(1_u8);
+    for k in 1..(terms+2) {
+        central_binomial *= 2*(2*k - 1);
+        central_binomial /= k;
+
+        let cube = BigUint::from(k).pow(3);
+        series.push(Rational::new(if k%2 == 1 { Sign::Pos } else { Sign::Neg }, BigUint::from(1_u8), cube * &central_binomial));
+    }
+
+    let last = series.pop().unwrap();
+    let a = series.into_iter().sum::<Rational>();
+    let b = &a + &last;
+
+    let mut range = RationalRange::swap_if_needed(a, b);
+    range.scale(&Rational::from((5, 2)));
+
+    config.denom_limit.apply(&mut range);
     return range;
 }
 
@@ -115,55 +455,84 @@ pub fn factorial_biguint(x: u32) -> BigUint {
 }
 
 pub fn factorial(x: u32) -> Rational {
-    return Rational { 
-        sign: Sign::Pos, 
-        numer: factorial_biguint(x), 
-        denom:  BigUint::from(1 as u8)
-    };
+    return Rational::from(factorial_biguint(x));
 }
 
 /// Initial coarse bounds for nth root of rational by just applying nth roots to the numerator and denominator individually
+/// Panics if root doesn't fit in a u32, rational_range_pow checks for that first
 fn initial_root_bounds(base: &Rational, root: &BigUint) -> RationalRange {
-    let root = biguint_to_u32(root);
+    let root = u32::try_from(root).expect("Attempted to take a root that doesn't fit in a u32");
 
-    let numer_root = base.numer.nth_root(root);
-    let denom_root = base.denom.nth_root(root);
+    let numer_root = base.numer().nth_root(root);
+    let denom_root = base.denom().nth_root(root);
 
-    let min_root = Rational {
-        sign: Sign::Pos,
-        numer: numer_root.clone(),
-        denom: denom_root.clone() + 1 as u8
-    };
-    let max_root = Rational {
-        sign: Sign::Pos,
-        numer: numer_root + 1 as u8, 
-        denom: denom_root
-    };
+    let min_root = Rational::from_parts(Sign::Pos, numer_root.clone(), denom_root.clone() + 1 as u8);
+    let max_root = Rational::from_parts(Sign::Pos, numer_root + 1 as u8, denom_root);
 
     return RationalRange::from((min_root, max_root));
 }
 
+/// Evaluates a function that is known to be monotone (in either direction) over the interval
+/// Only the endpoints are evaluated, f should return an enclosure of its value at the given point
+pub fn eval_monotone<F>(f: F, interval: &RationalRange) -> RationalRange where F: Fn(&Rational) -> RationalRange {
+    let mut range = f(&interval.min);
+
+    if interval.min != interval.max {
+        // Whichever direction f goes in, the union of both endpoint enclosures contains f over the whole interval
+        range.union(&f(&interval.max));
+    }
+
+    return range;
+}
+
+/// eval_monotone for functions that can fail, the first error is passed through
+pub fn try_eval_monotone<F, E>(f: F, interval: &RationalRange) -> Result<RationalRange, E> where F: Fn(&Rational) -> Result<RationalRange, E> {
+    let mut range = f(&interval.min)?;
+
+    if interval.min != interval.max {
+        range.union(&f(&interval.max)?);
+    }
+
+    return Ok(range);
+}
+
 /// Using Newton's method of computing principal roots
 /// The function we're solving is 0 = (output)^(root) - base
-fn nth_root(base: &Rational, root: &BigUint, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
+pub(crate) fn nth_root(base: &Rational, root: &BigUint, config: &NeopreneConfig) -> RationalRange {
+    if base.is_zero() {
+        return RationalRange::from(Rational::zero());
+    }
+    if base.is_negative() {
+        // Only reachable with odd roots, where the root of -x is -(root of x)
+        let mut a = base.clone();
+        a.negate();
+
+        let mut range = nth_root(&a, root, config);
+        range.min.negate();
+        range.max.negate();
+        std::mem::swap(&mut range.min, &mut range.max);
+
+        return range;
+    }
+
     let mut current_guess = initial_root_bounds(base, root);
 
-    let k = biguint_to_u32(approximation_iterations);
+    let k = iterations(config);
 
     for _ in 0..k {
-        let midpoint = rational_range_midpoint(&current_guess);
+        let midpoint = current_guess.midpoint();
 
         let mut f_midpoint = midpoint.clone();
         f_midpoint.powi(root);
         f_midpoint -= base;
         
-        let mut derivative_interval = current_guess;
+        let mut derivative_interval = current_guess.clone();
         let q = root - 1 as u8;
         derivative_interval.min.powi(&q);
-        derivative_interval.min.numer *= root;
+        derivative_interval.min *= root;
         derivative_interval.min.simplify();
         derivative_interval.max.powi(&q);
-        derivative_interval.max.numer *= root;
+        derivative_interval.max *= root;
         derivative_interval.max.simplify();
 
         let f_midpoint_div_derivative_interval: RationalRange;
@@ -183,62 +552,171 @@ fn nth_root(base: &Rational, root: &BigUint, approximation_iterations: &BigUint,
         let mut new_guess_max = midpoint;
         new_guess_max -= &f_midpoint_div_derivative_interval.min;
 
-        current_guess = RationalRange::from((new_guess_min, new_guess_max));
+        // The Newton step alone can overshoot far past zero when the guess is still wide (which happens a lot with larger roots),
+        // And the derivative bounds above are only valid for positive guesses, so the step is intersected with the previous guess
+        // (Both contain the root, so the intersection does too)
+        current_guess = RationalRange::from((
+            new_guess_min.max(current_guess.min), 
+            new_guess_max.min(current_guess.max)
+        ));
 
-        current_guess.to_with_denominator(limit_denom);
+        config.denom_limit.apply(&mut current_guess);
     }
     
     return current_guess;
 }
 
-fn nth_root_range(base: &RationalRange, root: &BigUint, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    let base_min_range = nth_root(&base.min, root, approximation_iterations, limit_denom);
-    let base_max_range = nth_root(&base.min, root, approximation_iterations, limit_denom);
+fn nth_root_range(base: &RationalRange, root: &BigUint, config: &NeopreneConfig) -> RationalRange {
+    return eval_monotone(|x| nth_root(x, root, config), base);
+}
 
-    if base_min_range.min > base_max_range.max {
-        return RationalRange::from((base_min_range.max, base_max_range.min));
-    } else {
-        return RationalRange::from((base_min_range.min, base_max_range.max));
+/// x^exp for a non-negative x by repeated squaring, with the denominator limited after every multiplication so the bignums stay small
+/// Since every step is increasing in x, rounding every step in the same direction gives a lower (or with round_up, upper) bound on the exact power
+fn rational_powi_directed(x: &Rational, exp: &BigUint, round_up: bool, config: &NeopreneConfig) -> Rational {
+    let mut result = Rational::one();
+    let mut square = x.clone();
+
+    for i in 0..exp.bits() {
+        if exp.bit(i) {
+            result *= &square;
+            config.denom_limit.apply_rational(&mut result, round_up);
+        }
+
+        if i + 1 < exp.bits() {
+            let a = square.clone();
+            square *= &a;
+            config.denom_limit.apply_rational(&mut square, round_up);
+        }
     }
+
+    return result;
 }
 
-pub fn rational_range_pow(base: &RationalRange, exp: &Rational, approximation_iterations: &BigUint, limit_denom: &BigUint) -> RationalRange {
-    if !exp.is_simplified() {
-        panic!("Attempted to compute rational_range_pow(..) with an unsimplified exp");
-    }
+/// Integer power of a range of any size exponent, with the denominators limited along the way (see RationalRange::powi for the exact version)
+pub fn rational_range_powi(base: &RationalRange, exp: &BigUint, config: &NeopreneConfig) -> RationalRange {
+    return base.powi_by(exp, |x, round_up| rational_powi_directed(x, exp, round_up, config));
+}
+
+pub fn rational_range_pow(base: &RationalRange, exp: &Rational, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
     if exp.is_zero() {
-        panic!("Attempted to compute rational_range_pow(..) with an exp of 0");
+        return Ok(RationalRange::from(Rational::one()));
     }
     if exp.is_one() {
-        return base.clone();
-    }
-    if exp > &Rational::from(8) {
-        panic!("Attempted to compute rational_range_pow(..) with an exp larger than 8")
+        return Ok(base.clone());
     }
 
-    if (base.min.is_negative() || base.max.is_negative()) && exp.is_denom_odd() {
+    let mut exp = exp.clone();
+    exp.simplify();
+
+    if (base.min.is_negative() || base.max.is_negative()) && !exp.is_denom_odd() {
         // If base is negative, exp.denom must be odd to have a real root
-        panic!("Attempted to compute rational_range_pow(..) in such a way that a complex number would be produced");
+        return Err(NeopreneError::ComplexResult);
     }
 
-    let mut a = base.clone();
-    a.min.powi(&exp.numer);
-    a.max.powi(&exp.numer);
+    if u32::try_from(&*exp.denom()).is_err() {
+        return Err(NeopreneError::TooLarge);
+    }
 
-    let mut pow_range = nth_root_range(&a, &exp.denom, approximation_iterations, limit_denom);
-    match a.descriptor() {
-        RationalRangeDescriptor::OverlapZero => {
-            if !pow_range.min.is_negative() && !pow_range.max.is_negative() {
-                // Consider (-1..2)^2, which can range between (0..4)
-                pow_range.min = Rational::zero();
-            }
-        },
-        _ => ()
+    let mut pow_range = rational_range_powi(base, &exp.numer(), config);
+    if !exp.is_int_assume_simplified() {
+        pow_range = nth_root_range(&pow_range, &exp.denom(), config);
     }
 
     if exp.is_negative() {
+        let zero = Rational::zero();
+        if pow_range.min <= zero && pow_range.max >= zero {
+            return Err(NeopreneError::ZeroReciprocal);
+        }
+
         pow_range.reciprocate();
     }
 
-    return pow_range;
-}
\ No newline at end of file
+    return Ok(pow_range);
+}
+
+/// The index-th smallest real root of polynomial, bisected down to a width of 2^-(8 * approximation_iterations)
+pub fn algebraic_root(polynomial: &RationalPolynomial, index: usize, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+    let bits = 8 * iterations(config);
+    let max_width = Rational::new(Sign::Pos, BigUint::from(1_u8), BigUint::from(1_u8) << bits);
+
+    return polynomial.real_root(index, &max_width).ok_or(NeopreneError::MissingRoot);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn endpoints(range: &RationalRange) -> (Rational, Rational) {
+        return (range.min.clone(), range.max.clone());
+    }
+
+    fn config() -> NeopreneConfig {
+        return NeopreneConfig::new(&BigUint::from(8_u8));
+    }
+
+    #[test]
+    fn eval_monotone_increasing() {
+        let range = eval_monotone(|x| RationalRange::from(x * x), &RationalRange::from((1, 3)));
+        assert_eq!(endpoints(&range), (Rational::from(1), Rational::from(9)));
+    }
+
+    #[test]
+    fn eval_monotone_decreasing() {
+        let range = eval_monotone(|x| RationalRange::from(Rational::one() / x), &RationalRange::from((1, 4)));
+        assert_eq!(endpoints(&range), (Rational::from((1, 4)), Rational::one()));
+    }
+
+    #[test]
+    fn eval_monotone_unions_endpoint_enclosures() {
+        // Each point comes back as [x - 1, x + 1], so the result has to reach past both endpoints
+        let range = eval_monotone(|x| RationalRange::from((x - 1_u32, x + 1_u32)), &RationalRange::from((2, 5)));
+        assert_eq!(endpoints(&range), (Rational::from(1), Rational::from(6)));
+    }
+
+    #[test]
+    fn eval_monotone_point_interval() {
+        let calls = Cell::new(0);
+        let range = eval_monotone(|x| {
+            calls.set(calls.get() + 1);
+            return RationalRange::from(x * x);
+        }, &RationalRange::from((3, 3)));
+
+        assert_eq!(endpoints(&range), (Rational::from(9), Rational::from(9)));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn try_eval_monotone_passes_errors_through() {
+        let result = try_eval_monotone(|x| if x.is_negative() { Err(NeopreneError::ComplexResult) } else { Ok(RationalRange::from(x)) }, &RationalRange::from((-1, 1)));
+        assert_eq!(result.err(), Some(NeopreneError::ComplexResult));
+
+        let result = try_eval_monotone(|x| if x.is_negative() { Err(NeopreneError::ComplexResult) } else { Ok(RationalRange::from(x)) }, &RationalRange::from((0, 1)));
+        assert_eq!(endpoints(&result.unwrap()), (Rational::zero(), Rational::one()));
+    }
+
+    #[test]
+    fn nth_root_range_uses_both_endpoints() {
+        // This used to take the root of base.min for both ends, which gave about [2, 2] here
+        let range = nth_root_range(&RationalRange::from((4, 9)), &BigUint::from(2_u8), &config());
+        assert!(range.min <= 2 && range.max >= 3);
+        assert!(range.max < Rational::from((31, 10)));
+    }
+
+    #[test]
+    fn nth_root_range_odd_root_of_negative_range() {
+        let range = nth_root_range(&RationalRange::from((-27, -8)), &BigUint::from(3_u8), &config());
+        assert!(range.min <= -3 && range.max >= -2);
+        assert!(range.min > Rational::from((-31, 10)) && range.max < Rational::from((-19, 10)));
+
+        let range = nth_root_range(&RationalRange::from((-8, 27)), &BigUint::from(3_u8), &config());
+        assert!(range.min <= -2 && range.max >= 3);
+    }
+
+    #[test]
+    fn nth_root_range_point() {
+        let range = nth_root_range(&RationalRange::from((2, 2)), &BigUint::from(2_u8), &config());
+        assert!(range.contains(&Rational::from((141421, 100000))) && range.width() < Rational::from((1, 1000)));
+    }
+}
@@ -0,0 +1,306 @@
+use num_bigint::{BigInt, BigUint};
+
+use crate::rational::Rational;
+use crate::rational_range::RationalRange;
+use crate::neoprene_config::NeopreneConfig;
+use crate::neoprene_taylor::{compute_pi, factorial, rational_range_powi, eval_monotone, try_eval_monotone, fast_series_terms, nth_root};
+use crate::neoprene::NeopreneError;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Trig {
+    Sin,
+    Cos
+}
+
+impl Trig {
+    /// The power of the first term of the taylor series
+    fn first_power(self) -> u32 {
+        match self {
+            Trig::Sin => {
+                return 1;
+            },
+            Trig::Cos => {
+                return 0;
+            }
+        }
+    }
+
+    /// The extremes are at (k + offset) * pi, where the value is (-1)^k
+    fn extreme_offset(self) -> Rational {
+        match self {
+            Trig::Sin => {
+                return Rational::from((1, 2));
+            },
+            Trig::Cos => {
+                return Rational::zero();
+            }
+        }
+    }
+}
+
+/// Grows with approximation_iterations like the other series, 3 iterations is 16 terms which is plenty for |x| <= pi
+fn series_terms(config: &NeopreneConfig) -> u32 {
+    let iterations = u32::try_from(&config.approximation_iterations).unwrap_or(1000).min(1000);
+    return 4 * iterations + 4;
+}
+
+/// x - 2k*pi for the k that brings x closest to zero, as a range since pi is only known as one
+/// Values already within [-pi, pi] are left alone, so they don't pick up any of the uncertainty in pi
+fn reduce_argument(x: &Rational, pi: &RationalRange) -> RationalRange {
+    if x.abs() <= pi.min {
+        return RationalRange::from(x);
+    }
+
+    let two_pi = pi.midpoint() * 2_u32;
+    let k = (x / &two_pi).round();
+
+    let mut shift = pi.clone();
+    shift.scale(&(Rational::from(k) * 2_u32));
+
+    let mut r = RationalRange::from(x);
+    r -= &shift;
+
+    return r;
+}
+
+/// The taylor series evaluated with range arithmetic, plus the Lagrange bound on the remainder
+/// Meant for the narrow ranges reduce_argument produces, as every power is enclosed separately
+fn trig_series(r: &RationalRange, trig: Trig, config: &NeopreneConfig) -> RationalRange {
+    let terms = series_terms(config);
+    let first_power = trig.first_power();
+
+    let mut sum = RationalRange::from(Rational::zero());
+    for n in 0..terms {
+        let power = 2*n + first_power;
+
+        let mut coefficient = factorial(power);
+        coefficient.invert();
+        if n % 2 == 1 {
+            coefficient.negate();
+        }
+
+        let mut term = rational_range_powi(r, &BigUint::from(power), config);
+        term.scale(&coefficient);
+        sum += &term;
+    }
+
+    // The next term bounds the remainder, |R| <= max|r|^p / p!
+    let next_power = 2*terms + first_power;
+    let mut remainder = r.abs().max;
+    remainder.powi(&BigUint::from(next_power));
+    remainder /= &factorial(next_power);
+
+    sum.widen(&remainder);
+
+    return sum;
+}
+
+fn trig_point(x: &Rational, pi: &RationalRange, trig: Trig, config: &NeopreneConfig) -> RationalRange {
+    return trig_series(&reduce_argument(x, pi), trig, config);
+}
+
+/// Sin and cos are monotone between their extremes, so the range is the union of the values at both endpoints
+/// And the extremes (+1 or -1) that might lie inside x
+fn rational_range_trig(x: &RationalRange, pi: &RationalRange, trig: Trig, config: &NeopreneConfig) -> RationalRange {
+    let full_period = RationalRange::from((Rational::from(-1), Rational::from(1)));
+
+    if x.width() >= &pi.min * 2_u32 {
+        return full_period;
+    }
+
+    let mut range = trig_point(&x.min, pi, trig, config);
+    if !x.is_point() {
+        range.union(&trig_point(&x.max, pi, trig, config));
+    }
+
+    // Bounds on x / pi, to find which extremes might be inside x
+    let offset = trig.extreme_offset();
+    let quotient_min = (&x.min / &pi.min).min(&x.min / &pi.max);
+    let quotient_max = (&x.max / &pi.min).max(&x.max / &pi.max);
+
+    let mut k = (&quotient_min - &offset).floor();
+    let k_max = (&quotient_max - &offset).ceil();
+
+    if &k_max - &k > BigInt::from(8) {
+        // Only happens for huge x where pi isn't known well enough to tell where the extremes are
+        return full_period;
+    }
+
+    while k <= k_max {
+        let mut extreme = pi.clone();
+        extreme.scale(&(Rational::from(k.clone()) + &offset));
+
+        if extreme.possibly_eq(x) {
+            if k.bit(0) {
+                range.min = Rational::from(-1);
+            } else {
+                range.max = Rational::from(1);
+            }
+        }
+
+        k += 1;
+    }
+
+    if range.min < -1 {
+        range.min = Rational::from(-1);
+    }
+    if range.max > 1 {
+        range.max = Rational::from(1);
+    }
+
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// An enclosure of sin over x, with pi passed in so callers with a cached enclosure of it can reuse that
+pub fn rational_range_sin(x: &RationalRange, pi: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    return rational_range_trig(x, pi, Trig::Sin, config);
+}
+
+/// An enclosure of cos over x, see rational_range_sin
+pub fn rational_range_cos(x: &RationalRange, pi: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    return rational_range_trig(x, pi, Trig::Cos, config);
+}
+
+/// An enclosure of tan over x, failing if x may contain a pole (a zero of cos)
+/// Between poles tan is increasing, so it's taken as sin/cos at both endpoints
+pub fn rational_range_tan(x: &RationalRange, pi: &RationalRange, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+    if rational_range_cos(x, pi, config).contains_zero() {
+        return Err(NeopreneError::ZeroReciprocal);
+    }
+
+    let mut range = try_eval_monotone(|a| {
+        let a = RationalRange::from(a);
+        return rational_range_sin(&a, pi, config).try_div(&rational_range_cos(&a, pi, config)).map_err(|_| NeopreneError::ZeroReciprocal);
+    }, x)?;
+
+    config.denom_limit.apply(&mut range);
+    return Ok(range);
+}
+
+/// arctan(x) for 0 <= x <= 5/12, from Euler's series sum_{n>=0} 2^(2n) n!^2/(2n+1)! x^(2n+1)/(1 + x^2)^(n+1)
+/// Each term is the last times y (2n+2)/(2n+3) where y = x^2/(1 + x^2) < 1/6, so the tail is at most twice the first term left out
+fn arctan_series(x: &Rational, config: &NeopreneConfig) -> RationalRange {
+    let terms = fast_series_terms(config);
+
+    let one_plus_x_squared = x * x + 1_u32;
+    let y = x * x / &one_plus_x_squared;
+
+    let mut term = x / &one_plus_x_squared;
+    let mut series = Vec::with_capacity(terms as usize);
+    for n in 0..terms {
+        series.push(term.clone());
+        term *= &y;
+        term *= 2*n + 2;
+        term /= 2*n + 3;
+    }
+
+    let min = series.into_iter().sum::<Rational>();
+    let max = &min + &(term * 2_u32);
+
+    return RationalRange::from((min, max));
+}
+
+/// Reduces x into [0, 5/12] using arctan(-x) = -arctan(x), arctan(x) = pi/2 - arctan(1/x) and arctan(x) = pi/4 - arctan((1 - x)/(1 + x))
+/// (The last one takes (5/12, 1] to [0, 7/17))
+fn arctan_point(x: &Rational, pi: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    if x.is_negative() {
+        let mut range = arctan_point(&-x, pi, config);
+        range.negate();
+        return range;
+    }
+
+    if *x > 1 {
+        let mut a = x.clone();
+        a.invert();
+
+        let mut range = pi.clone();
+        range.scale(&Rational::from((1, 2)));
+        range -= &arctan_point(&a, pi, config);
+        return range;
+    }
+
+    if *x > Rational::from((5, 12)) {
+        let a = (Rational::one() - x) / (x + 1_u32);
+
+        let mut range = pi.clone();
+        range.scale(&Rational::from((1, 4)));
+        range -= &arctan_series(&a, config);
+        return range;
+    }
+
+    return arctan_series(x, config);
+}
+
+/// An enclosure of arctan over x, which is increasing so only the endpoints are needed
+pub fn rational_range_arctan(x: &RationalRange, pi: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    let mut range = eval_monotone(|a| arctan_point(a, pi, config), x);
+    config.denom_limit.apply(&mut range);
+    return range;
+}
+
+/// arcsin(x) = 2 arctan(x/(1 + sqrt(1 - x^2))), where the argument of arctan stays within [-1, 1] even as x gets close to +-1
+fn arcsin_point(x: &Rational, pi: &RationalRange, config: &NeopreneConfig) -> RationalRange {
+    let mut denom = nth_root(&(Rational::one() - x * x), &BigUint::from(2_u8), config);
+    denom += &Rational::one();
+
+    let mut arg = RationalRange::from(x);
+    arg /= &denom;
+
+    let mut range = rational_range_arctan(&arg, pi, config);
+    range.scale(&Rational::from(2));
+    return range;
+}
+
+/// An enclosure of arcsin over x, failing if x may reach outside [-1, 1]
+pub fn rational_range_arcsin(x: &RationalRange, pi: &RationalRange, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+    if x.min < -1 || x.max > 1 {
+        return Err(NeopreneError::OutsideDomain);
+    }
+
+    let mut range = eval_monotone(|a| arcsin_point(a, pi, config), x);
+    config.denom_limit.apply(&mut range);
+    return Ok(range);
+}
+
+/// An enclosure of arccos over x as pi/2 - arcsin(x), failing if x may reach outside [-1, 1]
+pub fn rational_range_arccos(x: &RationalRange, pi: &RationalRange, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+    let mut range = pi.clone();
+    range.scale(&Rational::from((1, 2)));
+    range -= &rational_range_arcsin(x, pi, config)?;
+
+    config.denom_limit.apply(&mut range);
+    return Ok(range);
+}
+
+impl RationalRange {
+    /// An enclosure of sin over this range, computing pi at the precision of config
+    pub fn sin(&self, config: &NeopreneConfig) -> RationalRange {
+        return rational_range_sin(self, &compute_pi(config), config);
+    }
+
+    /// An enclosure of cos over this range, computing pi at the precision of config
+    pub fn cos(&self, config: &NeopreneConfig) -> RationalRange {
+        return rational_range_cos(self, &compute_pi(config), config);
+    }
+
+    /// An enclosure of tan over this range, computing pi at the precision of config
+    pub fn tan(&self, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+        return rational_range_tan(self, &compute_pi(config), config);
+    }
+
+    /// An enclosure of arctan over this range, computing pi at the precision of config
+    pub fn arctan(&self, config: &NeopreneConfig) -> RationalRange {
+        return rational_range_arctan(self, &compute_pi(config), config);
+    }
+
+    /// An enclosure of arcsin over this range, computing pi at the precision of config
+    pub fn arcsin(&self, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+        return rational_range_arcsin(self, &compute_pi(config), config);
+    }
+
+    /// An enclosure of arccos over this range, computing pi at the precision of config
+    pub fn arccos(&self, config: &NeopreneConfig) -> Result<RationalRange, NeopreneError> {
+        return rational_range_arccos(self, &compute_pi(config), config);
+    }
+}
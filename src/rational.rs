@@ -1,9 +1,79 @@
-use std::fmt::{Debug, Formatter};
-use std::ops::{AddAssign, SubAssign, MulAssign, DivAssign, Not, BitXor};
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign, Not, BitXor};
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::iter::{Sum, Product};
 
-use num_bigint::BigUint;
+use num_bigint::{BigInt, BigUint};
 
+/// Binary (Stein) gcd on machine words, which only needs shifts and subtractions
+fn binary_gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    if a == 0 {
+        return b;
+    }
+    if b == 0 {
+        return a;
+    }
+
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+
+    loop {
+        b >>= b.trailing_zeros();
+
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+/// Lehmer's step, which runs Euclid on the leading 64 bits of a and b (a >= b) for as long as that gives the same quotients the full numbers would
+/// Returns the cofactors (x_a, x_b, y_a, y_b) such that the next pair is (x_a*a + x_b*b, y_a*a + y_b*b), or None if not even one quotient could be found that way
+fn lehmer_cofactors(a: &BigUint, b: &BigUint) -> Option<(i128, i128, i128, i128)> {
+    let shift = a.bits() - 64;
+    let mut a_high = u64::try_from(a >> shift).unwrap() as i128;
+    let mut b_high = u64::try_from(b >> shift).unwrap() as i128;
+
+    let (mut x_a, mut x_b, mut y_a, mut y_b) = (1_i128, 0_i128, 0_i128, 1_i128);
+
+    // Both quotients bound the true one (Knuth's Algorithm L), so while they agree it's the right one
+    while b_high + y_a > 0 && b_high + y_b > 0 {
+        let q = (a_high + x_a).div_euclid(b_high + y_a);
+        if q != (a_high + x_b).div_euclid(b_high + y_b) {
+            break;
+        }
+
+        (x_a, y_a) = (y_a, x_a - q * y_a);
+        (x_b, y_b) = (y_b, x_b - q * y_b);
+        (a_high, b_high) = (b_high, a_high - q * b_high);
+    }
+
+    if x_b == 0 {
+        return None;
+    }
+
+    return Some((x_a, x_b, y_a, y_b));
+}
+
+/// x*a + y*b where x and y have opposite signs (or one is 0) and the result is known to be non negative
+fn combine(x: i128, a: &BigUint, y: i128, b: &BigUint) -> BigUint {
+    if y <= 0 {
+        return a * x.unsigned_abs() - b * y.unsigned_abs();
+    }
+
+    return b * y.unsigned_abs() - a * x.unsigned_abs();
+}
+
+/// Lehmer's gcd while the numbers are big, where each round does the work of dozens of Euclid steps with a few multiplications by machine words
+/// (Falling back to a single Euclid step when the leading bits can't decide the quotient, i.e. when it's huge)
+/// And then binary gcd once they fit in a u64, which avoids allocating a new BigUint for every remaining step
+/// See benches/gcd.rs for the comparison against plain Euclid
 pub fn gcd(a_r: &BigUint,  b_r: &BigUint) -> BigUint {
     if *a_r==*b_r {
         return a_r.clone();
@@ -17,10 +87,24 @@ pub fn gcd(a_r: &BigUint,  b_r: &BigUint) -> BigUint {
     }
 
     while b > BigUint::ZERO {
-        let mut temp = a;
-        temp %= &b;
-        a = b;
-        b = temp;
+        if let Ok(b_small) = u64::try_from(&b) {
+            a %= &b;
+            let a_small = u64::try_from(&a).unwrap();
+
+            return BigUint::from(binary_gcd_u64(b_small, a_small));
+        }
+
+        match lehmer_cofactors(&a, &b) {
+            Some((x_a, x_b, y_a, y_b)) => {
+                (a, b) = (combine(x_a, &a, x_b, &b), combine(y_a, &a, y_b, &b));
+            },
+            None => {
+                let mut temp = a;
+                temp %= &b;
+                a = b;
+                b = temp;
+            }
+        }
     }
 
     return a;
@@ -38,7 +122,8 @@ pub fn lcm(a_r: &BigUint, b_r: &BigUint) -> BigUint {
     return b;
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Sign {
     Pos,
     Neg
@@ -81,167 +166,875 @@ impl Debug for Sign {
     }
 }
 
-#[derive(Clone)]
+/// Which way to go when a value has to be rounded onto a coarser grid (fewer digits, a smaller denominator, an integer, ...)
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RoundingMode {
+    /// Towards negative infinity
+    Floor,
+    /// Towards positive infinity
+    Ceil,
+    TowardZero,
+    AwayFromZero,
+    /// To the nearest value, with ties going away from zero
+    HalfAwayFromZero,
+    /// To the nearest value, with ties going to the even one
+    HalfEven
+}
+
+impl RoundingMode {
+    /// Rounds numer / denom to an integer, where negative says whether the value being rounded is actually -(numer / denom)
+    /// Returns the magnitude of the rounded value, the sign stays the caller's business
+    pub(crate) fn round_quotient(self, numer: &BigUint, denom: &BigUint, negative: bool) -> BigUint {
+        return self.round_quotient_exact(numer, denom, negative).0;
+    }
+
+    /// Same as round_quotient, but also says whether any rounding happened (false when denom divides numer)
+    pub(crate) fn round_quotient_exact(self, numer: &BigUint, denom: &BigUint, negative: bool) -> (BigUint, bool) {
+        let mut quotient = numer / denom;
+        let remainder = numer % denom;
+
+        if remainder == BigUint::ZERO {
+            return (quotient, false);
+        }
+
+        let round_magnitude_up = match self {
+            RoundingMode::Floor => negative,
+            RoundingMode::Ceil => !negative,
+            RoundingMode::TowardZero => false,
+            RoundingMode::AwayFromZero => true,
+            RoundingMode::HalfAwayFromZero => {
+                let twice_remainder = remainder << 1_u8;
+                twice_remainder >= *denom
+            },
+            RoundingMode::HalfEven => {
+                let twice_remainder = remainder << 1_u8;
+                match twice_remainder.cmp(denom) {
+                    Ordering::Less => false,
+                    Ordering::Greater => true,
+                    Ordering::Equal => quotient.bit(0)
+                }
+            }
+        };
+
+        if round_magnitude_up {
+            quotient += 1_u8;
+        }
+
+        return (quotient, true);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RationalError {
+    /// Tried to make a Rational out of a NaN or infinite float
+    NotFinite,
+    /// E.g. zero raised to a negative power
+    DivisionByZero,
+    ZeroDenominator,
+    /// Tried to make a RationalRange whose min is above its max
+    MinAboveMax
+}
+
+impl Display for RationalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            RationalError::NotFinite => {
+                return write!(f, "value is not finite");
+            },
+            RationalError::DivisionByZero => {
+                return write!(f, "division by zero");
+            },
+            RationalError::ZeroDenominator => {
+                return write!(f, "rational has a zero denominator");
+            },
+            RationalError::MinAboveMax => {
+                return write!(f, "range min is above its max");
+            }
+        }
+    }
+}
+
+impl std::error::Error for RationalError {}
+
+/// How a Rational is stored, anything whose numerator and denominator fit in machine words is Small and never allocates
+/// Most values in real expressions are tiny (1, 2, -1/2, ...), so arithmetic on them stays on machine integers and only falls back to BigUint on overflow
+enum Repr {
+    /// numerator / denominator, with the sign on the numerator (which is never i64::MIN, so negating can't overflow)
+    Small(i64, u64),
+    /// Only used when the numerator or denominator doesn't fit in a Small
+    Big(Sign, BigUint, BigUint)
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::rational_serde::RationalRepr", into = "crate::rational_serde::RationalRepr"))]
+/// The representation is private, so outside code can't build a zero denominator
+/// Anything built through new (or the From impls) has zero as Sign::Pos, which is what Eq, Ord and Hash expect
 pub struct Rational {
-    pub sign: Sign,
-    pub numer: BigUint,
-    pub denom: BigUint
+    repr: Repr
+}
+
+/// clone_from reuses the BigUint buffers when both sides are stored big, which lets range arithmetic overwrite an endpoint without allocating
+impl Clone for Repr {
+    fn clone(&self) -> Repr {
+        match self {
+            Repr::Small(numer, denom) => {
+                return Repr::Small(*numer, *denom);
+            },
+            Repr::Big(sign, numer, denom) => {
+                return Repr::Big(*sign, numer.clone(), denom.clone());
+            }
+        }
+    }
+
+    fn clone_from(&mut self, source: &Repr) {
+        if let (Repr::Big(sign, numer, denom), Repr::Big(source_sign, source_numer, source_denom)) = (&mut *self, source) {
+            *sign = *source_sign;
+            numer.clone_from(source_numer);
+            denom.clone_from(source_denom);
+            return;
+        }
+
+        *self = source.clone();
+    }
+}
+
+impl Clone for Rational {
+    fn clone(&self) -> Rational {
+        return Rational { repr: self.repr.clone() };
+    }
+
+    fn clone_from(&mut self, source: &Rational) {
+        self.repr.clone_from(&source.repr);
+    }
+}
+
+fn sign_of(negative: bool) -> Sign {
+    return if negative { Sign::Neg } else { Sign::Pos };
 }
 
 impl Rational {
     pub fn new(sign: Sign, numer: BigUint, denom: BigUint) -> Rational {
-        let mut a = Rational { 
-            sign, 
-            numer, 
-            denom
-        };
+        let mut a = Rational::from_parts(sign, numer, denom);
 
         a.simplify();
 
         return a;
     }
 
+    /// Same as new, but fails instead of panicking on a zero denominator
+    pub fn try_new(sign: Sign, numer: BigUint, denom: BigUint) -> Result<Rational, RationalError> {
+        if denom == BigUint::ZERO {
+            return Err(RationalError::ZeroDenominator);
+        }
+
+        return Ok(Rational::new(sign, numer, denom));
+    }
+
     pub fn new_usize(sign: Sign, numer: usize, denom: usize) -> Rational {
         return Rational::new(sign, BigUint::from(numer), BigUint::from(denom));
     }
 
+    /// sign * numer / denom exactly as given (not simplified), stored small if it fits
+    pub(crate) fn from_parts(sign: Sign, numer: BigUint, denom: BigUint) -> Rational {
+        if let (Ok(small_numer), Ok(small_denom)) = (i64::try_from(&numer), u64::try_from(&denom)) {
+            return Rational { repr: Repr::Small(if sign == Sign::Neg { -small_numer } else { small_numer }, small_denom) };
+        }
+
+        // Zero is always Sign::Pos
+        let sign = if numer == BigUint::ZERO { Sign::Pos } else { sign };
+
+        return Rational { repr: Repr::Big(sign, numer, denom) };
+    }
+
+    /// from_parts for the results of the machine integer paths, which can be up to twice as wide as a Small
+    fn from_wide(sign: Sign, numer: u128, denom: u128) -> Rational {
+        if let (Ok(small_numer), Ok(small_denom)) = (i64::try_from(numer), u64::try_from(denom)) {
+            return Rational { repr: Repr::Small(if sign == Sign::Neg { -small_numer } else { small_numer }, small_denom) };
+        }
+
+        return Rational::from_parts(sign, BigUint::from(numer), BigUint::from(denom));
+    }
+
+    /// sign * magnitude as a Small, if it fits
+    fn small_integer(sign: Sign, magnitude: &BigUint) -> Option<Rational> {
+        let magnitude = i64::try_from(magnitude).ok()?;
+
+        return Some(Rational { repr: Repr::Small(if sign == Sign::Neg { -magnitude } else { magnitude }, 1) });
+    }
+
+    /// (sign, numerator, denominator), borrowed when the value is stored big
+    fn parts(&self) -> (Sign, Cow<'_, BigUint>, Cow<'_, BigUint>) {
+        match &self.repr {
+            Repr::Small(numer, denom) => {
+                return (sign_of(*numer < 0), Cow::Owned(BigUint::from(numer.unsigned_abs())), Cow::Owned(BigUint::from(*denom)));
+            },
+            Repr::Big(sign, numer, denom) => {
+                return (*sign, Cow::Borrowed(numer), Cow::Borrowed(denom));
+            }
+        }
+    }
+
+    /// Moves the parts out and leaves zero behind, the big paths take self apart like this and put it back together with from_parts
+    fn take_parts(&mut self) -> (Sign, BigUint, BigUint) {
+        return std::mem::take(self).into_parts();
+    }
+
     pub fn simplify(&mut self) {
-        let gcd = gcd(&self.numer, &self.denom);
+        if let Repr::Small(numer, denom) = &mut self.repr {
+            if *denom == 0 {
+                panic!("Rational with zero denominator detected in simplify function");
+            }
+
+            let gcd = binary_gcd_u64(numer.unsigned_abs(), *denom);
+            *numer /= gcd as i64;
+            *denom /= gcd;
+
+            return;
+        }
+
+        let (sign, mut numer, mut denom) = self.take_parts();
+        let gcd = gcd(&numer, &denom);
 
         if gcd != BigUint::from(1 as u8) {
-            self.numer /= &gcd;
-            self.denom /= &gcd;
+            numer /= &gcd;
+            denom /= &gcd;
         }
 
-        if self.denom == BigUint::ZERO {
+        if denom == BigUint::ZERO {
             panic!("Rational with zero denominator detected in simplify function");
         }
+
+        *self = Rational::from_parts(sign, numer, denom);
+    }
+
+    pub fn sign(&self) -> Sign {
+        match &self.repr {
+            Repr::Small(numer, _) => {
+                return sign_of(*numer < 0);
+            },
+            Repr::Big(sign, _, _) => {
+                return *sign;
+            }
+        }
+    }
+
+    /// Borrowed when the value is stored big, small values build a BigUint on the spot
+    pub fn numer(&self) -> Cow<'_, BigUint> {
+        return self.parts().1;
+    }
+
+    /// Borrowed when the value is stored big, small values build a BigUint on the spot
+    pub fn denom(&self) -> Cow<'_, BigUint> {
+        return self.parts().2;
+    }
+
+    /// numer().bits() without building a BigUint
+    pub(crate) fn numer_bits(&self) -> u64 {
+        match &self.repr {
+            Repr::Small(numer, _) => {
+                return (u64::BITS - numer.unsigned_abs().leading_zeros()) as u64;
+            },
+            Repr::Big(_, numer, _) => {
+                return numer.bits();
+            }
+        }
+    }
+
+    /// denom().bits() without building a BigUint
+    pub(crate) fn denom_bits(&self) -> u64 {
+        match &self.repr {
+            Repr::Small(_, denom) => {
+                return (u64::BITS - denom.leading_zeros()) as u64;
+            },
+            Repr::Big(_, _, denom) => {
+                return denom.bits();
+            }
+        }
+    }
+
+    /// (sign, numerator, denominator)
+    pub fn into_parts(self) -> (Sign, BigUint, BigUint) {
+        match self.repr {
+            Repr::Small(numer, denom) => {
+                return (sign_of(numer < 0), BigUint::from(numer.unsigned_abs()), BigUint::from(denom));
+            },
+            Repr::Big(sign, numer, denom) => {
+                return (sign, numer, denom);
+            }
+        }
     }
 
     pub fn is_zero(&self) -> bool {
-        return self.numer == BigUint::ZERO;
+        match &self.repr {
+            Repr::Small(numer, _) => {
+                return *numer == 0;
+            },
+            Repr::Big(_, numer, _) => {
+                return *numer == BigUint::ZERO;
+            }
+        }
     }
     pub fn zero() -> Rational {
         return Rational::from(0);
     }
 
-    pub fn is_one(&self) -> bool {
-        return self.numer != BigUint::ZERO && self.numer == self.denom;
+    pub fn is_one(&self) -> bool {
+        match &self.repr {
+            Repr::Small(numer, denom) => {
+                return *numer > 0 && *numer as u64 == *denom;
+            },
+            Repr::Big(sign, numer, denom) => {
+                return *numer != BigUint::ZERO && numer == denom && *sign == Sign::Pos;
+            }
+        }
+    }
+    pub fn one() -> Rational {
+        return Rational::from(1);
+    }
+
+
+    pub fn negate(&mut self) {
+        match &mut self.repr {
+            Repr::Small(numer, _) => {
+                *numer = -*numer;
+            },
+            Repr::Big(sign, numer, _) => {
+                if *numer != BigUint::ZERO {
+                    *sign = !*sign;
+                }
+            }
+        }
+    }
+
+    pub fn invert(&mut self) {
+        if self.is_zero() {
+            panic!("Rational with zero numerator attempted to invert")
+        }
+
+        if let Repr::Small(numer, denom) = self.repr && let Ok(new_numer) = i64::try_from(denom) {
+            self.repr = Repr::Small(if numer < 0 { -new_numer } else { new_numer }, numer.unsigned_abs());
+            return;
+        }
+
+        let (sign, numer, denom) = self.take_parts();
+        *self = Rational::from_parts(sign, denom, numer);
+    }
+
+    /// Same as invert, but fails (leaving self untouched) instead of panicking on zero
+    pub fn checked_invert(&mut self) -> Result<(), RationalError> {
+        if self.is_zero() {
+            return Err(RationalError::DivisionByZero);
+        }
+
+        self.invert();
+
+        return Ok(());
+    }
+
+    /// self / rhs, failing instead of panicking when rhs is zero
+    pub fn checked_div(&self, rhs: &Rational) -> Result<Rational, RationalError> {
+        if rhs.is_zero() {
+            return Err(RationalError::DivisionByZero);
+        }
+
+        let mut a = self.clone();
+        a /= rhs;
+
+        return Ok(a);
+    }
+
+    /// Exact power by repeated squaring, so any exponent works (as long as the result fits in memory)
+    pub fn powi(&mut self, exp: &BigUint) {
+        if *exp == BigUint::ZERO {
+            *self = Rational::one();
+            return;
+        }
+
+        if *exp == BigUint::from(1_u8) {
+            return;
+        }
+
+        if let Repr::Small(numer, denom) = self.repr
+            && let Ok(exp) = u32::try_from(exp)
+            && let (Some(new_numer), Some(new_denom)) = ((numer.unsigned_abs() as u128).checked_pow(exp), (denom as u128).checked_pow(exp)) {
+            *self = Rational::from_wide(sign_of(numer < 0 && exp % 2 == 1), new_numer, new_denom);
+            return;
+        }
+
+        let (mut sign, self_numer, self_denom) = self.take_parts();
+
+        if !exp.bit(0) {
+            sign = Sign::Pos;
+        }
+
+        let mut numer = BigUint::from(1_u8);
+        let mut denom = BigUint::from(1_u8);
+
+        for i in (0..exp.bits()).rev() {
+            numer = &numer * &numer;
+            denom = &denom * &denom;
+
+            if exp.bit(i) {
+                numer *= &self_numer;
+                denom *= &self_denom;
+            }
+        }
+
+        *self = Rational::from_parts(sign, numer, denom);
+
+        // No need to simplify, as if numer and denom are already simplified then their powers will also be
+        // (Consider that their prime factorizations will still contain the same prime bases after the operation)
+    }
+
+    /// powi for exponents of either sign, negative exponents invert the result
+    /// Fails (leaving self untouched) for zero to a negative power
+    pub fn pow_bigint(&mut self, exp: &BigInt) -> Result<(), RationalError> {
+        if exp.sign() == num_bigint::Sign::Minus {
+            if self.is_zero() {
+                return Err(RationalError::DivisionByZero);
+            }
+
+            self.invert();
+        }
+
+        self.powi(exp.magnitude());
+
+        return Ok(());
+    }
+
+    pub fn pow_signed(&mut self, exp: i64) -> Result<(), RationalError> {
+        return self.pow_bigint(&BigInt::from(exp));
+    }
+
+    /// Forces the denominator into a set value, rounding the numerator according to mode
+    /// Returns whether any rounding happened, i.e. false when the value was already exactly representable with the new denominator
+    pub fn to_with_denominator(&mut self, new_denom: &BigUint, mode: RoundingMode) -> bool {
+        let negative = self.is_negative();
+        let (sign, numer, denom) = self.take_parts();
+
+        let scaled_numer = numer * new_denom;
+        let (numer, inexact) = mode.round_quotient_exact(&scaled_numer, &denom, negative);
+
+        *self = Rational::from_parts(sign, numer, new_denom.clone());
+
+        return inexact;
+    }
+
+    pub fn is_simplified(&self) -> bool {
+        if let Repr::Small(numer, denom) = &self.repr {
+            return binary_gcd_u64(numer.unsigned_abs(), *denom) == 1;
+        }
+
+        let (_, numer, denom) = self.parts();
+        return gcd(&numer, &denom) == BigUint::from(1 as u8);
+    }
+
+    pub fn is_negative(&self) -> bool {
+        return !self.is_zero() && matches!(self.sign(), Sign::Neg);
+    }
+
+    pub fn is_positive(&self) -> bool {
+        return !self.is_zero() && matches!(self.sign(), Sign::Pos);
+    }
+
+    pub fn abs(&self) -> Rational {
+        if self.is_negative() {
+            return -self;
+        }
+        return self.clone();
+    }
+
+    /// -1, 0 or 1
+    pub fn signum(&self) -> Rational {
+        if self.is_zero() {
+            return Rational::zero();
+        }
+
+        return Rational::new(self.sign(), BigUint::from(1_u8), BigUint::from(1_u8));
+    }
+
+    /// The smaller of the two without cloning either (Ord::min takes them by value)
+    pub fn min_ref<'a>(&'a self, other: &'a Rational) -> &'a Rational {
+        if other < self {
+            return other;
+        }
+        return self;
+    }
+
+    /// The larger of the two without cloning either (Ord::max takes them by value)
+    pub fn max_ref<'a>(&'a self, other: &'a Rational) -> &'a Rational {
+        if other > self {
+            return other;
+        }
+        return self;
+    }
+
+    pub fn is_int(&self) -> bool {
+        if let Repr::Small(numer, denom) = &self.repr {
+            return numer.unsigned_abs() % *denom == 0;
+        }
+
+        let (_, numer, denom) = self.parts();
+
+        if *denom == BigUint::from(1 as u8) {
+            return true;
+        }
+
+        return gcd(&numer, &denom) == *denom;
+    }
+
+    pub fn is_denom_odd(&self) -> bool {
+        match &self.repr {
+            Repr::Small(_, denom) => {
+                return denom % 2 == 1;
+            },
+            Repr::Big(_, _, denom) => {
+                return denom.bit(0);
+            }
+        }
+    }
+
+    pub fn is_int_assume_simplified(&self) -> bool {
+        match &self.repr {
+            Repr::Small(_, denom) => {
+                return *denom == 1;
+            },
+            Repr::Big(_, _, denom) => {
+                return *denom == BigUint::from(1 as u8);
+            }
+        }
+    }
+
+    /// self += rhs without the gcd at the end, for long accumulation loops that call simplify once when they're done
+    /// When one denominator divides the other the larger one is used as the common denominator, otherwise it's their product,
+    /// So this stays small for series like sum(1/n!) but can grow quickly for unrelated denominators
+    /// (Values that are small enough are added on machine integers, where the gcd is cheap enough to always do)
+    pub fn add_assign_raw(&mut self, rhs: &Rational) {
+        if self.small_binop(rhs, SmallOp::Add) {
+            return;
+        }
+
+        self.add_signed_raw(rhs, rhs.sign());
+    }
+
+    /// self -= rhs without the gcd at the end, see add_assign_raw
+    pub fn sub_assign_raw(&mut self, rhs: &Rational) {
+        if self.small_binop(rhs, SmallOp::Sub) {
+            return;
+        }
+
+        self.add_signed_raw(rhs, !rhs.sign());
+    }
+
+    /// self *= rhs without the gcd at the end
+    pub fn mul_assign_raw(&mut self, rhs: &Rational) {
+        if self.small_binop(rhs, SmallOp::Mul) {
+            return;
+        }
+
+        let (sign, mut numer, mut denom) = self.take_parts();
+        let (rhs_sign, rhs_numer, rhs_denom) = rhs.parts();
+
+        numer *= &*rhs_numer;
+        denom *= &*rhs_denom;
+
+        *self = Rational::from_parts(sign ^ rhs_sign, numer, denom);
+    }
+
+    /// self /= rhs without the gcd at the end, panics if rhs is zero
+    pub fn div_assign_raw(&mut self, rhs: &Rational) {
+        if rhs.is_zero() {
+            panic!("Rational attempted to divide by zero");
+        }
+
+        if self.small_binop(rhs, SmallOp::Div) {
+            return;
+        }
+
+        let (sign, mut numer, mut denom) = self.take_parts();
+        let (rhs_sign, rhs_numer, rhs_denom) = rhs.parts();
+
+        numer *= &*rhs_denom;
+        denom *= &*rhs_numer;
+
+        *self = Rational::from_parts(sign ^ rhs_sign, numer, denom);
+    }
+
+    fn add_signed_raw(&mut self, rhs: &Rational, rhs_sign: Sign) {
+        let (sign, mut numer, mut denom) = self.take_parts();
+        let (_, rhs_numer, rhs_denom) = rhs.parts();
+
+        let rhs_numer = if denom == *rhs_denom {
+            rhs_numer.into_owned()
+        } else if (&denom % &*rhs_denom) == BigUint::ZERO {
+            &*rhs_numer * (&denom / &*rhs_denom)
+        } else if (&*rhs_denom % &denom) == BigUint::ZERO {
+            let factor = &*rhs_denom / &denom;
+            numer *= &factor;
+            denom *= &factor;
+            rhs_numer.into_owned()
+        } else {
+            numer *= &*rhs_denom;
+            let rhs_numer = &*rhs_numer * &denom;
+            denom *= &*rhs_denom;
+            rhs_numer
+        };
+
+        *self = Rational::add_signed_numer(sign, numer, denom, rhs_numer, rhs_sign);
+    }
+
+    /// sign * numer / denom plus rhs_numer / denom with the given sign
+    fn add_signed_numer(mut sign: Sign, mut numer: BigUint, denom: BigUint, rhs_numer: BigUint, rhs_sign: Sign) -> Rational {
+        if sign == rhs_sign {
+            numer += rhs_numer;
+        } else if numer >= rhs_numer {
+            numer -= rhs_numer;
+        } else {
+            numer = rhs_numer - &numer;
+            sign = rhs_sign;
+        }
+
+        return Rational::from_parts(sign, numer, denom);
+    }
+
+    /// Adding an integer keeps the denominator as is, and since gcd(a + n*b, b) = gcd(a, b) a simplified value stays simplified
+    fn add_integer(&mut self, sign: Sign, magnitude: &BigUint) {
+        if let Some(rhs) = Rational::small_integer(sign, magnitude) && self.small_binop(&rhs, SmallOp::Add) {
+            return;
+        }
+
+        let (self_sign, numer, denom) = self.take_parts();
+        let rhs_numer = magnitude * &denom;
+
+        *self = Rational::add_signed_numer(self_sign, numer, denom, rhs_numer, sign);
+    }
+
+    fn sub_integer(&mut self, sign: Sign, magnitude: &BigUint) {
+        self.add_integer(!sign, magnitude);
+    }
+
+    /// Only the gcd of the integer and the denominator is needed, which is a lot smaller than a full simplify
+    fn mul_integer(&mut self, sign: Sign, magnitude: &BigUint) {
+        if let Some(rhs) = Rational::small_integer(sign, magnitude) && self.small_binop(&rhs, SmallOp::Mul) {
+            return;
+        }
+
+        let (self_sign, mut numer, mut denom) = self.take_parts();
+        let gcd = gcd(magnitude, &denom);
+
+        numer *= magnitude / &gcd;
+        denom /= &gcd;
+
+        *self = Rational::from_parts(self_sign ^ sign, numer, denom);
+    }
+
+    fn div_integer(&mut self, sign: Sign, magnitude: &BigUint) {
+        if *magnitude == BigUint::ZERO {
+            panic!("Rational attempted to divide by zero");
+        }
+
+        if let Some(rhs) = Rational::small_integer(sign, magnitude) && self.small_binop(&rhs, SmallOp::Div) {
+            return;
+        }
+
+        let (self_sign, mut numer, mut denom) = self.take_parts();
+        let gcd = gcd(magnitude, &numer);
+
+        numer /= &gcd;
+        denom *= magnitude / &gcd;
+
+        *self = Rational::from_parts(self_sign ^ sign, numer, denom);
+    }
+
+    /// Rounds to an integer in the given direction
+    pub fn round_to_integer(&self, mode: RoundingMode) -> BigInt {
+        let (sign, numer, denom) = self.parts();
+        let magnitude = mode.round_quotient(&numer, &denom, self.is_negative());
+
+        return match sign {
+            Sign::Pos => BigInt::from(magnitude),
+            Sign::Neg => -BigInt::from(magnitude)
+        };
+    }
+
+    /// Rounds towards negative infinity
+    pub fn floor(&self) -> BigInt {
+        return self.round_to_integer(RoundingMode::Floor);
     }
-    pub fn one() -> Rational {
-        return Rational::from(1);
+
+    /// Rounds towards positive infinity
+    pub fn ceil(&self) -> BigInt {
+        return self.round_to_integer(RoundingMode::Ceil);
     }
 
+    /// Rounds to the nearest integer, with ties going away from zero (same as f64::round)
+    pub fn round(&self) -> BigInt {
+        return self.round_to_integer(RoundingMode::HalfAwayFromZero);
+    }
 
-    pub fn negate(&mut self) {
-        self.sign = !self.sign;
+    /// Rounds towards zero
+    pub fn trunc(&self) -> BigInt {
+        return self.round_to_integer(RoundingMode::TowardZero);
     }
 
-    pub fn invert(&mut self) {
-        if self.numer == BigUint::ZERO {
-            panic!("Rational with zero numerator attempted to invert")
-        }
+    /// Splits into (trunc(self), self - trunc(self)), so the fractional part has the same sign as self and a magnitude below 1
+    pub fn split_int_frac(&self) -> (BigInt, Rational) {
+        let int_part = self.trunc();
 
-        std::mem::swap(&mut self.numer, &mut self.denom);
-    }
+        let mut frac_part = self.clone();
+        frac_part -= &Rational::from(int_part.clone());
 
-    pub fn powi(&mut self, exp: &BigUint) {
-        if *exp == BigUint::ZERO {
-            *self = Rational::one();
-            return;
-        }
+        return (int_part, frac_part);
+    }
 
-        if *exp == BigUint::from(1 as u8) {
-            return;
+    /// The integer q such that self = q * rhs + r with 0 <= r < |rhs|, same convention as i64::div_euclid
+    pub fn div_euclid(&self, rhs: &Rational) -> BigInt {
+        if rhs.is_zero() {
+            panic!("Rational attempted div_euclid by zero");
         }
 
-        let u32_digits = exp.to_u32_digits();
+        let mut quotient = self.clone();
+        quotient /= rhs;
 
-        if u32_digits.len() != 1 || *exp > BigUint::from(12 as u8) {
-            panic!("Attempted to do powi with a pretty large exponent");
+        if rhs.is_negative() {
+            return quotient.ceil();
         }
+        return quotient.floor();
+    }
 
-        if exp % BigUint::from(2 as u8) == BigUint::ZERO {
-            self.sign = Sign::Pos;
-        }
+    /// The r in 0 <= r < |rhs| such that self = q * rhs + r for some integer q, so e.g. x.rem_euclid(2pi) reduces x into [0, 2pi)
+    pub fn rem_euclid(&self, rhs: &Rational) -> Rational {
+        let mut a = Rational::from(self.div_euclid(rhs));
+        a *= rhs;
 
-        self.numer = self.numer.pow(u32_digits[0]);
-        self.denom = self.denom.pow(u32_digits[0]);
+        let mut r = self.clone();
+        r -= &a;
 
-        // No need to simplify, as if numer and denom are already simplified then their powers will also be
-        // (Consider that their prime factorizations will still contain the same prime bases after the operation)
+        return r;
     }
 
-    /// Forces the denominator into a set value and modifies the numerator to have the closest value to the initial value
-    /// When round_up is false, it will round down
-    pub fn to_with_denominator(&mut self, new_denom: &BigUint, round_up: bool) {
-        let mut new_mod_old = new_denom.clone();
-        new_mod_old %= &self.denom;
-        let mut new_div_old = new_denom.clone();
-        new_div_old /= &self.denom;
-
-        // At this point I start playing with my values so the names don't really match
-        new_div_old *= &self.numer;
-        new_mod_old *= &self.numer;
-        new_mod_old /= &self.denom;
+    /// The largest positive rational g such that self / g and rhs / g are both integers, gcd(a/b, c/d) = gcd(a, c) / lcm(b, d)
+    /// Always positive, with gcd(x, 0) = |x| and gcd(0, 0) = 0 like the integer version
+    pub fn gcd(&self, rhs: &Rational) -> Rational {
+        let (_, a_numer, a_denom) = simplified_clone(self).into_parts();
+        let (_, b_numer, b_denom) = simplified_clone(rhs).into_parts();
 
-        self.numer = new_div_old;
-        self.numer += &new_mod_old;
+        return Rational::new(Sign::Pos, gcd(&a_numer, &b_numer), lcm(&a_denom, &b_denom));
+    }
 
-        // This could account for when the fraction is already an exact representation of the previous value but I think detecting that
-        // Would be worse for performance than just adding one whenever
-        if round_up {
-            self.numer += 1 as u8;
+    /// The smallest positive rational that is an integer multiple of both, lcm(a/b, c/d) = lcm(a, c) / gcd(b, d)
+    /// Zero if either side is zero
+    pub fn lcm(&self, rhs: &Rational) -> Rational {
+        if self.is_zero() || rhs.is_zero() {
+            return Rational::zero();
         }
 
-        self.denom = new_denom.clone();
-    }
+        let (_, a_numer, a_denom) = simplified_clone(self).into_parts();
+        let (_, b_numer, b_denom) = simplified_clone(rhs).into_parts();
 
-    pub fn is_simplified(&self) -> bool {
-        return gcd(&self.numer, &self.denom) == BigUint::from(1 as u8);
+        return Rational::new(Sign::Pos, lcm(&a_numer, &b_numer), gcd(&a_denom, &b_denom));
     }
 
-    pub fn is_negative(&self) -> bool {
-        return !self.is_zero() && matches!(self.sign, Sign::Neg);
-    }
+    /// The gcd of all the values, so every value divided by it is an integer and those integers have no common factor
+    /// Used to pull a common rational factor out of a sum, zero for an empty (or all zero) slice
+    pub fn content(values: &[Rational]) -> Rational {
+        let mut numer = BigUint::ZERO;
+        let mut denom = BigUint::from(1_u8);
 
-    pub fn is_int(&self) -> bool {
-        if self.denom == BigUint::from(1 as u8) {
-            return true;
+        for x in values {
+            let (_, x_numer, x_denom) = simplified_clone(x).into_parts();
+
+            numer = gcd(&numer, &x_numer);
+            denom = lcm(&denom, &x_denom);
         }
 
-        return gcd(&self.numer, &self.denom) == self.denom;
+        return Rational::new(Sign::Pos, numer, denom);
     }
 
-    pub fn is_denom_odd(&self) -> bool {
-        return self.denom.bit(0);
+    pub fn div_euclid_biguint(&self, rhs: &BigUint) -> BigInt {
+        return self.div_euclid(&Rational::new(Sign::Pos, rhs.clone(), BigUint::from(1_u8)));
     }
 
-    pub fn is_int_assume_simplified(&self) -> bool {
-        return self.denom == BigUint::from(1 as u8);
+    pub fn rem_euclid_biguint(&self, rhs: &BigUint) -> Rational {
+        return self.rem_euclid(&Rational::new(Sign::Pos, rhs.clone(), BigUint::from(1_u8)));
     }
 
-    /// This function is highly lossy and should absolutely never be used for any reason in Neoprene or Byzantine code
-    /// (I only use it to easily see a numerical value for a rational with large numbers)
+    /// The nearest f64 (ties to even), only for looking at values, Neoprene and Byzantine code should never rely on floats
     pub fn to_float(&self) -> f64 {
-        let mut a = self.numer.clone();
-        a *= 2_u32.pow(20);
-        a /= &self.denom;
+        return self.to_f64_round(RoundingMode::HalfEven);
+    }
+
+    /// Converts to an f64 rounded according to mode, so e.g. Floor and Ceil give floats that are guaranteed to be below and above the exact value
+    /// Values past f64::MAX become infinity, or f64::MAX when the mode rounds towards zero
+    pub fn to_f64_round(&self, mode: RoundingMode) -> f64 {
+        if self.is_zero() {
+            return 0.0;
+        }
+
+        let negative = self.is_negative();
+        let (_, numer, denom) = self.parts();
 
-        let mut b = 0.0_f64;
-        let a = a.to_u64_digits();
-        for (i, a) in a.iter().enumerate() {
-            b += (*a as f64) * (2_u32.pow(i as u32) as f64);
+        // Scale by 2^shift so the quotient has 53 bits (or fewer for subnormals, whose last bit is always worth 2^-1074)
+        let bit_difference = self.numer_bits() as i64 - self.denom_bits() as i64;
+        let mut shift = 53 - bit_difference;
+        if scaled_quotient(&numer, &denom, shift).bits() > 53 {
+            shift -= 1;
         }
+        shift = shift.min(1074);
+
+        let (scaled_numer, scaled_denom) = scale_by_power_of_two(&numer, &denom, shift);
+        let (mantissa, _) = mode.round_quotient_exact(&scaled_numer, &scaled_denom, negative);
+
+        // Rounding up can carry into a 54th bit (e.g. 2^53 - 1/2 rounded up), which is still exact as an f64
+        let exponent = mantissa.bits() as i64 - 1 - shift;
+
+        let magnitude = if exponent > 1023 {
+            let towards_zero = match mode {
+                RoundingMode::Floor => !negative,
+                RoundingMode::Ceil => negative,
+                RoundingMode::TowardZero => true,
+                _ => false
+            };
+
+            if towards_zero { f64::MAX } else { f64::INFINITY }
+        } else {
+            // Both halves of the scale are normal powers of two, and the result is representable by construction, so this is exact
+            let mantissa = u64::try_from(&mantissa).unwrap() as f64;
+            let first_half = -shift / 2;
+            mantissa * power_of_two_f64(first_half) * power_of_two_f64(-shift - first_half)
+        };
+
+        if negative {
+            return -magnitude;
+        }
+        return magnitude;
+    }
+}
 
-        return b / (2_u32.pow(20) as f64);
+/// numer * 2^shift and denom, or numer and denom * 2^-shift for a negative shift
+fn scale_by_power_of_two(numer: &BigUint, denom: &BigUint, shift: i64) -> (BigUint, BigUint) {
+    if shift >= 0 {
+        return (numer << shift as u64, denom.clone());
     }
+    return (numer.clone(), denom << shift.unsigned_abs());
+}
+
+fn scaled_quotient(numer: &BigUint, denom: &BigUint, shift: i64) -> BigUint {
+    let (numer, denom) = scale_by_power_of_two(numer, denom, shift);
+    return numer / denom;
+}
+
+/// 2^exp for -1022 <= exp <= 1023, built straight from the bits
+fn power_of_two_f64(exp: i64) -> f64 {
+    return f64::from_bits(((exp + 1023) as u64) << 52);
 }
 
 impl Debug for Rational {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> 
     { 
-        let a = match self.sign {
+        let (sign, numer, denom) = self.parts();
+
+        let a = match sign {
             Sign::Pos => {
                 ""
             },
@@ -250,134 +1043,416 @@ impl Debug for Rational {
             }
         };
 
-        if self.denom == BigUint::from(1 as u8) {
-            return write!(f, "{}{}", a, self.numer);
+        if *denom == BigUint::from(1 as u8) {
+            return write!(f, "{}{}", a, numer);
+        }
+        return write!(f, "({}{}/{})", a, numer, denom);
+    }
+}
+
+fn simplified_clone(x: &Rational) -> Rational {
+    let mut a = x.clone();
+    a.simplify();
+    return a;
+}
+
+/// Euclid on the wide results of the machine integer paths, then binary gcd once both fit in a u64
+fn small_gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        if let (Ok(a_small), Ok(b_small)) = (u64::try_from(a), u64::try_from(b)) {
+            return binary_gcd_u64(a_small, b_small) as u128;
         }
-        return write!(f, "({}{}/{})", a, self.numer, self.denom);
+
+        let temp = a % b;
+        a = b;
+        b = temp;
+    }
+
+    return a;
+}
+
+#[derive(Clone, Copy)]
+enum SmallOp {
+    Add,
+    Sub,
+    Mul,
+    Div
+}
+
+impl Rational {
+    /// Tries self (op) rhs on machine integers, leaving the result simplified
+    /// Returns false if either side is stored big, a sum overflows, or for division by zero (which is left to panic in the big path)
+    /// Numerators below 2^63 and denominators below 2^64 keep every product below 2^127, so only the sums can overflow an i128
+    fn small_binop(&mut self, rhs: &Rational, op: SmallOp) -> bool {
+        let (Repr::Small(a_numer, a_denom), Repr::Small(b_numer, b_denom)) = (&self.repr, &rhs.repr) else {
+            return false;
+        };
+
+        let (a_numer, a_denom, b_numer, b_denom) = (*a_numer as i128, *a_denom as u128, *b_numer as i128, *b_denom as u128);
+
+        let (numer, denom) = match op {
+            SmallOp::Add => {
+                let Some(numer) = (a_numer * b_denom as i128).checked_add(b_numer * a_denom as i128) else {
+                    return false;
+                };
+                (numer, a_denom * b_denom)
+            },
+            SmallOp::Sub => {
+                let Some(numer) = (a_numer * b_denom as i128).checked_sub(b_numer * a_denom as i128) else {
+                    return false;
+                };
+                (numer, a_denom * b_denom)
+            },
+            SmallOp::Mul => {
+                (a_numer * b_numer, a_denom * b_denom)
+            },
+            SmallOp::Div => {
+                if b_numer == 0 {
+                    return false;
+                }
+
+                let numer = a_numer * b_denom as i128;
+                (if b_numer < 0 { -numer } else { numer }, a_denom * b_numer.unsigned_abs())
+            }
+        };
+
+        let magnitude = numer.unsigned_abs();
+        let gcd = small_gcd(magnitude, denom);
+        *self = Rational::from_wide(sign_of(numer < 0), magnitude / gcd, denom / gcd);
+
+        return true;
     }
 }
 
 impl AddAssign<&Rational> for Rational {
     fn add_assign(&mut self, rhs: &Rational) { 
-        let denom_lcm = lcm(&self.denom, &rhs.denom);
+        if self.small_binop(rhs, SmallOp::Add) {
+            return;
+        }
+
+        let (mut sign, mut numer, denom) = self.take_parts();
+        let (rhs_sign, rhs_numer, rhs_denom) = rhs.parts();
+
+        let denom_lcm = lcm(&denom, &rhs_denom);
 
-        let self_factor = denom_lcm.clone() / &self.denom;
-        let rhs_factor = denom_lcm / &rhs.denom;
+        let self_factor = &denom_lcm / &denom;
+        let rhs_factor = &denom_lcm / &*rhs_denom;
 
-        self.numer *= &self_factor;
-        self.denom *= &self_factor;
+        numer *= &self_factor;
 
-        let rhs_normalized_numer = rhs.numer.clone() * rhs_factor;
+        let rhs_normalized_numer = rhs_numer.into_owned() * rhs_factor;
 
-        match (self.sign, rhs.sign, self.numer > rhs_normalized_numer) {
+        match (sign, rhs_sign, numer > rhs_normalized_numer) {
             (Sign::Pos, Sign::Pos, _) => {
-                self.numer += rhs_normalized_numer;
+                numer += rhs_normalized_numer;
             },
             (Sign::Neg, Sign::Neg, _) => {
-                self.numer += rhs_normalized_numer;
+                numer += rhs_normalized_numer;
             }
             (Sign::Pos, Sign::Neg, true) => {
-                self.numer -= rhs_normalized_numer;
+                numer -= rhs_normalized_numer;
             },
             (Sign::Pos, Sign::Neg, false) => {
-                self.numer = rhs_normalized_numer - &self.numer;
-                self.sign = Sign::Neg;
+                numer = rhs_normalized_numer - &numer;
+                sign = Sign::Neg;
             },
             (Sign::Neg, Sign::Pos, true) => {
-                self.numer += rhs_normalized_numer;
+                numer -= rhs_normalized_numer;
             },
             (Sign::Neg, Sign::Pos, false) => {
-                self.numer = rhs_normalized_numer - &self.numer;
-                self.sign = Sign::Pos;
+                numer = rhs_normalized_numer - &numer;
+                sign = Sign::Pos;
             }
         }
         // Phew!
 
+        *self = Rational::from_parts(sign, numer, denom_lcm);
         self.simplify();
     }
 }
 
 impl SubAssign<&Rational> for Rational {
     fn sub_assign(&mut self, rhs: &Rational) { 
-        let denom_lcm = lcm(&self.denom, &rhs.denom);
+        if self.small_binop(rhs, SmallOp::Sub) {
+            return;
+        }
+
+        let (mut sign, mut numer, denom) = self.take_parts();
+        let (rhs_sign, rhs_numer, rhs_denom) = rhs.parts();
+
+        let denom_lcm = lcm(&denom, &rhs_denom);
 
-        let self_factor = denom_lcm.clone() / &self.denom;
-        let rhs_factor = denom_lcm / &rhs.denom;
+        let self_factor = &denom_lcm / &denom;
+        let rhs_factor = &denom_lcm / &*rhs_denom;
 
-        self.numer *= &self_factor;
-        self.denom *= &self_factor;
+        numer *= &self_factor;
 
-        let rhs_normalized_numer = rhs.numer.clone() * rhs_factor;
+        let rhs_normalized_numer = rhs_numer.into_owned() * rhs_factor;
 
-        match (self.sign, !rhs.sign, self.numer > rhs_normalized_numer) {
+        match (sign, !rhs_sign, numer > rhs_normalized_numer) {
             (Sign::Pos, Sign::Pos, _) => {
-                self.numer += rhs_normalized_numer;
+                numer += rhs_normalized_numer;
             },
             (Sign::Neg, Sign::Neg, _) => {
-                self.numer += rhs_normalized_numer;
+                numer += rhs_normalized_numer;
             }
             (Sign::Pos, Sign::Neg, true) => {
-                self.numer -= rhs_normalized_numer;
+                numer -= rhs_normalized_numer;
             },
             (Sign::Pos, Sign::Neg, false) => {
-                self.numer = rhs_normalized_numer - &self.numer;
-                self.sign = Sign::Neg;
+                numer = rhs_normalized_numer - &numer;
+                sign = Sign::Neg;
             },
             (Sign::Neg, Sign::Pos, true) => {
-                self.numer += rhs_normalized_numer;
+                numer -= rhs_normalized_numer;
             },
             (Sign::Neg, Sign::Pos, false) => {
-                self.numer = rhs_normalized_numer - &self.numer;
-                self.sign = Sign::Pos;
+                numer = rhs_normalized_numer - &numer;
+                sign = Sign::Pos;
             }
         }
         // Phetwo!
 
+        *self = Rational::from_parts(sign, numer, denom_lcm);
         self.simplify();
     }
 }
 
 impl MulAssign<&Rational> for Rational {
     fn mul_assign(&mut self, rhs: &Rational) {
-        self.sign = self.sign ^ rhs.sign;
-        self.numer *= &rhs.numer;
-        self.denom *= &rhs.denom;
-    
+        if self.small_binop(rhs, SmallOp::Mul) {
+            return;
+        }
+
+        self.mul_assign_raw(rhs);
         self.simplify();
     }
 }
 
 impl DivAssign<&Rational> for Rational {
     fn div_assign(&mut self, rhs: &Rational) {
-        self.sign = self.sign ^ rhs.sign;
-        self.numer *= &rhs.denom;
-        self.denom *= &rhs.numer;
+        if self.small_binop(rhs, SmallOp::Div) {
+            return;
+        }
+
+        let (sign, mut numer, mut denom) = self.take_parts();
+        let (rhs_sign, rhs_numer, rhs_denom) = rhs.parts();
 
+        numer *= &*rhs_denom;
+        denom *= &*rhs_numer;
+
+        *self = Rational::from_parts(sign ^ rhs_sign, numer, denom);
         self.simplify();
     }
 }
 
+/// The by-value and mixed reference operators all forward to the XAssign<&Rational> implementations above
+macro_rules! forward_rational_binop {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl $assign_trait<Rational> for Rational {
+            fn $assign_method(&mut self, rhs: Rational) {
+                $assign_trait::$assign_method(self, &rhs);
+            }
+        }
+
+        impl $trait<&Rational> for &Rational {
+            type Output = Rational;
+
+            fn $method(self, rhs: &Rational) -> Rational {
+                let mut a = self.clone();
+                $assign_trait::$assign_method(&mut a, rhs);
+                return a;
+            }
+        }
+
+        impl $trait<Rational> for &Rational {
+            type Output = Rational;
+
+            fn $method(self, rhs: Rational) -> Rational {
+                return $trait::$method(self, &rhs);
+            }
+        }
+
+        impl $trait<&Rational> for Rational {
+            type Output = Rational;
+
+            fn $method(mut self, rhs: &Rational) -> Rational {
+                $assign_trait::$assign_method(&mut self, rhs);
+                return self;
+            }
+        }
+
+        impl $trait<Rational> for Rational {
+            type Output = Rational;
+
+            fn $method(mut self, rhs: Rational) -> Rational {
+                $assign_trait::$assign_method(&mut self, &rhs);
+                return self;
+            }
+        }
+    };
+}
+
+forward_rational_binop!(Add, add, AddAssign, add_assign);
+forward_rational_binop!(Sub, sub, SubAssign, sub_assign);
+forward_rational_binop!(Mul, mul, MulAssign, mul_assign);
+forward_rational_binop!(Div, div, DivAssign, div_assign);
+
+fn primitive_parts<T: Into<i128>>(i: T) -> (Sign, BigUint) {
+    let i: i128 = i.into();
+    return (if i < 0 { Sign::Neg } else { Sign::Pos }, BigUint::from(i.unsigned_abs()));
+}
+
+fn biguint_parts(i: &BigUint) -> (Sign, &BigUint) {
+    return (Sign::Pos, i);
+}
+
+fn bigint_parts(i: &BigInt) -> (Sign, &BigUint) {
+    return (if i.sign() == num_bigint::Sign::Minus { Sign::Neg } else { Sign::Pos }, i.magnitude());
+}
+
+/// Arithmetic with a plain integer on the right, so small constants don't have to be wrapped in a Rational first
+/// These skip most of the gcd work the Rational versions need (see add_integer and mul_integer)
+macro_rules! rational_integer_binop {
+    ($rhs:ty, $parts:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $inner:ident) => {
+        impl $assign_trait<$rhs> for Rational {
+            fn $assign_method(&mut self, rhs: $rhs) {
+                let (sign, magnitude) = $parts(rhs);
+                self.$inner(sign, &magnitude);
+            }
+        }
+
+        impl $trait<$rhs> for Rational {
+            type Output = Rational;
+
+            fn $method(mut self, rhs: $rhs) -> Rational {
+                $assign_trait::$assign_method(&mut self, rhs);
+                return self;
+            }
+        }
+
+        impl $trait<$rhs> for &Rational {
+            type Output = Rational;
+
+            fn $method(self, rhs: $rhs) -> Rational {
+                let mut a = self.clone();
+                $assign_trait::$assign_method(&mut a, rhs);
+                return a;
+            }
+        }
+    };
+}
+
+macro_rules! rational_integer_ops {
+    ($($rhs:ty => $parts:ident),*) => {
+        $(
+            rational_integer_binop!($rhs, $parts, Add, add, AddAssign, add_assign, add_integer);
+            rational_integer_binop!($rhs, $parts, Sub, sub, SubAssign, sub_assign, sub_integer);
+            rational_integer_binop!($rhs, $parts, Mul, mul, MulAssign, mul_assign, mul_integer);
+            rational_integer_binop!($rhs, $parts, Div, div, DivAssign, div_assign, div_integer);
+        )*
+    };
+}
+
+rational_integer_ops!(i32 => primitive_parts, i64 => primitive_parts, u32 => primitive_parts, u64 => primitive_parts, &BigUint => biguint_parts, &BigInt => bigint_parts);
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(mut self) -> Rational {
+        self.negate();
+        return self;
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        let mut a = self.clone();
+        a.negate();
+        return a;
+    }
+}
+
+/// Combines neighbours pairwise (a tree instead of a left fold) with the raw operations and simplifies once at the end,
+/// So the operands of each step stay about the same size and the n gcds of a sequential fold become one
+fn pairwise_reduce(mut terms: Vec<Rational>, identity: Rational, combine: fn(&mut Rational, &Rational)) -> Rational {
+    if terms.is_empty() {
+        return identity;
+    }
+
+    while terms.len() > 1 {
+        let mut next = Vec::with_capacity(terms.len().div_ceil(2));
+        let mut iter = terms.into_iter();
+
+        while let Some(mut a) = iter.next() {
+            if let Some(b) = iter.next() {
+                combine(&mut a, &b);
+            }
+            next.push(a);
+        }
+
+        terms = next;
+    }
+
+    let mut a = terms.pop().unwrap();
+    a.simplify();
+    return a;
+}
+
+impl Sum for Rational {
+    fn sum<I: Iterator<Item = Rational>>(iter: I) -> Rational {
+        return pairwise_reduce(iter.collect(), Rational::zero(), Rational::add_assign_raw);
+    }
+}
+
+impl<'a> Sum<&'a Rational> for Rational {
+    fn sum<I: Iterator<Item = &'a Rational>>(iter: I) -> Rational {
+        return pairwise_reduce(iter.cloned().collect(), Rational::zero(), Rational::add_assign_raw);
+    }
+}
+
+impl Product for Rational {
+    fn product<I: Iterator<Item = Rational>>(iter: I) -> Rational {
+        return pairwise_reduce(iter.collect(), Rational::one(), Rational::mul_assign_raw);
+    }
+}
+
+impl<'a> Product<&'a Rational> for Rational {
+    fn product<I: Iterator<Item = &'a Rational>>(iter: I) -> Rational {
+        return pairwise_reduce(iter.cloned().collect(), Rational::one(), Rational::mul_assign_raw);
+    }
+}
+
 impl PartialEq for Rational {
     fn eq(&self, other: &Self) -> bool {
-        if self.numer == BigUint::ZERO && other.denom == BigUint::ZERO {
-            // Both are zero
+        if let (Repr::Small(a_numer, a_denom), Repr::Small(b_numer, b_denom)) = (&self.repr, &other.repr) {
+            return *a_numer as i128 * *b_denom as i128 == *b_numer as i128 * *a_denom as i128;
+        }
+
+        let (self_sign, self_numer, self_denom) = self.parts();
+        let (other_sign, other_numer, other_denom) = other.parts();
+
+        if *self_numer == BigUint::ZERO && *other_numer == BigUint::ZERO {
+            // Both are zero (no matter the signs)
             return true;
         }
-        if self.sign != other.sign {
+        if self_sign != other_sign {
             // Signs differ
             return false;
         }
-        if self.numer == other.numer && self.denom == other.denom {
+        if self_numer == other_numer && self_denom == other_denom {
             // Trivially equal
             return true;
         }
 
-        let denom_lcm = lcm(&self.denom, &other.denom);
+        let denom_lcm = lcm(&self_denom, &other_denom);
 
-        let mut a = denom_lcm.clone() / &self.denom;
-        a *= &self.numer;
-        let mut b = denom_lcm / &other.denom;
-        b *= &other.numer;
+        let mut a = &denom_lcm / &*self_denom;
+        a *= &*self_numer;
+        let mut b = denom_lcm / &*other_denom;
+        b *= &*other_numer;
 
         return a == b;
     }
@@ -385,6 +1460,24 @@ impl PartialEq for Rational {
 
 impl Eq for Rational {}
 
+/// Hashes the canonical form (simplified, with zero always positive), so values that are equal under Eq hash the same
+/// Even if they were built unsimplified or as a negative zero (a simplified value is always stored small when it fits, so equal values also agree on that)
+impl Hash for Rational {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &simplified_clone(self).repr {
+            Repr::Small(numer, denom) => {
+                numer.hash(state);
+                denom.hash(state);
+            },
+            Repr::Big(sign, numer, denom) => {
+                sign.hash(state);
+                numer.hash(state);
+                denom.hash(state);
+            }
+        }
+    }
+}
+
 impl PartialOrd for Rational {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         return Some(self.cmp(other));
@@ -393,11 +1486,18 @@ impl PartialOrd for Rational {
 
 impl Ord for Rational {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.numer == BigUint::ZERO && other.numer == BigUint::ZERO {
+        if let (Repr::Small(a_numer, a_denom), Repr::Small(b_numer, b_denom)) = (&self.repr, &other.repr) {
+            return (*a_numer as i128 * *b_denom as i128).cmp(&(*b_numer as i128 * *a_denom as i128));
+        }
+
+        let (self_sign, self_numer, self_denom) = self.parts();
+        let (other_sign, other_numer, other_denom) = other.parts();
+
+        if *self_numer == BigUint::ZERO && *other_numer == BigUint::ZERO {
             // Both are zero
             return Ordering::Equal;
         }
-        match (self.sign, other.sign) {
+        match (self_sign, other_sign) {
             (Sign::Pos, Sign::Neg) => {
                 return Ordering::Greater;
             }
@@ -406,24 +1506,24 @@ impl Ord for Rational {
             }
             _ => {}
         }
-        if self.numer == other.numer && self.denom == other.denom {
+        if self_numer == other_numer && self_denom == other_denom {
             // Trivially equal
             return Ordering::Equal;
         }
 
-        let denom_lcm = lcm(&self.denom, &other.denom);
+        let denom_lcm = lcm(&self_denom, &other_denom);
 
-        let mut a = denom_lcm.clone() / &self.denom;
-        a *= &self.numer;
-        let mut b = denom_lcm / &other.denom;
-        b *= &other.numer;
+        let mut a = &denom_lcm / &*self_denom;
+        a *= &*self_numer;
+        let mut b = denom_lcm / &*other_denom;
+        b *= &*other_numer;
 
         if a == b {
             return Ordering::Equal;
         }
 
-        // Note that at this point we know self.sign == other.sign
-        return match (a > b, self.sign) {
+        // Note that at this point we know self_sign == other_sign
+        return match (a > b, self_sign) {
             (true, Sign::Pos) => Ordering::Greater,
             (true, Sign::Neg) => Ordering::Less,
             (false, Sign::Pos) => Ordering::Less,
@@ -432,22 +1532,203 @@ impl Ord for Rational {
     }
 }
 
+impl Rational {
+    /// Compares against sign * magnitude without building a Rational for it
+    fn cmp_integer(&self, sign: Sign, magnitude: &BigUint) -> Ordering {
+        if let Repr::Small(..) = self.repr && let Some(rhs) = Rational::small_integer(sign, magnitude) {
+            return self.cmp(&rhs);
+        }
+
+        let rhs_zero = *magnitude == BigUint::ZERO;
+
+        match (self.is_zero(), rhs_zero) {
+            (true, true) => {
+                return Ordering::Equal;
+            },
+            (true, false) => {
+                return if sign == Sign::Pos { Ordering::Less } else { Ordering::Greater };
+            },
+            (false, true) => {
+                return if self.sign() == Sign::Pos { Ordering::Greater } else { Ordering::Less };
+            },
+            (false, false) => {}
+        }
+
+        let (self_sign, numer, denom) = self.parts();
+
+        match (self_sign, sign) {
+            (Sign::Pos, Sign::Neg) => {
+                return Ordering::Greater;
+            },
+            (Sign::Neg, Sign::Pos) => {
+                return Ordering::Less;
+            },
+            _ => {}
+        }
+
+        let magnitude_ord = if *denom == BigUint::from(1_u8) {
+            numer.as_ref().cmp(magnitude)
+        } else {
+            numer.as_ref().cmp(&(magnitude * &*denom))
+        };
+
+        return match self_sign {
+            Sign::Pos => magnitude_ord,
+            Sign::Neg => magnitude_ord.reverse()
+        };
+    }
+}
+
+fn primitive_ref_parts<T: Into<i128> + Copy>(i: &T) -> (Sign, BigUint) {
+    return primitive_parts(*i);
+}
+
+/// Comparisons with plain integers (in both directions), so threshold checks like exp > 8 don't need a temporary Rational
+macro_rules! rational_integer_cmp {
+    ($($rhs:ty => $parts:ident),*) => {
+        $(
+            impl PartialEq<$rhs> for Rational {
+                fn eq(&self, other: &$rhs) -> bool {
+                    let (sign, magnitude) = $parts(other);
+                    return self.cmp_integer(sign, &magnitude) == Ordering::Equal;
+                }
+            }
+
+            impl PartialOrd<$rhs> for Rational {
+                fn partial_cmp(&self, other: &$rhs) -> Option<Ordering> {
+                    let (sign, magnitude) = $parts(other);
+                    return Some(self.cmp_integer(sign, &magnitude));
+                }
+            }
+
+            impl PartialEq<Rational> for $rhs {
+                fn eq(&self, other: &Rational) -> bool {
+                    return other == self;
+                }
+            }
+
+            impl PartialOrd<Rational> for $rhs {
+                fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+                    return other.partial_cmp(self).map(Ordering::reverse);
+                }
+            }
+        )*
+    };
+}
+
+rational_integer_cmp!(i32 => primitive_ref_parts, i64 => primitive_ref_parts, u32 => primitive_ref_parts, u64 => primitive_ref_parts, BigUint => biguint_parts, BigInt => bigint_parts);
+
 impl From<isize> for Rational {
     fn from(i: isize) -> Rational { 
-        return Rational { 
-            sign: if i.is_positive() { Sign::Pos } else { Sign::Neg }, 
-            numer: BigUint::from(i.unsigned_abs()), 
-            denom: BigUint::from(1 as u8) 
-        } 
+        return Rational::from(i as i128);
     }
 }
 
+/// (numerator, denominator), simplified like Rational::new (and so also panics on a zero denominator)
 impl From<(isize, isize)> for Rational {
     fn from(i: (isize, isize)) -> Rational { 
-        return Rational { 
-            sign: if i.0.is_positive() ^ i.1.is_positive() { Sign::Neg } else { Sign::Pos }, 
-            numer: BigUint::from(i.0.unsigned_abs()), 
-            denom: BigUint::from(i.1.unsigned_abs()) 
-        } 
+        let sign = if (i.0 < 0) ^ (i.1 < 0) { Sign::Neg } else { Sign::Pos };
+
+        return Rational::new(sign, BigUint::from(i.0.unsigned_abs()), BigUint::from(i.1.unsigned_abs()));
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Rational {
+        return Rational::zero();
+    }
+}
+/// Every finite float is a (possibly huge) integer times a power of two, so this is exact
+impl TryFrom<f64> for Rational {
+    type Error = RationalError;
+
+    fn try_from(x: f64) -> Result<Rational, RationalError> {
+        if !x.is_finite() {
+            return Err(RationalError::NotFinite);
+        }
+
+        let bits = x.to_bits();
+        let sign = if bits >> 63 == 1 { Sign::Neg } else { Sign::Pos };
+        let biased_exp = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1_u64 << 52) - 1);
+
+        // Subnormals don't have the implicit leading one, and share the exponent of the smallest normal
+        let (mantissa, exp) = if biased_exp == 0 {
+            (fraction, -1074_i64)
+        } else {
+            (fraction | (1_u64 << 52), biased_exp - 1075)
+        };
+
+        if mantissa == 0 {
+            return Ok(Rational::zero());
+        }
+
+        let mut numer = BigUint::from(mantissa);
+        let mut denom = BigUint::from(1_u8);
+        if exp >= 0 {
+            numer <<= exp as u64;
+        } else {
+            denom <<= exp.unsigned_abs();
+        }
+
+        return Ok(Rational::new(sign, numer, denom));
+    }
+}
+
+/// Every f32 is exactly representable as an f64
+impl TryFrom<f32> for Rational {
+    type Error = RationalError;
+
+    fn try_from(x: f32) -> Result<Rational, RationalError> {
+        return Rational::try_from(x as f64);
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(i: BigInt) -> Rational {
+        let sign = if i.sign() == num_bigint::Sign::Minus { Sign::Neg } else { Sign::Pos };
+
+        return Rational::from_parts(sign, i.magnitude().clone(), BigUint::from(1_u8));
+    }
+}
+
+impl From<BigUint> for Rational {
+    fn from(i: BigUint) -> Rational {
+        return Rational::from_parts(Sign::Pos, i, BigUint::from(1_u8));
+    }
+}
+
+impl From<u128> for Rational {
+    fn from(i: u128) -> Rational {
+        return Rational::from_wide(Sign::Pos, i, 1);
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(i: i128) -> Rational {
+        return Rational::from_wide(sign_of(i < 0), i.unsigned_abs(), 1);
     }
-}
\ No newline at end of file
+}
+
+/// (numerator, denominator, sign), simplified like Rational::new (and so also panics on a zero denominator)
+impl From<(BigUint, BigUint, Sign)> for Rational {
+    fn from(i: (BigUint, BigUint, Sign)) -> Rational {
+        return Rational::new(i.2, i.0, i.1);
+    }
+}
+
+/// The smaller integer types all go through i128/u128, these are also what lets a bare literal like Rational::from(3) pick an impl
+macro_rules! rational_from_primitive {
+    ($via:ty, $($t:ty),*) => {
+        $(
+            impl From<$t> for Rational {
+                fn from(i: $t) -> Rational {
+                    return Rational::from(i as $via);
+                }
+            }
+        )*
+    };
+}
+
+rational_from_primitive!(i128, i8, i16, i32, i64);
+rational_from_primitive!(u128, u8, u16, u32, u64, usize);
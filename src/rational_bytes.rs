@@ -0,0 +1,156 @@
+//! Compact binary encoding for Rational
+//! A sign byte (0 for positive, 1 for negative), then the numerator and then the denominator,
+//! Each as a LEB128 length followed by that many little-endian bytes (zero is an empty run of bytes)
+//! So small values like 1/3 take 5 bytes instead of the ~40 serde_json needs
+
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigUint;
+
+use crate::rational::{Rational, Sign};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeRationalError {
+    /// The bytes ran out in the middle of a value
+    UnexpectedEnd,
+    /// The sign byte wasn't 0 or 1
+    InvalidSign,
+    /// A length prefix didn't fit in a usize
+    InvalidLength,
+    ZeroDenominator,
+    /// from_bytes was given more than one value, use read_bytes to decode from a longer buffer
+    TrailingBytes
+}
+
+impl Display for DecodeRationalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            DecodeRationalError::UnexpectedEnd => {
+                return write!(f, "unexpected end of bytes while decoding rational");
+            },
+            DecodeRationalError::InvalidSign => {
+                return write!(f, "invalid sign byte in encoded rational");
+            },
+            DecodeRationalError::InvalidLength => {
+                return write!(f, "invalid length prefix in encoded rational");
+            },
+            DecodeRationalError::ZeroDenominator => {
+                return write!(f, "rational has a zero denominator");
+            },
+            DecodeRationalError::TrailingBytes => {
+                return write!(f, "trailing bytes after encoded rational");
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeRationalError {}
+
+pub(crate) fn write_length(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+
+        if len == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_biguint(out: &mut Vec<u8>, x: &BigUint) {
+    if *x == BigUint::ZERO {
+        write_length(out, 0);
+        return;
+    }
+
+    let bytes = x.to_bytes_le();
+    write_length(out, bytes.len());
+    out.extend_from_slice(&bytes);
+}
+
+/// Returns the length and how many bytes it took
+pub(crate) fn read_length(bytes: &[u8]) -> Result<(usize, usize), DecodeRationalError> {
+    let mut len: usize = 0;
+
+    for (i, byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        let value = (byte & 0x7f) as usize;
+
+        if shift >= usize::BITS || (value << shift) >> shift != value {
+            return Err(DecodeRationalError::InvalidLength);
+        }
+        len |= value << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok((len, i + 1));
+        }
+    }
+
+    return Err(DecodeRationalError::UnexpectedEnd);
+}
+
+/// Returns the value and how many bytes it took
+fn read_biguint(bytes: &[u8]) -> Result<(BigUint, usize), DecodeRationalError> {
+    let (len, prefix_len) = read_length(bytes)?;
+    let end = prefix_len.checked_add(len).ok_or(DecodeRationalError::InvalidLength)?;
+
+    if end > bytes.len() {
+        return Err(DecodeRationalError::UnexpectedEnd);
+    }
+
+    return Ok((BigUint::from_bytes_le(&bytes[prefix_len..end]), end));
+}
+
+impl Rational {
+    /// See the module docs for the format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(3 + (self.numer_bits() + self.denom_bits()).div_ceil(8) as usize);
+        self.write_bytes(&mut out);
+        return out;
+    }
+
+    /// Appends the encoding to out, for packing many values into one buffer
+    pub fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(match self.sign() {
+            Sign::Pos => 0,
+            Sign::Neg => 1
+        });
+        write_biguint(out, &self.numer());
+        write_biguint(out, &self.denom());
+    }
+
+    /// Decodes exactly one value, failing if there are bytes left over
+    pub fn from_bytes(bytes: &[u8]) -> Result<Rational, DecodeRationalError> {
+        let (a, used) = Rational::read_bytes(bytes)?;
+
+        if used != bytes.len() {
+            return Err(DecodeRationalError::TrailingBytes);
+        }
+
+        return Ok(a);
+    }
+
+    /// Decodes the value at the start of bytes, returning it and how many bytes it took (so the next one starts there)
+    /// The result is simplified like Rational::new
+    pub fn read_bytes(bytes: &[u8]) -> Result<(Rational, usize), DecodeRationalError> {
+        let sign = match bytes.first() {
+            Some(0) => Sign::Pos,
+            Some(1) => Sign::Neg,
+            Some(_) => {
+                return Err(DecodeRationalError::InvalidSign);
+            },
+            None => {
+                return Err(DecodeRationalError::UnexpectedEnd);
+            }
+        };
+
+        let (numer, numer_len) = read_biguint(&bytes[1..])?;
+        let (denom, denom_len) = read_biguint(&bytes[1 + numer_len..])?;
+
+        let a = Rational::try_new(sign, numer, denom).map_err(|_| DecodeRationalError::ZeroDenominator)?;
+
+        return Ok((a, 1 + numer_len + denom_len));
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::{AddAssign, SubAssign, MulAssign, Neg};
+use std::fmt::{Debug, Display, Formatter};
+
+use num_bigint::BigUint;
+
+use crate::rational::{Rational, RoundingMode};
+use crate::rational_range::RationalRange;
+
+/// The range [mid - rad, mid + rad], an alternative to RationalRange's endpoints
+/// Sums only touch the midpoints and radii separately, and rounding error can be pushed into the radius (see to_with_denominator),
+/// So long sums keep much smaller denominators than they would as endpoints
+#[derive(Clone, PartialEq, Eq)]
+pub struct CenteredRange {
+    pub mid: Rational,
+    /// Never negative
+    pub rad: Rational
+}
+
+impl CenteredRange {
+    /// Panics if rad is negative
+    pub fn new(mid: Rational, rad: Rational) -> CenteredRange {
+        if rad.is_negative() {
+            panic!("Attempted to create CenteredRange with a negative radius");
+        }
+
+        return CenteredRange { mid, rad };
+    }
+
+    pub fn min(&self) -> Rational {
+        return &self.mid - &self.rad;
+    }
+
+    pub fn max(&self) -> Rational {
+        return &self.mid + &self.rad;
+    }
+
+    pub fn contains(&self, x: &Rational) -> bool {
+        return (x - &self.mid).abs() <= self.rad;
+    }
+
+    /// Rounds the midpoint to the nearest multiple of 1/new_denom and the radius up to one, adding the rounding error of the midpoint to the radius
+    /// So the result still contains the original range
+    pub fn to_with_denominator(&mut self, new_denom: &BigUint) {
+        let original_mid = self.mid.clone();
+        self.mid.to_with_denominator(new_denom, RoundingMode::HalfEven);
+
+        self.rad += &(&self.mid - &original_mid).abs();
+        self.rad.to_with_denominator(new_denom, RoundingMode::Ceil);
+    }
+
+    /// Multiplies by k, the radius scales by |k|
+    pub fn scale(&mut self, k: &Rational) {
+        self.mid *= k;
+        self.rad *= &k.abs();
+    }
+}
+
+impl From<RationalRange> for CenteredRange {
+    fn from(range: RationalRange) -> CenteredRange {
+        let mut rad = range.width();
+        rad /= 2_u32;
+
+        return CenteredRange { mid: range.midpoint(), rad };
+    }
+}
+
+impl From<&RationalRange> for CenteredRange {
+    fn from(range: &RationalRange) -> CenteredRange {
+        return CenteredRange::from(range.clone());
+    }
+}
+
+impl From<CenteredRange> for RationalRange {
+    fn from(range: CenteredRange) -> RationalRange {
+        return RationalRange::from((range.min(), range.max()));
+    }
+}
+
+impl From<&CenteredRange> for RationalRange {
+    fn from(range: &CenteredRange) -> RationalRange {
+        return RationalRange::from((range.min(), range.max()));
+    }
+}
+
+/// The range containing just this value
+impl From<Rational> for CenteredRange {
+    fn from(x: Rational) -> CenteredRange {
+        return CenteredRange { mid: x, rad: Rational::zero() };
+    }
+}
+
+impl Debug for CenteredRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "[{:?} +- {:?}]", self.mid, self.rad);
+    }
+}
+
+impl Display for CenteredRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "[{} +- {}]", self.mid, self.rad);
+    }
+}
+
+impl AddAssign<&CenteredRange> for CenteredRange {
+    fn add_assign(&mut self, rhs: &CenteredRange) {
+        self.mid += &rhs.mid;
+        self.rad += &rhs.rad;
+    }
+}
+
+impl SubAssign<&CenteredRange> for CenteredRange {
+    fn sub_assign(&mut self, rhs: &CenteredRange) {
+        // The radii still add, as the uncertainty in rhs is just as big after negating it
+        self.mid -= &rhs.mid;
+        self.rad += &rhs.rad;
+    }
+}
+
+/// (a +- r)(b +- s) = ab + (a s + b r + r s) in the worst case, so the radius is |a| s + |b| r + r s
+/// This is a little wider than multiplying the endpoints when both ranges are wide, but never narrower than the true product
+impl MulAssign<&CenteredRange> for CenteredRange {
+    fn mul_assign(&mut self, rhs: &CenteredRange) {
+        let mut rad = self.mid.abs();
+        rad *= &rhs.rad;
+        rad += &(rhs.mid.abs() * &self.rad);
+        rad += &(&self.rad * &rhs.rad);
+
+        self.mid *= &rhs.mid;
+        self.rad = rad;
+    }
+}
+
+impl Neg for CenteredRange {
+    type Output = CenteredRange;
+
+    fn neg(mut self) -> CenteredRange {
+        self.mid.negate();
+        return self;
+    }
+}
+
+impl Neg for &CenteredRange {
+    type Output = CenteredRange;
+
+    fn neg(self) -> CenteredRange {
+        return -self.clone();
+    }
+}
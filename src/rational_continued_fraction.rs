@@ -0,0 +1,102 @@
+use num_bigint::BigUint;
+
+use crate::rational::{Rational, Sign};
+use crate::rational_range::RationalRange;
+
+impl Rational {
+    /// The coefficients [a0; a1, a2, ...] of the (finite) simple continued fraction of the magnitude
+    /// The last coefficient is never 1 (except for the value 1 itself), so the expansion is the shorter of the two possible ones
+    /// Negative values give the expansion of their magnitude, the sign has to be carried separately
+    pub fn to_continued_fraction(&self) -> Vec<BigUint> {
+        let mut coefficients = Vec::new();
+
+        let mut numer = self.numer().into_owned();
+        let mut denom = self.denom().into_owned();
+
+        // Just Euclid's algorithm, keeping the quotients
+        while denom != BigUint::ZERO {
+            coefficients.push(&numer / &denom);
+
+            let remainder = numer % &denom;
+            numer = denom;
+            denom = remainder;
+        }
+
+        return coefficients;
+    }
+
+    /// Builds the (non-negative) value of the simple continued fraction [a0; a1, a2, ...]
+    /// Panics if there are no coefficients, or if a zero coefficient after a0 would make the value divide by zero
+    pub fn from_continued_fraction(coefficients: &[BigUint]) -> Rational {
+        if coefficients.is_empty() {
+            panic!("Attempted to build a Rational from an empty continued fraction");
+        }
+
+        // Standard convergent recurrence, h(n) = a(n) * h(n-1) + h(n-2) and likewise for k, starting from h = 1/0 and k = 0/1
+        let mut h_prev = BigUint::ZERO;
+        let mut h = BigUint::from(1_u8);
+        let mut k_prev = BigUint::from(1_u8);
+        let mut k = BigUint::ZERO;
+
+        for a in coefficients {
+            let h_next = a * &h + &h_prev;
+            let k_next = a * &k + &k_prev;
+
+            h_prev = std::mem::replace(&mut h, h_next);
+            k_prev = std::mem::replace(&mut k, k_next);
+        }
+
+        return Rational::new(Sign::Pos, h, k);
+    }
+}
+
+/// The simplest rational in [a_numer/a_denom, b_numer/b_denom] for 0 <= a <= b, by walking down the Stern-Brocot tree one continued fraction term at a time
+fn simplest_in_positive(mut a_numer: BigUint, mut a_denom: BigUint, mut b_numer: BigUint, mut b_denom: BigUint) -> Rational {
+    let mut coefficients = Vec::new();
+
+    loop {
+        let a_floor = &a_numer / &a_denom;
+        let a_ceil = (&a_numer + &a_denom - 1_u8) / &a_denom;
+
+        if &a_ceil * &b_denom <= b_numer {
+            // There's an integer in the interval, and the smallest one is the simplest
+            coefficients.push(a_ceil);
+            break;
+        }
+
+        // Otherwise both ends share the integer part, so it's the next term and the rest comes from the reciprocals of the fractional parts
+        // (Note that the ends swap, as 1/x is decreasing)
+        let new_a_numer = b_denom.clone();
+        let new_a_denom = b_numer - &a_floor * &b_denom;
+        let new_b_numer = a_denom.clone();
+        let new_b_denom = a_numer - &a_floor * &a_denom;
+
+        coefficients.push(a_floor);
+
+        a_numer = new_a_numer;
+        a_denom = new_a_denom;
+        b_numer = new_b_numer;
+        b_denom = new_b_denom;
+    }
+
+    return Rational::from_continued_fraction(&coefficients);
+}
+
+impl Rational {
+    /// The rational with the smallest denominator inside the (closed) range, ties going to the one closest to zero
+    /// Useful for picking a bound that's as cheap as possible to compute with while still being inside an enclosure
+    pub fn simplest_in(range: &RationalRange) -> Rational {
+        if !range.min.is_negative() {
+            return simplest_in_positive(range.min.numer().into_owned(), range.min.denom().into_owned(), range.max.numer().into_owned(), range.max.denom().into_owned());
+        }
+        if range.max.is_negative() {
+            // Mirror the range onto the positive side and back
+            let mut a = simplest_in_positive(range.max.numer().into_owned(), range.max.denom().into_owned(), range.min.numer().into_owned(), range.min.denom().into_owned());
+            a.negate();
+            return a;
+        }
+
+        // The range contains zero
+        return Rational::new(Sign::Pos, BigUint::ZERO, BigUint::from(1_u8));
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Sub, Mul, Neg, AddAssign, SubAssign, MulAssign};
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::rational::{Rational, RationalError, RoundingMode, Sign};
+
+/// A rational whose denominator is a power of two, stored as mantissa * 2^exp
+/// Adding and multiplying these never needs a gcd or lcm, and rounding to a fixed number of fractional bits is just a shift,
+/// Which makes them a cheap representation for precision-limited bounds
+/// The mantissa is always odd (or zero with an exp of 0), so every value has exactly one representation
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Dyadic {
+    mantissa: BigInt,
+    exp: i64
+}
+
+fn is_negative_bigint(i: &BigInt) -> bool {
+    return i.sign() == num_bigint::Sign::Minus;
+}
+
+impl Dyadic {
+    pub fn new(mantissa: BigInt, exp: i64) -> Dyadic {
+        let mut a = Dyadic { mantissa, exp };
+        a.normalize();
+        return a;
+    }
+
+    pub fn zero() -> Dyadic {
+        return Dyadic { mantissa: BigInt::ZERO, exp: 0 };
+    }
+
+    pub fn is_zero(&self) -> bool {
+        return self.mantissa == BigInt::ZERO;
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        return &self.mantissa;
+    }
+
+    pub fn exp(&self) -> i64 {
+        return self.exp;
+    }
+
+    /// Moves trailing zero bits of the mantissa into the exponent
+    fn normalize(&mut self) {
+        match self.mantissa.trailing_zeros() {
+            Some(zeros) => {
+                self.mantissa >>= zeros;
+                self.exp += zeros as i64;
+            },
+            None => {
+                self.exp = 0;
+            }
+        }
+    }
+
+    /// Rounds onto the grid of multiples of 2^-frac_bits (frac_bits can be negative to round to multiples of 2, 4, ...)
+    pub fn round_to_frac_bits(&self, frac_bits: i64, mode: RoundingMode) -> Dyadic {
+        let target_exp = -frac_bits;
+        if self.exp >= target_exp {
+            return self.clone();
+        }
+
+        let shift = (target_exp - self.exp) as u64;
+        let negative = is_negative_bigint(&self.mantissa);
+        let magnitude = mode.round_quotient(self.mantissa.magnitude(), &(BigUint::from(1_u8) << shift), negative);
+
+        let mantissa = if negative { -BigInt::from(magnitude) } else { BigInt::from(magnitude) };
+
+        return Dyadic::new(mantissa, target_exp);
+    }
+
+    /// The multiple of 2^-frac_bits nearest to x in the direction given by mode
+    pub fn from_rational(x: &Rational, frac_bits: i64, mode: RoundingMode) -> Dyadic {
+        let (numer, denom) = if frac_bits >= 0 {
+            (&*x.numer() << frac_bits as u64, x.denom().into_owned())
+        } else {
+            (x.numer().into_owned(), &*x.denom() << frac_bits.unsigned_abs())
+        };
+
+        let magnitude = mode.round_quotient(&numer, &denom, x.is_negative());
+        let mantissa = if x.is_negative() { -BigInt::from(magnitude) } else { BigInt::from(magnitude) };
+
+        return Dyadic::new(mantissa, -frac_bits);
+    }
+
+    /// Some(x) if x is dyadic (its simplified denominator is a power of two)
+    pub fn from_rational_exact(x: &Rational) -> Option<Dyadic> {
+        let mut a = x.clone();
+        a.simplify();
+
+        if a.denom().count_ones() != 1 {
+            return None;
+        }
+
+        let frac_bits = a.denom().trailing_zeros().unwrap() as i64;
+
+        return Some(Dyadic::from_rational(&a, frac_bits, RoundingMode::TowardZero));
+    }
+
+    pub fn to_rational(&self) -> Rational {
+        let sign = if is_negative_bigint(&self.mantissa) { Sign::Neg } else { Sign::Pos };
+        let magnitude = self.mantissa.magnitude().clone();
+
+        if self.exp >= 0 {
+            return Rational::new(sign, magnitude << self.exp as u64, BigUint::from(1_u8));
+        }
+        return Rational::new(sign, magnitude, BigUint::from(1_u8) << self.exp.unsigned_abs());
+    }
+
+    pub fn to_f64_round(&self, mode: RoundingMode) -> f64 {
+        return self.to_rational().to_f64_round(mode);
+    }
+}
+
+impl From<&Dyadic> for Rational {
+    fn from(x: &Dyadic) -> Rational {
+        return x.to_rational();
+    }
+}
+
+impl From<Dyadic> for Rational {
+    fn from(x: Dyadic) -> Rational {
+        return x.to_rational();
+    }
+}
+
+/// Every finite float is dyadic, so this is exact
+impl TryFrom<f64> for Dyadic {
+    type Error = RationalError;
+
+    fn try_from(x: f64) -> Result<Dyadic, RationalError> {
+        let a = Rational::try_from(x)?;
+        return Ok(Dyadic::from_rational_exact(&a).unwrap());
+    }
+}
+
+impl Debug for Dyadic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "({}*2^{})", self.mantissa, self.exp);
+    }
+}
+
+impl Display for Dyadic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return Display::fmt(&self.to_rational(), f);
+    }
+}
+
+/// Both mantissas shifted onto the smaller of the two exponents
+fn aligned(a: &Dyadic, b: &Dyadic) -> (BigInt, BigInt, i64) {
+    let exp = a.exp.min(b.exp);
+
+    return (&a.mantissa << (a.exp - exp) as u64, &b.mantissa << (b.exp - exp) as u64, exp);
+}
+
+impl AddAssign<&Dyadic> for Dyadic {
+    fn add_assign(&mut self, rhs: &Dyadic) {
+        let (a, b, exp) = aligned(self, rhs);
+        *self = Dyadic::new(a + b, exp);
+    }
+}
+
+impl SubAssign<&Dyadic> for Dyadic {
+    fn sub_assign(&mut self, rhs: &Dyadic) {
+        let (a, b, exp) = aligned(self, rhs);
+        *self = Dyadic::new(a - b, exp);
+    }
+}
+
+impl MulAssign<&Dyadic> for Dyadic {
+    fn mul_assign(&mut self, rhs: &Dyadic) {
+        // The product of two odd mantissas is odd, so this is already normalized (unless one of them is zero)
+        self.mantissa *= &rhs.mantissa;
+        self.exp += rhs.exp;
+        self.normalize();
+    }
+}
+
+impl Add<&Dyadic> for &Dyadic {
+    type Output = Dyadic;
+
+    fn add(self, rhs: &Dyadic) -> Dyadic {
+        let mut a = self.clone();
+        a += rhs;
+        return a;
+    }
+}
+
+impl Sub<&Dyadic> for &Dyadic {
+    type Output = Dyadic;
+
+    fn sub(self, rhs: &Dyadic) -> Dyadic {
+        let mut a = self.clone();
+        a -= rhs;
+        return a;
+    }
+}
+
+impl Mul<&Dyadic> for &Dyadic {
+    type Output = Dyadic;
+
+    fn mul(self, rhs: &Dyadic) -> Dyadic {
+        let mut a = self.clone();
+        a *= rhs;
+        return a;
+    }
+}
+
+impl Neg for Dyadic {
+    type Output = Dyadic;
+
+    fn neg(mut self) -> Dyadic {
+        self.mantissa = -self.mantissa;
+        return self;
+    }
+}
+
+impl Neg for &Dyadic {
+    type Output = Dyadic;
+
+    fn neg(self) -> Dyadic {
+        return -self.clone();
+    }
+}
+
+impl PartialOrd for Dyadic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for Dyadic {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, _) = aligned(self, other);
+        return a.cmp(&b);
+    }
+}
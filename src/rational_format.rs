@@ -0,0 +1,119 @@
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigUint;
+
+use crate::rational::{gcd, Rational, RoundingMode};
+
+/// Inserts a decimal point so that the last `digits` digits of the magnitude end up after it, padding with zeros where needed
+fn place_decimal_point(magnitude: &BigUint, digits: usize) -> String {
+    let mut s = magnitude.to_str_radix(10);
+
+    if digits == 0 {
+        return s;
+    }
+
+    if s.len() <= digits {
+        s.insert_str(0, &"0".repeat(digits + 1 - s.len()));
+    }
+    s.insert(s.len() - digits, '.');
+
+    return s;
+}
+
+impl Rational {
+    /// Exact decimal expansion with `digits` digits after the decimal point, with the last digit rounded according to mode
+    /// Unlike converting to a float this keeps every requested digit, no matter how big the numerator and denominator get
+    pub fn to_decimal_string(&self, digits: usize, mode: RoundingMode) -> String {
+        let scaled_numer = &*self.numer() * BigUint::from(10_u8).pow(digits as u32);
+        let magnitude = mode.round_quotient(&scaled_numer, &self.denom(), self.is_negative());
+
+        let mut s = place_decimal_point(&magnitude, digits);
+
+        // Something like -0.001 rounded towards zero to 2 digits is just 0.00
+        if self.is_negative() && magnitude != BigUint::ZERO {
+            s.insert(0, '-');
+        }
+
+        return s;
+    }
+
+    /// The exact decimal expansion, with the repeating part in parentheses, e.g. 1/7 is "0.(142857)" and 1/6 is "0.1(6)"
+    /// Terminating expansions have no parentheses at all, e.g. 1/4 is "0.25"
+    /// Note that the period can be as long as the denominator, so this is meant for displaying reasonably sized values
+    pub fn to_repeating_decimal_string(&self) -> String {
+        let (self_numer, self_denom) = (self.numer(), self.denom());
+        let gcd = gcd(&self_numer, &self_denom);
+        let numer = &*self_numer / &gcd;
+        let denom = &*self_denom / &gcd;
+
+        let mut s = String::new();
+        if self.is_negative() {
+            s.push('-');
+        }
+        s.push_str(&(&numer / &denom).to_str_radix(10));
+
+        let mut remainder = numer % &denom;
+        if remainder == BigUint::ZERO {
+            return s;
+        }
+
+        // The digits before the period come from the factors of 2 and 5 in the denominator, and everything after that repeats
+        let twos = denom.trailing_zeros().unwrap_or(0);
+        let mut fives = 0_u64;
+        let mut rest = denom.clone();
+        while &rest % 5_u8 == BigUint::ZERO {
+            rest /= 5_u8;
+            fives += 1;
+        }
+        let preperiod_length = twos.max(fives);
+
+        s.push('.');
+
+        for _ in 0..preperiod_length {
+            remainder *= 10_u8;
+            s.push_str(&(&remainder / &denom).to_str_radix(10));
+            remainder %= &denom;
+        }
+
+        if remainder == BigUint::ZERO {
+            return s;
+        }
+
+        // Since the rest of the denominator is coprime to 10, the long division comes back around to exactly this remainder
+        let period_start = remainder.clone();
+        s.push('(');
+        loop {
+            remainder *= 10_u8;
+            s.push_str(&(&remainder / &denom).to_str_radix(10));
+            remainder %= &denom;
+
+            if remainder == period_start {
+                break;
+            }
+        }
+        s.push(')');
+
+        return s;
+    }
+}
+
+/// Plain "numer/denom" (or just the numerator for integers), without the parentheses Debug uses
+/// Width, fill, alignment, '+' and '0' all work like they do for integers, and the alternate form {:#} writes a mixed number like "-3 16/113"
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let (self_numer, self_denom) = (self.numer(), self.denom());
+        let gcd = gcd(&self_numer, &self_denom);
+        let numer = &*self_numer / &gcd;
+        let denom = &*self_denom / &gcd;
+
+        let body = if denom == BigUint::from(1_u8) {
+            numer.to_str_radix(10)
+        } else if f.alternate() && numer > denom {
+            format!("{} {}/{}", &numer / &denom, &numer % &denom, denom)
+        } else {
+            format!("{}/{}", numer, denom)
+        };
+
+        return f.pad_integral(!self.is_negative(), "", &body);
+    }
+}
@@ -0,0 +1,47 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+
+use crate::rational::{Rational, Sign};
+
+fn sign_of(i: &BigInt) -> Sign {
+    if i.sign() == num_bigint::Sign::Minus {
+        return Sign::Neg;
+    }
+    return Sign::Pos;
+}
+
+/// BigRational normally keeps the sign on the numerator, but one built with new_raw can have it on either (or both)
+impl From<BigRational> for Rational {
+    fn from(ratio: BigRational) -> Rational {
+        let (numer, denom) = ratio.into_raw();
+        let sign = sign_of(&numer) ^ sign_of(&denom);
+
+        return Rational::new(sign, numer.magnitude().clone(), denom.magnitude().clone());
+    }
+}
+
+impl From<Rational> for BigRational {
+    fn from(rational: Rational) -> BigRational {
+        let (sign, numer, denom) = rational.into_parts();
+
+        let numer = match sign {
+            Sign::Pos => BigInt::from(numer),
+            Sign::Neg => -BigInt::from(numer)
+        };
+
+        return BigRational::new(numer, BigInt::from(denom));
+    }
+}
+
+impl From<&Rational> for BigRational {
+    fn from(rational: &Rational) -> BigRational {
+        return BigRational::from(rational.clone());
+    }
+}
+
+impl From<&BigRational> for Rational {
+    fn from(ratio: &BigRational) -> Rational {
+        return Rational::from(ratio.clone());
+    }
+}
+
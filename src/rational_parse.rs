@@ -0,0 +1,144 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use num_bigint::BigUint;
+
+use crate::rational::{Rational, Sign};
+use crate::rational_range::RationalRange;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRationalError {
+    /// The string (or one side of the '/') had nothing to parse
+    Empty,
+    /// A character that isn't a digit, or a sign or decimal point in the wrong place
+    InvalidDigit,
+    ZeroDenominator
+}
+
+impl Display for ParseRationalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ParseRationalError::Empty => {
+                return write!(f, "cannot parse rational from empty string");
+            },
+            ParseRationalError::InvalidDigit => {
+                return write!(f, "invalid digit found in string");
+            },
+            ParseRationalError::ZeroDenominator => {
+                return write!(f, "rational has a zero denominator");
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseRationalError {}
+
+fn parse_digits(s: &str) -> Result<BigUint, ParseRationalError> {
+    if !s.bytes().all(|x| x.is_ascii_digit()) {
+        return Err(ParseRationalError::InvalidDigit);
+    }
+
+    return BigUint::parse_bytes(s.as_bytes(), 10).ok_or(ParseRationalError::InvalidDigit);
+}
+
+/// Parses an unsigned integer or decimal literal like "12", "3.25" or ".5" into (numerator, denominator)
+pub(crate) fn parse_unsigned_decimal(s: &str) -> Result<(BigUint, BigUint), ParseRationalError> {
+    if s.is_empty() {
+        return Err(ParseRationalError::Empty);
+    }
+
+    match s.split_once('.') {
+        None => {
+            return Ok((parse_digits(s)?, BigUint::from(1_u8)));
+        },
+        Some((int_part, frac_part)) => {
+            if int_part.is_empty() && frac_part.is_empty() {
+                return Err(ParseRationalError::InvalidDigit);
+            }
+
+            // "3.25" is 325/100, so the digits are just joined together
+            let mut digits = String::with_capacity(int_part.len() + frac_part.len());
+            digits.push_str(int_part);
+            digits.push_str(frac_part);
+
+            let numer = parse_digits(&digits)?;
+            let denom = BigUint::from(10_u8).pow(frac_part.len() as u32);
+
+            return Ok((numer, denom));
+        }
+    }
+}
+
+/// Splits off a leading '+' or '-'
+pub(crate) fn parse_sign(s: &str) -> (Sign, &str) {
+    if let Some(rest) = s.strip_prefix('-') {
+        return (Sign::Neg, rest);
+    }
+    if let Some(rest) = s.strip_prefix('+') {
+        return (Sign::Pos, rest);
+    }
+
+    return (Sign::Pos, s);
+}
+
+/// Accepts an optional sign followed by an integer or decimal, optionally followed by '/' and an unsigned integer or decimal
+/// E.g. "-355/113", "3.25", "+7" or "1.5/4"
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    fn from_str(s: &str) -> Result<Rational, ParseRationalError> {
+        if s.is_empty() {
+            return Err(ParseRationalError::Empty);
+        }
+
+        let (sign, s) = parse_sign(s);
+
+        let (numer_part, denom_part) = match s.split_once('/') {
+            Some((numer_part, denom_part)) => (numer_part, Some(denom_part)),
+            None => (s, None)
+        };
+
+        let (mut numer, mut denom) = parse_unsigned_decimal(numer_part)?;
+
+        if let Some(denom_part) = denom_part {
+            let (denom_numer, denom_denom) = parse_unsigned_decimal(denom_part)?;
+
+            if denom_numer == BigUint::ZERO {
+                return Err(ParseRationalError::ZeroDenominator);
+            }
+
+            // (a/b) / (c/d) = (a*d) / (b*c)
+            numer *= denom_denom;
+            denom *= denom_numer;
+        }
+
+        let sign = if numer == BigUint::ZERO { Sign::Pos } else { sign };
+
+        return Ok(Rational::new(sign, numer, denom));
+    }
+}
+
+impl RationalRange {
+    /// Reads a decimal literal as a measurement, so the last digit is only known to within half a unit
+    /// "3.14" is [3.135, 3.145] and "12" is [11.5, 12.5], use from_decimal_str_exact for the literal value alone
+    pub fn from_decimal_str(s: &str) -> Result<RationalRange, ParseRationalError> {
+        let (sign, s) = parse_sign(s);
+        let (numer, denom) = parse_unsigned_decimal(s)?;
+
+        let mut half_ulp = Rational::new(Sign::Pos, BigUint::from(1_u8), denom.clone());
+        half_ulp /= 2_u32;
+
+        let mut range = RationalRange::from(Rational::new(sign, numer, denom));
+        range.widen(&half_ulp);
+
+        return Ok(range);
+    }
+
+    /// Reads a decimal literal as the single point it spells out, "3.14" is [3.14, 3.14]
+    pub fn from_decimal_str_exact(s: &str) -> Result<RationalRange, ParseRationalError> {
+        let (sign, s) = parse_sign(s);
+        let (numer, denom) = parse_unsigned_decimal(s)?;
+
+        return Ok(RationalRange::from(Rational::new(sign, numer, denom)));
+    }
+}
@@ -0,0 +1,441 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{AddAssign, MulAssign, Neg, SubAssign};
+
+use num_bigint::BigUint;
+
+use crate::rational::Rational;
+use crate::rational_range::RationalRange;
+
+/// c_0 + c_1*x + c_2*x^2 + ... with rational coefficients, stored densely from the constant term up
+/// The last coefficient is never zero (the zero polynomial has none at all), so equal polynomials are always equal as structs
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RationalPolynomial {
+    coefficients: Vec<Rational>
+}
+
+impl RationalPolynomial {
+    /// coefficients[i] is the coefficient of x^i, trailing zeros are dropped
+    pub fn new(coefficients: Vec<Rational>) -> RationalPolynomial {
+        let mut a = RationalPolynomial { coefficients };
+        a.trim();
+
+        return a;
+    }
+
+    pub fn zero() -> RationalPolynomial {
+        return RationalPolynomial { coefficients: Vec::new() };
+    }
+
+    pub fn constant(c: Rational) -> RationalPolynomial {
+        return RationalPolynomial::new(vec![c]);
+    }
+
+    /// coefficient * x^degree
+    pub fn monomial(coefficient: Rational, degree: usize) -> RationalPolynomial {
+        let mut coefficients = vec![Rational::zero(); degree];
+        coefficients.push(coefficient);
+
+        return RationalPolynomial::new(coefficients);
+    }
+
+    fn trim(&mut self) {
+        while let Some(last) = self.coefficients.last() && last.is_zero() {
+            self.coefficients.pop();
+        }
+    }
+
+    /// From the constant term up, empty for the zero polynomial
+    pub fn coefficients(&self) -> &[Rational] {
+        return &self.coefficients;
+    }
+
+    /// The coefficient of x^i, zero past the degree
+    pub fn coefficient(&self, i: usize) -> Rational {
+        return self.coefficients.get(i).cloned().unwrap_or_else(Rational::zero);
+    }
+
+    /// None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        return self.coefficients.len().checked_sub(1);
+    }
+
+    /// None for the zero polynomial
+    pub fn leading_coefficient(&self) -> Option<&Rational> {
+        return self.coefficients.last();
+    }
+
+    pub fn is_zero(&self) -> bool {
+        return self.coefficients.is_empty();
+    }
+
+    /// The value at x, by Horner's rule
+    pub fn evaluate(&self, x: &Rational) -> Rational {
+        let mut value = Rational::zero();
+        for c in self.coefficients.iter().rev() {
+            value *= x;
+            value += c;
+        }
+
+        return value;
+    }
+
+    pub fn scale(&mut self, k: &Rational) {
+        for c in self.coefficients.iter_mut() {
+            *c *= k;
+        }
+        self.trim();
+    }
+
+    pub fn derivative(&self) -> RationalPolynomial {
+        let coefficients = self.coefficients.iter().enumerate().skip(1).map(|(i, c)| c * Rational::from(i as i128)).collect();
+        return RationalPolynomial::new(coefficients);
+    }
+
+    /// self^exp by repeated squaring, anything to the 0 (zero included) is 1
+    pub fn pow(&self, mut exp: u32) -> RationalPolynomial {
+        let mut result = RationalPolynomial::constant(Rational::one());
+        let mut base = self.clone();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                let square = base.clone();
+                base *= &square;
+            }
+        }
+
+        return result;
+    }
+
+    /// self divided by its leading coefficient, the zero polynomial stays zero
+    pub fn monic(&self) -> RationalPolynomial {
+        let mut a = self.clone();
+        if let Some(leading) = self.leading_coefficient() {
+            let mut inverse = leading.clone();
+            inverse.invert();
+            a.scale(&inverse);
+        }
+
+        return a;
+    }
+
+    /// (quotient, remainder) with self = quotient * divisor + remainder, the remainder having a lower degree than divisor
+    /// Panics if divisor is zero
+    pub fn div_rem(&self, divisor: &RationalPolynomial) -> (RationalPolynomial, RationalPolynomial) {
+        let (Some(divisor_degree), Some(divisor_leading)) = (divisor.degree(), divisor.leading_coefficient()) else {
+            panic!("Attempted to divide a RationalPolynomial by zero");
+        };
+
+        let mut remainder = self.clone();
+        let mut quotient = vec![Rational::zero(); self.coefficients.len().saturating_sub(divisor_degree)];
+
+        while let Some(degree) = remainder.degree() && degree >= divisor_degree {
+            let shift = degree - divisor_degree;
+            let c = &remainder.coefficients[degree] / divisor_leading;
+
+            for (i, x) in divisor.coefficients.iter().enumerate() {
+                remainder.coefficients[shift + i] -= &(&c * x);
+            }
+            // The leading term cancels exactly, so this always lowers the degree
+            remainder.trim();
+
+            quotient[shift] = c;
+        }
+
+        return (RationalPolynomial::new(quotient), remainder);
+    }
+
+    /// The monic greatest common divisor by Euclid's algorithm, only zero when both are
+    pub fn gcd(&self, other: &RationalPolynomial) -> RationalPolynomial {
+        let mut a = self.clone();
+        let mut b = other.clone();
+
+        while !b.is_zero() {
+            let remainder = a.div_rem(&b).1;
+            a = b;
+            b = remainder;
+        }
+
+        return a.monic();
+    }
+
+    /// The resultant, which is zero exactly when the two have a common root (counting complex ones) or either is zero
+    /// Worked out by Euclid's algorithm as well, using res(f, g) = (-1)^(deg f deg g) * lc(g)^(deg f - deg r) * res(g, r) for r = f mod g
+    /// The resultant of two non-zero constants is 1
+    pub fn resultant(&self, other: &RationalPolynomial) -> Rational {
+        let mut f = self.clone();
+        let mut g = other.clone();
+        let mut result = Rational::one();
+
+        loop {
+            let (Some(m), Some(n), Some(leading)) = (f.degree(), g.degree(), g.leading_coefficient()) else {
+                return Rational::zero();
+            };
+
+            if n == 0 {
+                // res(f, c) = c^deg f
+                let mut power = leading.clone();
+                power.powi(&BigUint::from(m));
+                return result * power;
+            }
+
+            let remainder = f.div_rem(&g).1;
+            let Some(k) = remainder.degree() else {
+                // g divides f, and isn't constant, so they share a root
+                return Rational::zero();
+            };
+
+            if m % 2 == 1 && n % 2 == 1 {
+                result.negate();
+            }
+            let mut power = leading.clone();
+            power.powi(&BigUint::from(m - k));
+            result *= &power;
+
+            f = g;
+            g = remainder;
+        }
+    }
+
+    /// self divided by gcd(self, self'), which has the same roots but each only once, made monic
+    pub fn square_free(&self) -> RationalPolynomial {
+        let repeated = self.gcd(&self.derivative());
+        if repeated.is_zero() {
+            return RationalPolynomial::zero();
+        }
+
+        return self.div_rem(&repeated).0.monic();
+    }
+
+    /// The Sturm sequence of self, which should be square free (see square_free)
+    /// p, p', and then the negated remainders of dividing each by the next until they run out
+    pub fn sturm_sequence(&self) -> Vec<RationalPolynomial> {
+        let mut sequence = vec![self.clone(), self.derivative()];
+
+        while let [.., a, b] = sequence.as_slice() && !b.is_zero() {
+            let remainder = -a.div_rem(b).1;
+            sequence.push(remainder);
+        }
+        sequence.pop();
+
+        return sequence;
+    }
+
+    /// A bound on the absolute value of every root (1 + the biggest |c_i / c_n|), so all of them are strictly between -bound and bound
+    /// Panics for the zero polynomial, which has every number as a root
+    pub fn root_bound(&self) -> Rational {
+        let Some(leading) = self.leading_coefficient() else {
+            panic!("Attempted to bound the roots of the zero RationalPolynomial");
+        };
+
+        let biggest = self.coefficients.iter().map(|c| (c / leading).abs()).max().unwrap();
+        return biggest + Rational::one();
+    }
+
+    /// The number of distinct real roots, panics for the zero polynomial
+    pub fn count_real_roots(&self) -> usize {
+        let square_free = self.square_free();
+        let bound = self.root_bound();
+        let sturm = square_free.sturm_sequence();
+
+        return sign_changes(&sturm, &-&bound) - sign_changes(&sturm, &bound);
+    }
+
+    /// An enclosure of the index-th smallest distinct real root (counting from 0) no wider than max_width, or None if there aren't that many
+    /// The root is found by bisecting with the Sturm sequence until it's the only one left in the interval, and from then on by the sign of self alone
+    /// The enclosure is a single point when a midpoint happens to hit the root exactly
+    pub fn real_root(&self, index: usize, max_width: &Rational) -> Option<RationalRange> {
+        if self.is_zero() {
+            return None;
+        }
+
+        let square_free = self.square_free();
+        let sturm = square_free.sturm_sequence();
+        let bound = self.root_bound();
+
+        // The number of roots in (lo, x], the root is in (lo, hi] as long as roots_up_to(lo) <= index < roots_up_to(hi)
+        let mut lo = -&bound;
+        let mut hi = bound;
+        let below_lo = sign_changes(&sturm, &lo);
+        let roots_up_to = |x: &Rational| below_lo - sign_changes(&sturm, x);
+
+        let mut count_lo = 0;
+        let mut count_hi = roots_up_to(&hi);
+        if count_hi <= index {
+            return None;
+        }
+
+        while &hi - &lo > *max_width {
+            let mid = (&lo + &hi) / Rational::from(2);
+
+            if count_hi - count_lo > 1 {
+                let count_mid = roots_up_to(&mid);
+                if count_mid > index {
+                    hi = mid;
+                    count_hi = count_mid;
+                } else {
+                    lo = mid;
+                    count_lo = count_mid;
+                }
+                continue;
+            }
+
+            // Only the root is left in (lo, hi], so square_free changes sign there and nowhere else
+            let value_hi = square_free.evaluate(&hi);
+            if value_hi.is_zero() {
+                return Some(RationalRange::from(hi));
+            }
+            let value_mid = square_free.evaluate(&mid);
+            if value_mid.is_zero() {
+                return Some(RationalRange::from(mid));
+            }
+
+            if value_mid.is_negative() == value_hi.is_negative() {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        return Some(RationalRange::from((lo, hi)));
+    }
+}
+
+/// The number of sign changes along the Sturm sequence at x, skipping zeros
+/// Between two points that aren't roots, the difference of these is the number of distinct roots in between (Sturm's theorem)
+/// At a root it's the same as just past it, so it also counts the roots in (a, b] for any a < b
+pub(crate) fn sign_changes(sturm: &[RationalPolynomial], x: &Rational) -> usize {
+    let mut changes = 0;
+    let mut last_negative = None;
+
+    for p in sturm {
+        let value = p.evaluate(x);
+        if value.is_zero() {
+            continue;
+        }
+
+        if let Some(last_negative) = last_negative && last_negative != value.is_negative() {
+            changes += 1;
+        }
+        last_negative = Some(value.is_negative());
+    }
+
+    return changes;
+}
+
+impl Debug for RationalPolynomial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "{:?}", self.coefficients);
+    }
+}
+
+/// Highest power first in x, like 3*x^2 - x + 1/2
+impl Display for RationalPolynomial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut first = true;
+        for (i, c) in self.coefficients.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+
+            match (first, c.is_negative()) {
+                (true, true) => {
+                    write!(f, "-")?;
+                },
+                (true, false) => {},
+                (false, true) => {
+                    write!(f, " - ")?;
+                },
+                (false, false) => {
+                    write!(f, " + ")?;
+                }
+            }
+            first = false;
+
+            let magnitude = c.abs();
+            if i == 0 {
+                write!(f, "{}", magnitude)?;
+                continue;
+            }
+            if !magnitude.is_one() {
+                write!(f, "{}*", magnitude)?;
+            }
+            if i == 1 {
+                write!(f, "x")?;
+            } else {
+                write!(f, "x^{}", i)?;
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl AddAssign<&RationalPolynomial> for RationalPolynomial {
+    fn add_assign(&mut self, rhs: &RationalPolynomial) {
+        if self.coefficients.len() < rhs.coefficients.len() {
+            self.coefficients.resize(rhs.coefficients.len(), Rational::zero());
+        }
+
+        for (c, x) in self.coefficients.iter_mut().zip(&rhs.coefficients) {
+            *c += x;
+        }
+        self.trim();
+    }
+}
+
+impl SubAssign<&RationalPolynomial> for RationalPolynomial {
+    fn sub_assign(&mut self, rhs: &RationalPolynomial) {
+        if self.coefficients.len() < rhs.coefficients.len() {
+            self.coefficients.resize(rhs.coefficients.len(), Rational::zero());
+        }
+
+        for (c, x) in self.coefficients.iter_mut().zip(&rhs.coefficients) {
+            *c -= x;
+        }
+        self.trim();
+    }
+}
+
+impl MulAssign<&RationalPolynomial> for RationalPolynomial {
+    fn mul_assign(&mut self, rhs: &RationalPolynomial) {
+        if self.is_zero() || rhs.is_zero() {
+            self.coefficients.clear();
+            return;
+        }
+
+        let mut product = vec![Rational::zero(); self.coefficients.len() + rhs.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in rhs.coefficients.iter().enumerate() {
+                product[i + j] += &(a * b);
+            }
+        }
+
+        // Rationals have no zero divisors, so the leading coefficient can't vanish
+        self.coefficients = product;
+    }
+}
+
+impl Neg for RationalPolynomial {
+    type Output = RationalPolynomial;
+
+    fn neg(mut self) -> RationalPolynomial {
+        self.coefficients.iter_mut().for_each(Rational::negate);
+        return self;
+    }
+}
+
+impl Neg for &RationalPolynomial {
+    type Output = RationalPolynomial;
+
+    fn neg(self) -> RationalPolynomial {
+        return -self.clone();
+    }
+}
@@ -0,0 +1,78 @@
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+use rand::distributions::Distribution;
+
+use crate::rational::{Rational, RoundingMode, Sign};
+use crate::rational_range::RationalRange;
+
+/// Uniform over the multiples of 1/denom inside a range, which for a large enough denom is a good stand-in for a uniform real in the range
+/// Sampled values have a denominator dividing denom (they're simplified, so it can be smaller)
+pub struct RationalUniform {
+    denom: Rational,
+    /// The grid points are (low + i)/denom for i in 0..=span
+    low: Rational,
+    span: BigUint
+}
+
+impl RationalUniform {
+    /// Panics if denom is zero or if the range is too narrow to contain any multiple of 1/denom
+    pub fn new(range: &RationalRange, denom: &BigUint) -> RationalUniform {
+        if *denom == BigUint::ZERO {
+            panic!("RationalUniform with a zero denominator");
+        }
+
+        let denom_rational = Rational::from(denom.clone());
+        let mut min = range.min.clone();
+        min *= &denom_rational;
+        let mut max = range.max.clone();
+        max *= &denom_rational;
+
+        let low = min.round_to_integer(RoundingMode::Ceil);
+        let high = max.round_to_integer(RoundingMode::Floor);
+
+        if low > high {
+            panic!("RationalUniform range contains no multiple of 1/denom");
+        }
+
+        let span = (high - &low).magnitude().clone();
+
+        return RationalUniform {
+            denom: denom_rational,
+            low: Rational::from(low),
+            span
+        };
+    }
+}
+
+impl Distribution<Rational> for RationalUniform {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Rational {
+        let mut a = Rational::from(rng.gen_biguint_below(&(&self.span + 1_u8)));
+        a += &self.low;
+        a /= &self.denom;
+
+        return a;
+    }
+}
+
+impl Rational {
+    /// A uniformly distributed multiple of 1/denom inside the range, see RationalUniform (which is cheaper for drawing many values)
+    pub fn random_in<R: Rng + ?Sized>(rng: &mut R, range: &RationalRange, denom: &BigUint) -> Rational {
+        return RationalUniform::new(range, denom).sample(rng);
+    }
+
+    /// A random sign, a numerator below 2^numer_bits and a denominator in 1..=max_denom, each uniformly chosen
+    /// Meant for stress tests, where a spread of sizes and signs matters more than any particular distribution
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, numer_bits: u64, max_denom: &BigUint) -> Rational {
+        if *max_denom == BigUint::ZERO {
+            panic!("Rational::random with a max_denom of zero");
+        }
+
+        let sign = if rng.r#gen::<bool>() { Sign::Pos } else { Sign::Neg };
+        let numer = rng.gen_biguint(numer_bits);
+        let denom = rng.gen_biguint_below(max_denom) + 1_u8;
+
+        let sign = if numer == BigUint::ZERO { Sign::Pos } else { sign };
+
+        return Rational::new(sign, numer, denom);
+    }
+}
@@ -1,11 +1,14 @@
-use std::ops::{AddAssign, MulAssign};
-use std::fmt::{Debug, Formatter};
+use std::ops::{Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+use std::fmt::{Debug, Display, Formatter};
+use std::cmp::Ordering;
 
 use num_bigint::BigUint;
 
-use crate::rational::Rational;
+use crate::rational::{Rational, RationalError, RoundingMode};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::rational_serde::RationalRangeRepr", into = "crate::rational_serde::RationalRangeRepr"))]
 pub struct RationalRange {
     pub min: Rational,
     pub max: Rational
@@ -18,10 +21,41 @@ pub enum RationalRangeDescriptor {
 }
 use RationalRangeDescriptor::{BothPos, BothNeg, OverlapZero};
 
+/// Which way a monotone function goes, see RationalRange::map_monotone
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Increasing,
+    Decreasing
+}
+
 impl RationalRange {
+    /// Like From<(Rational, Rational)>, but returns an error instead of panicking when min > max
+    pub fn try_new(min: Rational, max: Rational) -> Result<RationalRange, RationalError> {
+        if min > max {
+            return Err(RationalError::MinAboveMax);
+        }
+
+        return Ok(RationalRange { min, max });
+    }
+
+    /// The range between a and b, whichever order they come in
+    pub fn swap_if_needed(a: Rational, b: Rational) -> RationalRange {
+        if a > b {
+            return RationalRange { min: b, max: a };
+        }
+
+        return RationalRange { min: a, max: b };
+    }
+
+    /// Rounds both endpoints onto multiples of 1/new_denom, outwards (min down and max up) so the result still contains the original range
     pub fn to_with_denominator(&mut self, new_denom: &BigUint) {
-        self.min.to_with_denominator(new_denom, false);
-        self.max.to_with_denominator(new_denom, false);
+        self.to_with_denominators(new_denom, new_denom);
+    }
+
+    /// Same as to_with_denominator, but with a different denominator for each endpoint
+    pub fn to_with_denominators(&mut self, min_denom: &BigUint, max_denom: &BigUint) {
+        self.min.to_with_denominator(min_denom, RoundingMode::Floor);
+        self.max.to_with_denominator(max_denom, RoundingMode::Ceil);
     }
 
     pub fn descriptor(&self) -> RationalRangeDescriptor {
@@ -41,25 +75,369 @@ impl RationalRange {
         }
     }
 
+    /// self * rhs as the smallest and largest of the four endpoint products, which covers every sign combination without any case analysis
+    /// This is the reference MulAssign is checked against in debug builds, it always allocates all four products
+    pub fn mul_by_endpoints(&self, rhs: &RationalRange) -> RationalRange {
+        let [a, b, c, d] = [&self.min * &rhs.min, &self.min * &rhs.max, &self.max * &rhs.min, &self.max * &rhs.max];
+
+        let (low_ab, high_ab) = if a <= b { (a, b) } else { (b, a) };
+        let (low_cd, high_cd) = if c <= d { (c, d) } else { (d, c) };
+
+        return RationalRange { min: low_ab.min(low_cd), max: high_ab.max(high_cd) };
+    }
+
+    /// Expands this range so that it also contains all of other
+    pub fn union(&mut self, other: &RationalRange) {
+        if other.min < self.min {
+            self.min = other.min.clone();
+        }
+        if other.max > self.max {
+            self.max = other.max.clone();
+        }
+    }
+
+    /// max - min, zero for a single point
+    pub fn width(&self) -> Rational {
+        return &self.max - &self.min;
+    }
+
+    /// The width relative to the smallest magnitude in the range, so a precision of n digits is a relative width below 10^-n
+    /// None if the range contains zero, where any width is infinitely large relative to zero
+    pub fn relative_width(&self) -> Option<Rational> {
+        if self.contains_zero() {
+            return None;
+        }
+
+        let smallest_magnitude = if self.min.is_negative() { self.max.abs() } else { self.min.clone() };
+
+        return Some(self.width() / smallest_magnitude);
+    }
+
+    /// Exactly halfway between min and max
+    pub fn midpoint(&self) -> Rational {
+        let mut a = self.min.clone();
+        a += &self.max;
+        a /= 2_u32;
+
+        return a;
+    }
+
+    /// (min rounded down, max rounded up) as floats, so the float interval always contains the exact one
+    /// Ends beyond the float range become infinities
+    pub fn to_f64_pair(&self) -> (f64, f64) {
+        return (self.min.to_f64_round(RoundingMode::Floor), self.max.to_f64_round(RoundingMode::Ceil));
+    }
+
+    /// How many digits after the decimal point min and max agree on (checking at most max_digits), None if even their integer parts differ
+    /// Digits are floor based like NeopreneDigitStream, so [-0.25, -0.24] agrees on 1 digit (as -1 + 0.75 and -1 + 0.76)
+    /// Every value in the range shares those digits, so Some(50) means the range pins the value down to 50 decimal places
+    pub fn agreeing_digits(&self, max_digits: usize) -> Option<usize> {
+        let agree = |digits: usize| -> bool {
+            let scale = BigUint::from(10_u8).pow(digits as u32);
+            return (&self.min * &scale).floor() == (&self.max * &scale).floor();
+        };
+
+        if !agree(0) {
+            return None;
+        }
+
+        // Agreeing on n digits means also agreeing on every smaller n, so this can binary search for the first one that disagrees
+        let mut low = 0;
+        let mut high = max_digits;
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if agree(mid) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        return Some(low);
+    }
+
+    /// Greater if the whole range is positive, Less if it's all negative, Equal if it's exactly zero and None if it contains zero along with other values
+    pub fn sign(&self) -> Option<Ordering> {
+        if self.min > 0 {
+            return Some(Ordering::Greater);
+        }
+        if self.max < 0 {
+            return Some(Ordering::Less);
+        }
+        if self.min.is_zero() && self.max.is_zero() {
+            return Some(Ordering::Equal);
+        }
+
+        return None;
+    }
+
+    /// True if the range is a single value (min == max), which means it's exact and refining it can't help
+    pub fn is_point(&self) -> bool {
+        return self.min == self.max;
+    }
+
+    /// Every value in self is below every value in other
+    pub fn certainly_lt(&self, other: &RationalRange) -> bool {
+        return self.max < other.min;
+    }
+
+    /// Every value in self is above every value in other
+    pub fn certainly_gt(&self, other: &RationalRange) -> bool {
+        return self.min > other.max;
+    }
+
+    /// Both are the same single point
+    pub fn certainly_eq(&self, other: &RationalRange) -> bool {
+        return self.is_point() && other.is_point() && self.min == other.min;
+    }
+
+    /// Some value in self is below some value in other
+    pub fn possibly_lt(&self, other: &RationalRange) -> bool {
+        return self.min < other.max;
+    }
+
+    /// Some value in self is above some value in other
+    pub fn possibly_gt(&self, other: &RationalRange) -> bool {
+        return self.max > other.min;
+    }
+
+    /// The ranges overlap, so the values they enclose could be equal
+    pub fn possibly_eq(&self, other: &RationalRange) -> bool {
+        return self.min <= other.max && other.min <= self.max;
+    }
+
+    /// True if x is in the range, including the endpoints
+    pub fn contains(&self, x: &Rational) -> bool {
+        return self.min <= *x && *x <= self.max;
+    }
+
+    /// Splits into [min, x] and [x, max], panics if x isn't in the range
+    pub fn split_at(&self, x: &Rational) -> (RationalRange, RationalRange) {
+        if !self.contains(x) {
+            panic!("Tried to split a rational range at a point outside of it");
+        }
+
+        return (
+            RationalRange { min: self.min.clone(), max: x.clone() },
+            RationalRange { min: x.clone(), max: self.max.clone() }
+        );
+    }
+
+    /// Splits at the midpoint into two halves of equal width
+    pub fn bisect(&self) -> (RationalRange, RationalRange) {
+        return self.split_at(&self.midpoint());
+    }
+
+    /// True if zero is in the range, including as one of the endpoints
+    pub fn contains_zero(&self) -> bool {
+        return self.min <= 0 && self.max >= 0;
+    }
+
+    /// self / rhs, failing if rhs contains zero (in which case the quotient isn't a single finite range, see RationalRangeSet for that)
+    pub fn try_div(&self, rhs: &RationalRange) -> Result<RationalRange, RationalError> {
+        if rhs.contains_zero() {
+            return Err(RationalError::DivisionByZero);
+        }
+
+        let mut reciprocal = rhs.clone();
+        reciprocal.reciprocate();
+
+        let mut a = self.clone();
+        a *= &reciprocal;
+
+        return Ok(a);
+    }
+
+    /// [min, max] becomes [-max, -min]
+    pub fn negate(&mut self) {
+        std::mem::swap(&mut self.min, &mut self.max);
+        self.min.negate();
+        self.max.negate();
+    }
+
+    /// The range of |x| for x in this range, which starts at zero when the range contains zero
+    pub fn abs(&self) -> RationalRange {
+        match self.descriptor() {
+            BothPos => {
+                return self.clone();
+            },
+            BothNeg => {
+                let mut a = self.clone();
+                a.negate();
+                return a;
+            },
+            OverlapZero => {
+                let min_abs = self.min.abs();
+                let max = if min_abs > self.max { min_abs } else { self.max.clone() };
+
+                return RationalRange { min: Rational::zero(), max };
+            }
+        }
+    }
+
+    /// Narrows self to the range of min(a, b) for a in self and b in other
+    pub fn pointwise_min(&mut self, other: &RationalRange) {
+        if other.min < self.min {
+            self.min = other.min.clone();
+        }
+        if other.max < self.max {
+            self.max = other.max.clone();
+        }
+    }
+
+    /// Widens self to the range of max(a, b) for a in self and b in other
+    pub fn pointwise_max(&mut self, other: &RationalRange) {
+        if other.min > self.min {
+            self.min = other.min.clone();
+        }
+        if other.max > self.max {
+            self.max = other.max.clone();
+        }
+    }
+
+    /// The values in both ranges, None if they don't overlap
+    pub fn intersect(&self, other: &RationalRange) -> Option<RationalRange> {
+        if !self.possibly_eq(other) {
+            return None;
+        }
+
+        let min = if self.min > other.min { &self.min } else { &other.min };
+        let max = if self.max < other.max { &self.max } else { &other.max };
+
+        return Some(RationalRange { min: min.clone(), max: max.clone() });
+    }
+
+    /// Narrows self, as one side of a sum that is known to lie in sum when the other side lies in other_addend
+    /// None if no value of self fits, meaning the ranges contradict each other
+    pub fn refine_addend(&self, sum: &RationalRange, other_addend: &RationalRange) -> Option<RationalRange> {
+        let mut a = sum.clone();
+        a -= other_addend;
+
+        return self.intersect(&a);
+    }
+
+    /// Narrows self, as one factor of a product that is known to lie in product when the other factor lies in other_factor
+    /// If other_factor contains zero, self could be anything as far as the product can tell, so it's returned as is
+    /// None if no value of self fits, meaning the ranges contradict each other
+    pub fn refine_factor(&self, product: &RationalRange, other_factor: &RationalRange) -> Option<RationalRange> {
+        return match product.try_div(other_factor) {
+            Ok(a) => self.intersect(&a),
+            Err(_) => Some(self.clone())
+        };
+    }
+
+    /// Multiplies both endpoints by k, swapping them when k is negative so min stays below max
+    pub fn scale(&mut self, k: &Rational) {
+        self.min *= k;
+        self.max *= k;
+
+        if k.is_negative() {
+            std::mem::swap(&mut self.min, &mut self.max);
+        }
+    }
+
+    /// Pushes min down and max up by eps, panics if eps is negative
+    pub fn widen(&mut self, eps: &Rational) {
+        if eps.is_negative() {
+            panic!("Attempted to widen RationalRange by a negative amount");
+        }
+
+        self.min -= eps;
+        self.max += eps;
+    }
+
+    /// Applies a function that is monotone over the whole range to both endpoints, putting the results in the right order for direction
+    /// f gets the point and whether its result will be the max, so functions that can't be computed exactly can round down for the min and up for the max
+    pub fn map_monotone<F>(&self, f: F, direction: Direction) -> RationalRange where F: Fn(&Rational, bool) -> Rational {
+        match direction {
+            Direction::Increasing => {
+                return RationalRange::from((f(&self.min, false), f(&self.max, true)));
+            },
+            Direction::Decreasing => {
+                return RationalRange::from((f(&self.max, false), f(&self.min, true)));
+            }
+        }
+    }
+
+    /// self^exp, with the exact power of each endpoint
+    /// Even powers of a range containing zero start at zero, and odd powers keep the sign, e.g. [-1, 2]^2 = [0, 4] and [-1, 2]^3 = [-1, 8]
+    pub fn powi(&self, exp: &BigUint) -> RationalRange {
+        return self.powi_by(exp, |x, _| {
+            let mut a = x.clone();
+            a.powi(exp);
+            return a;
+        });
+    }
+
+    /// The sign and ordering logic of powi, with powi_magnitude(x, round_up) giving x^exp for a non-negative x
+    /// (Rounded up or down as asked, for callers that limit denominators along the way)
+    pub(crate) fn powi_by<F>(&self, exp: &BigUint, powi_magnitude: F) -> RationalRange where F: Fn(&Rational, bool) -> Rational {
+        if *exp == BigUint::ZERO {
+            return RationalRange::from(Rational::one());
+        }
+
+        let exp_even = !exp.bit(0);
+
+        // Powers of negative values are done on the magnitude, and odd ones are negated afterwards (which flips the rounding direction too)
+        let signed_powi = |x: &Rational, round_up: bool| -> Rational {
+            let negate = x.is_negative() && !exp_even;
+
+            let mut a = powi_magnitude(&x.abs(), round_up ^ negate);
+            if negate {
+                a.negate();
+            }
+            return a;
+        };
+
+        match self.descriptor() {
+            OverlapZero if exp_even => {
+                // Consider (-1..2)^2, which can range between (0..4)
+                let min_pow = signed_powi(&self.min, true);
+                let max_pow = signed_powi(&self.max, true);
+
+                let max = if min_pow > max_pow { min_pow } else { max_pow };
+                return RationalRange::from((Rational::zero(), max));
+            },
+            BothNeg if exp_even => {
+                return self.map_monotone(signed_powi, Direction::Decreasing);
+            },
+            _ => {
+                // Odd powers are increasing everywhere, and even powers are increasing for positive values
+                return self.map_monotone(signed_powi, Direction::Increasing);
+            }
+        }
+    }
+
     pub fn reciprocate(&mut self) {
         match self.descriptor() {
             OverlapZero => {
                 panic!("Tried to get the reciprocal of a rational range that overlaps zero");
             },
             _ => {
-                self.min.invert();
-                self.max.invert();
-                // a > b implies (1/a) < (1/b)
-                std::mem::swap(&mut self.min, &mut self.max);
+                // 1/x is decreasing on both sides of zero
+                *self = self.map_monotone(|x, _| {
+                    let mut a = x.clone();
+                    a.invert();
+                    return a;
+                }, Direction::Decreasing);
             }
         }
     }
 }
 
+/// The alternate form ({:#?}) also shows both endpoints as floats, which is easier to read but a lot slower for big endpoints
 impl Debug for RationalRange {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> { 
-        //return write!(f, "[{:?} -> {:?}]", self.min, self.max); 
-        return write!(f, "[{:?} -> {:?}] f64 [{} -> {}]", self.min, self.max, self.min.to_float(), self.max.to_float()); 
+        if f.alternate() {
+            return write!(f, "[{:?} -> {:?}] f64 [{} -> {}]", self.min, self.max, self.min.to_float(), self.max.to_float()); 
+        }
+        return write!(f, "[{:?} -> {:?}]", self.min, self.max); 
+    }
+}
+
+impl Display for RationalRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "[{}, {}]", self.min, self.max);
     }
 }
 
@@ -76,10 +454,25 @@ impl From<(isize, isize)> for RationalRange {
     }
 }
 
+/// The range containing just this value
+impl From<Rational> for RationalRange {
+    fn from(i: Rational) -> RationalRange {
+        return RationalRange {
+            min: i.clone(),
+            max: i
+        };
+    }
+}
+
+impl From<&Rational> for RationalRange {
+    fn from(i: &Rational) -> RationalRange {
+        return RationalRange::from(i.clone());
+    }
+}
+
 impl From<(Rational, Rational)> for RationalRange {
     fn from(i: (Rational, Rational)) -> RationalRange { 
         if i.0 > i.1 {
-            println!("{:?} {:?}", i.0, i.1);
             panic!("Attempted to create RationalRange with min > max");
         }
         
@@ -97,6 +490,68 @@ impl AddAssign<&RationalRange> for RationalRange {
     }
 }
 
+impl Neg for RationalRange {
+    type Output = RationalRange;
+
+    fn neg(mut self) -> RationalRange {
+        self.negate();
+        return self;
+    }
+}
+
+impl Neg for &RationalRange {
+    type Output = RationalRange;
+
+    fn neg(self) -> RationalRange {
+        return -self.clone();
+    }
+}
+
+impl AddAssign<&Rational> for RationalRange {
+    fn add_assign(&mut self, rhs: &Rational) {
+        self.min += rhs;
+        self.max += rhs;
+    }
+}
+
+impl SubAssign<&Rational> for RationalRange {
+    fn sub_assign(&mut self, rhs: &Rational) {
+        self.min -= rhs;
+        self.max -= rhs;
+    }
+}
+
+/// Same as scale
+impl MulAssign<&Rational> for RationalRange {
+    fn mul_assign(&mut self, rhs: &Rational) {
+        self.scale(rhs);
+    }
+}
+
+impl SubAssign<&RationalRange> for RationalRange {
+    fn sub_assign(&mut self, rhs: &RationalRange) {
+        // The smallest difference is the smallest self minus the largest rhs, and the other way around
+        self.min -= &rhs.max;
+        self.max -= &rhs.min;
+    }
+}
+
+/// Panics if rhs contains zero, see try_div
+impl DivAssign<&RationalRange> for RationalRange {
+    fn div_assign(&mut self, rhs: &RationalRange) {
+        match self.try_div(rhs) {
+            Ok(a) => {
+                *self = a;
+            },
+            Err(_) => {
+                panic!("Tried to divide by a rational range that contains zero");
+            }
+        }
+    }
+}
+
+/// Picks the endpoint products from the signs of both ranges, so most cases multiply the endpoints in place
+/// Only when both ranges straddle zero are more than two products needed
 impl MulAssign<&RationalRange> for RationalRange {
     fn mul_assign(&mut self, rhs: &RationalRange) {
         match (self.descriptor(), rhs.descriptor()) {
@@ -104,60 +559,164 @@ impl MulAssign<&RationalRange> for RationalRange {
                 self.min *= &rhs.min;
                 self.max *= &rhs.max;
             },
-            (BothNeg, BothNeg) => {
-                self.min *= &rhs.min;
-                self.max *= &rhs.max;
-                std::mem::swap(&mut self.min, &mut self.max);
-            },
-
-            // Begin more complicated match cases
-
             (BothPos, BothNeg) => {
+                self.min *= &rhs.max;
+                self.max *= &rhs.min;
                 std::mem::swap(&mut self.min, &mut self.max);
-                self.min *= &rhs.min;
-                self.max *= &rhs.max;
             },
             (BothPos, OverlapZero) => {
-                self.min = self.max.clone();
+                // Only self.max is needed, so self.min's storage is reused for its second product
+                self.min.clone_from(&self.max);
                 self.min *= &rhs.min;
                 self.max *= &rhs.max;
-            }, 
-            
+            },
             (BothNeg, BothPos) => {
                 self.min *= &rhs.max;
                 self.max *= &rhs.min;
             },
+            (BothNeg, BothNeg) => {
+                self.min *= &rhs.min;
+                self.max *= &rhs.max;
+                std::mem::swap(&mut self.min, &mut self.max);
+            },
             (BothNeg, OverlapZero) => {
-                self.max = self.min.clone();
-                self.min *= &rhs.max;
+                self.max.clone_from(&self.min);
                 self.max *= &rhs.min;
+                self.min *= &rhs.max;
             },
-
             (OverlapZero, BothPos) => {
                 self.min *= &rhs.max;
                 self.max *= &rhs.max;
             },
             (OverlapZero, BothNeg) => {
-                std::mem::swap(&mut self.min, &mut self.max);
                 self.min *= &rhs.min;
                 self.max *= &rhs.min;
+                std::mem::swap(&mut self.min, &mut self.max);
             },
             (OverlapZero, OverlapZero) => {
-                let mut min_branch = self.max.clone();
-                min_branch *= &rhs.min;
-                let mut max_branch = self.min.clone();
-                max_branch *= &rhs.min;
+                // The min is one of the two negative cross products and the max one of the two positive same-side products
+                // Every endpoint is in two of those products, so two new values are the least this can do with
+                let low = &self.max * &rhs.min;
+                let high = &self.min * &rhs.min;
 
                 self.min *= &rhs.max;
-                if self.min < min_branch {
-                    self.min = min_branch;
+                self.max *= &rhs.max;
+
+                if low < self.min {
+                    self.min = low;
+                }
+                if high > self.max {
+                    self.max = high;
                 }
+            }
+        }
+    }
+}
 
-                self.max *= &rhs.max;
-                if self.max < max_branch {
-                    self.max = max_branch;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min: (isize, isize), max: (isize, isize)) -> RationalRange {
+        return RationalRange::from((Rational::from(min), Rational::from(max)));
+    }
+
+    /// Ranges of every descriptor, with zero and point ranges among them
+    fn samples() -> Vec<RationalRange> {
+        return vec![
+            range((2, 1), (3, 1)),
+            range((1, 3), (5, 2)),
+            range((0, 1), (3, 1)),
+            range((-3, 1), (-2, 1)),
+            range((-5, 2), (-1, 3)),
+            range((-3, 1), (0, 1)),
+            range((-2, 1), (3, 1)),
+            range((-7, 2), (1, 5)),
+            range((-1, 1), (1, 1)),
+            range((0, 1), (0, 1)),
+            range((3, 2), (3, 2)),
+            range((-3, 2), (-3, 2))
+        ];
+    }
+
+    fn times(a: &RationalRange, b: &RationalRange) -> RationalRange {
+        let mut product = a.clone();
+        product *= b;
+        return product;
+    }
+
+    #[test]
+    fn mul_matches_endpoint_enumeration() {
+        for a in samples() {
+            for b in samples() {
+                let expected = a.mul_by_endpoints(&b);
+                let product = times(&a, &b);
+
+                assert!(product.min == expected.min && product.max == expected.max, "{} * {} gave {}, expected {}", a, b, product, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn mul_encloses_products_of_inner_points() {
+        for a in samples() {
+            for b in samples() {
+                let product = times(&a, &b);
+
+                for i in 0..=4_u32 {
+                    for j in 0..=4_u32 {
+                        let x = &a.min + &(&a.width() * &Rational::from((i as isize, 4)));
+                        let y = &b.min + &(&b.width() * &Rational::from((j as isize, 4)));
+                        assert!(product.contains(&(&x * &y)), "{} * {} = {} doesn't contain {} * {}", a, b, product, x, y);
+                    }
                 }
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn mul_every_sign_combination() {
+        let pos = range((2, 1), (3, 1));
+        let neg = range((-3, 1), (-2, 1));
+        let overlap = range((-1, 1), (4, 1));
+
+        let cases = [
+            (&pos, &pos, (4, 9)),
+            (&pos, &neg, (-9, -4)),
+            (&pos, &overlap, (-3, 12)),
+            (&neg, &pos, (-9, -4)),
+            (&neg, &neg, (4, 9)),
+            (&neg, &overlap, (-12, 3)),
+            (&overlap, &pos, (-3, 12)),
+            (&overlap, &neg, (-12, 3)),
+            (&overlap, &overlap, (-4, 16))
+        ];
+
+        for (a, b, (min, max)) in cases {
+            let product = times(a, b);
+            assert!(product.min == min && product.max == max, "{} * {} gave {}", a, b, product);
+        }
+
+        // Both straddling zero where the negative cross product is the other one
+        let product = times(&range((-4, 1), (1, 1)), &range((-1, 1), (3, 1)));
+        assert!(product.min == -12 && product.max == 4, "{}", product);
+    }
+
+    #[test]
+    fn mul_zero_endpoints_and_points() {
+        let product = times(&range((0, 1), (3, 1)), &range((-2, 1), (5, 1)));
+        assert!(product.min == -6 && product.max == 15);
+
+        let product = times(&range((-3, 1), (0, 1)), &range((-2, 1), (5, 1)));
+        assert!(product.min == -15 && product.max == 6);
+
+        let product = times(&range((0, 1), (0, 1)), &range((-2, 1), (5, 1)));
+        assert!(product.min == 0 && product.max == 0);
+
+        let product = times(&range((-3, 2), (-3, 2)), &range((-2, 1), (5, 1)));
+        assert!(product.min == Rational::from((-15, 2)) && product.max == 3);
+
+        let product = times(&range((3, 2), (3, 2)), &range((-3, 2), (-3, 2)));
+        assert!(product.is_point() && product.min == Rational::from((-9, 4)));
+    }
+}
@@ -0,0 +1,303 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{AddAssign, MulAssign, DivAssign};
+
+use crate::rational::Rational;
+use crate::rational_range::RationalRange;
+
+/// A rational or one of the two infinities, for the unbounded ends of ranges in a RationalRangeSet
+/// The variant order makes the derived Ord the usual one
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExtendedRational {
+    NegInfinity,
+    Finite(Rational),
+    PosInfinity
+}
+
+use ExtendedRational::{NegInfinity, Finite, PosInfinity};
+
+impl ExtendedRational {
+    fn is_zero(&self) -> bool {
+        return matches!(self, Finite(x) if x.is_zero());
+    }
+
+    fn is_negative(&self) -> bool {
+        return match self {
+            NegInfinity => true,
+            Finite(x) => x.is_negative(),
+            PosInfinity => false
+        };
+    }
+
+    /// Only ever called on a min and a max (or two mins or two maxes), so the undefined inf - inf can't come up
+    fn add(&self, rhs: &ExtendedRational) -> ExtendedRational {
+        return match (self, rhs) {
+            (Finite(a), Finite(b)) => Finite(a + b),
+            (NegInfinity, _) | (_, NegInfinity) => NegInfinity,
+            _ => PosInfinity
+        };
+    }
+
+    /// 0 * inf is 0 here, which is the right convention for products of closed intervals (every finite value in one times zero is zero)
+    fn mul(&self, rhs: &ExtendedRational) -> ExtendedRational {
+        if self.is_zero() || rhs.is_zero() {
+            return Finite(Rational::zero());
+        }
+
+        return match (self, rhs) {
+            (Finite(a), Finite(b)) => Finite(a * b),
+            _ => {
+                if self.is_negative() ^ rhs.is_negative() { NegInfinity } else { PosInfinity }
+            }
+        };
+    }
+
+    /// 1/inf is 0, panics on zero
+    fn reciprocal(&self) -> ExtendedRational {
+        return match self {
+            Finite(x) => {
+                let mut a = x.clone();
+                a.invert();
+                Finite(a)
+            },
+            _ => Finite(Rational::zero())
+        };
+    }
+}
+
+impl From<Rational> for ExtendedRational {
+    fn from(x: Rational) -> ExtendedRational {
+        return Finite(x);
+    }
+}
+
+impl Debug for ExtendedRational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            NegInfinity => {
+                return write!(f, "-inf");
+            },
+            Finite(x) => {
+                return write!(f, "{:?}", x);
+            },
+            PosInfinity => {
+                return write!(f, "inf");
+            }
+        }
+    }
+}
+
+/// A RationalRange where either end can be infinite, the pieces of a RationalRangeSet
+/// min is never PosInfinity and max is never NegInfinity
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExtendedRationalRange {
+    pub min: ExtendedRational,
+    pub max: ExtendedRational
+}
+
+impl ExtendedRationalRange {
+    /// Panics if min > max or if either end is the wrong infinity
+    pub fn new(min: ExtendedRational, max: ExtendedRational) -> ExtendedRationalRange {
+        if min > max || min == PosInfinity || max == NegInfinity {
+            panic!("Attempted to create ExtendedRationalRange with min > max");
+        }
+
+        return ExtendedRationalRange { min, max };
+    }
+
+    /// Everything
+    pub fn unbounded() -> ExtendedRationalRange {
+        return ExtendedRationalRange { min: NegInfinity, max: PosInfinity };
+    }
+
+    pub fn contains(&self, x: &Rational) -> bool {
+        let x = Finite(x.clone());
+        return self.min <= x && x <= self.max;
+    }
+
+    fn contains_zero(&self) -> bool {
+        return self.contains(&Rational::zero());
+    }
+
+    /// The RationalRange with the same ends, None if either end is infinite
+    pub fn to_range(&self) -> Option<RationalRange> {
+        return match (&self.min, &self.max) {
+            (Finite(min), Finite(max)) => Some(RationalRange::from((min.clone(), max.clone()))),
+            _ => None
+        };
+    }
+
+    fn add(&self, rhs: &ExtendedRationalRange) -> ExtendedRationalRange {
+        return ExtendedRationalRange { min: self.min.add(&rhs.min), max: self.max.add(&rhs.max) };
+    }
+
+    /// The smallest and largest of the four endpoint products
+    fn mul(&self, rhs: &ExtendedRationalRange) -> ExtendedRationalRange {
+        let products = [self.min.mul(&rhs.min), self.min.mul(&rhs.max), self.max.mul(&rhs.min), self.max.mul(&rhs.max)];
+
+        let min = products.iter().min().unwrap().clone();
+        let max = products.iter().max().unwrap().clone();
+
+        return ExtendedRationalRange { min, max };
+    }
+
+    /// 1/x for every nonzero x in the range, which is two unbounded pieces when zero is strictly inside and nothing at all for [0, 0]
+    fn reciprocal(&self) -> Vec<ExtendedRationalRange> {
+        if !self.contains_zero() {
+            // Both ends have the same sign, so 1/x is decreasing across the whole range
+            return vec![ExtendedRationalRange { min: self.max.reciprocal(), max: self.min.reciprocal() }];
+        }
+
+        let mut pieces = Vec::with_capacity(2);
+        if self.min.is_negative() {
+            pieces.push(ExtendedRationalRange { min: NegInfinity, max: self.min.reciprocal() });
+        }
+        if !self.max.is_negative() && !self.max.is_zero() {
+            pieces.push(ExtendedRationalRange { min: self.max.reciprocal(), max: PosInfinity });
+        }
+
+        return pieces;
+    }
+}
+
+impl From<RationalRange> for ExtendedRationalRange {
+    fn from(range: RationalRange) -> ExtendedRationalRange {
+        return ExtendedRationalRange { min: Finite(range.min), max: Finite(range.max) };
+    }
+}
+
+impl Debug for ExtendedRationalRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "[{:?} -> {:?}]", self.min, self.max);
+    }
+}
+
+/// A union of disjoint ranges, which (unlike a single RationalRange) can soundly hold the reciprocal of a range containing zero
+/// The pieces are kept sorted, and overlapping or touching pieces are merged, so two equal sets always have the same pieces
+#[derive(Clone, PartialEq, Eq)]
+pub struct RationalRangeSet {
+    pieces: Vec<ExtendedRationalRange>
+}
+
+impl RationalRangeSet {
+    pub fn new(pieces: Vec<ExtendedRationalRange>) -> RationalRangeSet {
+        let mut a = RationalRangeSet { pieces };
+        a.normalize();
+        return a;
+    }
+
+    /// The set with no values in it
+    pub fn empty() -> RationalRangeSet {
+        return RationalRangeSet { pieces: Vec::new() };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.pieces.is_empty();
+    }
+
+    pub fn pieces(&self) -> &[ExtendedRationalRange] {
+        return &self.pieces;
+    }
+
+    pub fn contains(&self, x: &Rational) -> bool {
+        return self.pieces.iter().any(|piece| piece.contains(x));
+    }
+
+    /// The smallest single range containing every piece, None for the empty set
+    pub fn hull(&self) -> Option<ExtendedRationalRange> {
+        let first = self.pieces.first()?;
+        let last = self.pieces.last()?;
+
+        return Some(ExtendedRationalRange { min: first.min.clone(), max: last.max.clone() });
+    }
+
+    /// The hull as a RationalRange, None for the empty set or if the hull is unbounded
+    pub fn to_range(&self) -> Option<RationalRange> {
+        return self.hull()?.to_range();
+    }
+
+    pub fn union(&mut self, other: &RationalRangeSet) {
+        self.pieces.extend(other.pieces.iter().cloned());
+        self.normalize();
+    }
+
+    /// { 1/x : x in self, x != 0 }
+    pub fn reciprocal(&self) -> RationalRangeSet {
+        return RationalRangeSet::new(self.pieces.iter().flat_map(|piece| piece.reciprocal()).collect());
+    }
+
+    /// Sorts by min and merges pieces that overlap or touch
+    fn normalize(&mut self) {
+        self.pieces.sort_by(|a, b| a.min.cmp(&b.min));
+
+        let mut merged: Vec<ExtendedRationalRange> = Vec::with_capacity(self.pieces.len());
+        for piece in self.pieces.drain(..) {
+            match merged.last_mut() {
+                Some(last) if piece.min <= last.max => {
+                    if piece.max > last.max {
+                        last.max = piece.max;
+                    }
+                },
+                _ => {
+                    merged.push(piece);
+                }
+            }
+        }
+
+        self.pieces = merged;
+    }
+
+    /// Applies op to every pair of pieces and collects the results
+    fn combine(&self, rhs: &RationalRangeSet, op: fn(&ExtendedRationalRange, &ExtendedRationalRange) -> ExtendedRationalRange) -> RationalRangeSet {
+        let mut pieces = Vec::with_capacity(self.pieces.len() * rhs.pieces.len());
+
+        for a in &self.pieces {
+            for b in &rhs.pieces {
+                pieces.push(op(a, b));
+            }
+        }
+
+        return RationalRangeSet::new(pieces);
+    }
+}
+
+impl From<RationalRange> for RationalRangeSet {
+    fn from(range: RationalRange) -> RationalRangeSet {
+        return RationalRangeSet { pieces: vec![ExtendedRationalRange::from(range)] };
+    }
+}
+
+impl From<ExtendedRationalRange> for RationalRangeSet {
+    fn from(range: ExtendedRationalRange) -> RationalRangeSet {
+        return RationalRangeSet { pieces: vec![range] };
+    }
+}
+
+impl Debug for RationalRangeSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.pieces.is_empty() {
+            return write!(f, "{{}}");
+        }
+
+        let pieces: Vec<String> = self.pieces.iter().map(|piece| format!("{:?}", piece)).collect();
+        return write!(f, "{}", pieces.join(" U "));
+    }
+}
+
+impl AddAssign<&RationalRangeSet> for RationalRangeSet {
+    fn add_assign(&mut self, rhs: &RationalRangeSet) {
+        *self = self.combine(rhs, ExtendedRationalRange::add);
+    }
+}
+
+impl MulAssign<&RationalRangeSet> for RationalRangeSet {
+    fn mul_assign(&mut self, rhs: &RationalRangeSet) {
+        *self = self.combine(rhs, ExtendedRationalRange::mul);
+    }
+}
+
+/// Multiplies by the reciprocal of rhs, so dividing by a set containing zero is fine (it just gives unbounded pieces)
+impl DivAssign<&RationalRangeSet> for RationalRangeSet {
+    fn div_assign(&mut self, rhs: &RationalRangeSet) {
+        *self = self.combine(&rhs.reciprocal(), ExtendedRationalRange::mul);
+    }
+}
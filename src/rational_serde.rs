@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use num_bigint::BigUint;
+
+use crate::rational::{Rational, RationalError, Sign};
+use crate::rational_range::RationalRange;
+
+/// The serialized form of a Rational, numerator and denominator are decimal strings so they survive formats with limited integer sizes (like JSON)
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RationalRepr {
+    sign: Sign,
+    numer: String,
+    denom: String
+}
+
+impl From<Rational> for RationalRepr {
+    fn from(rational: Rational) -> RationalRepr {
+        return RationalRepr {
+            sign: rational.sign(),
+            numer: rational.numer().to_str_radix(10),
+            denom: rational.denom().to_str_radix(10)
+        };
+    }
+}
+
+/// Deserialized values go through the same checks as try_new, so a zero denominator is rejected and everything else comes out simplified
+impl TryFrom<RationalRepr> for Rational {
+    type Error = String;
+
+    fn try_from(repr: RationalRepr) -> Result<Rational, String> {
+        let numer = BigUint::parse_bytes(repr.numer.as_bytes(), 10).ok_or(format!("invalid numerator \"{}\"", repr.numer))?;
+        let denom = BigUint::parse_bytes(repr.denom.as_bytes(), 10).ok_or(format!("invalid denominator \"{}\"", repr.denom))?;
+
+        return Rational::try_new(repr.sign, numer, denom).map_err(|e: RationalError| e.to_string());
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RationalRangeRepr {
+    min: Rational,
+    max: Rational
+}
+
+impl From<RationalRange> for RationalRangeRepr {
+    fn from(range: RationalRange) -> RationalRangeRepr {
+        return RationalRangeRepr {
+            min: range.min,
+            max: range.max
+        };
+    }
+}
+
+/// Rejects min > max, which RationalRange::from would panic on
+impl TryFrom<RationalRangeRepr> for RationalRange {
+    type Error = String;
+
+    fn try_from(repr: RationalRangeRepr) -> Result<RationalRange, String> {
+        if repr.min > repr.max {
+            return Err(format!("range min {} is larger than max {}", repr.min, repr.max));
+        }
+
+        return Ok(RationalRange::from((repr.min, repr.max)));
+    }
+}